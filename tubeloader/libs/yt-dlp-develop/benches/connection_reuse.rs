@@ -0,0 +1,227 @@
+//! Compares the connections opened to download many small segments, with and without a shared HTTP client.
+//!
+//! A local HTTP/1.1 server, supporting keep-alive and range requests, counts the accepted connections:
+//! each one is a TCP handshake (and a TLS handshake against a real server).
+//!
+//! Run it with `cargo bench --bench connection_reuse`.
+
+use futures_util::{StreamExt, stream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use yt_dlp::fetcher::Fetcher;
+use yt_dlp::network::{HttpClient, NetworkConfig};
+
+/// The number of downloaded files.
+const DOWNLOADS: usize = 24;
+/// The number of files downloaded at the same time.
+const CONCURRENT_DOWNLOADS: usize = 4;
+/// The size of each file.
+const FILE_SIZE: usize = 256 * 1024;
+/// The size of each segment, small enough to send many requests per file.
+const SEGMENT_SIZE: usize = 8 * 1024;
+
+type BenchResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+#[tokio::main]
+async fn main() -> BenchResult<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}/asset.bin", listener.local_addr()?);
+    let connections = Arc::new(AtomicUsize::new(0));
+    tokio::spawn(serve(listener, connections.clone()));
+
+    let directory = std::env::temp_dir().join("yt-dlp-bench-connection-reuse");
+    let _ = tokio::fs::remove_dir_all(&directory).await;
+
+    println!(
+        "{} downloads of {} KiB in {} KiB segments, {} at a time",
+        DOWNLOADS,
+        FILE_SIZE / 1024,
+        SEGMENT_SIZE / 1024,
+        CONCURRENT_DOWNLOADS
+    );
+    println!("{:<20} {:>12} {:>12}", "client", "connections", "elapsed");
+
+    let (count, elapsed) = measure(&connections, client_per_request(&url, &directory)).await?;
+    report("one per request", count, elapsed);
+
+    let (count, elapsed) = measure(
+        &connections,
+        fetch_all(&url, &directory.join("fetcher"), None),
+    )
+    .await?;
+    report("one per download", count, elapsed);
+
+    let client = HttpClient::new(Arc::new(NetworkConfig::new()), None)?;
+    let (count, elapsed) = measure(
+        &connections,
+        fetch_all(&url, &directory.join("shared"), Some(client)),
+    )
+    .await?;
+    report("shared", count, elapsed);
+
+    let _ = tokio::fs::remove_dir_all(&directory).await;
+    Ok(())
+}
+
+/// Runs a scenario, returning the connections it opened and its duration.
+async fn measure(
+    connections: &AtomicUsize,
+    scenario: impl Future<Output = BenchResult<()>>,
+) -> BenchResult<(usize, Duration)> {
+    let before = connections.load(Ordering::SeqCst);
+    let start = Instant::now();
+
+    scenario.await?;
+
+    Ok((connections.load(Ordering::SeqCst) - before, start.elapsed()))
+}
+
+/// Prints the result of a scenario.
+fn report(name: &str, connections: usize, elapsed: Duration) {
+    println!("{:<20} {:>12} {:>12.2?}", name, connections, elapsed);
+}
+
+/// Downloads the files with fetchers, sharing the given client or building one per download.
+async fn fetch_all(url: &str, directory: &Path, client: Option<HttpClient>) -> BenchResult<()> {
+    let results: Vec<_> = stream::iter(0..DOWNLOADS)
+        .map(|index| {
+            let fetcher = Fetcher::new(url)
+                .with_segment_size(SEGMENT_SIZE)
+                .with_client(client.clone());
+            let destination = directory.join(format!("{}.bin", index));
+
+            async move { fetcher.fetch_asset(destination).await }
+        })
+        .buffer_unordered(CONCURRENT_DOWNLOADS)
+        .collect()
+        .await;
+
+    for result in results {
+        result?;
+    }
+    Ok(())
+}
+
+/// Downloads the files by building a new client for every request, like a fetcher without pool.
+async fn client_per_request(url: &str, directory: &Path) -> BenchResult<()> {
+    let results: Vec<_> = stream::iter(0..DOWNLOADS)
+        .map(|index| {
+            download_without_pool(
+                url,
+                directory.join("request").join(format!("{}.bin", index)),
+            )
+        })
+        .buffer_unordered(CONCURRENT_DOWNLOADS)
+        .collect()
+        .await;
+
+    for result in results {
+        result?;
+    }
+    Ok(())
+}
+
+/// Downloads a file segment by segment, each request with a new client.
+async fn download_without_pool(url: &str, destination: PathBuf) -> BenchResult<()> {
+    let head = reqwest::Client::new().head(url).send().await?;
+    let length: usize = head
+        .headers()
+        .get(reqwest::header::CONTENT_LENGTH)
+        .and_then(|length| length.to_str().ok()?.parse().ok())
+        .unwrap_or(0);
+
+    let mut data = Vec::with_capacity(length);
+    for start in (0..length).step_by(SEGMENT_SIZE) {
+        let end = (start + SEGMENT_SIZE).min(length) - 1;
+        let response = reqwest::Client::new()
+            .get(url)
+            .header(reqwest::header::RANGE, format!("bytes={}-{}", start, end))
+            .send()
+            .await?
+            .error_for_status()?;
+        data.extend_from_slice(&response.bytes().await?);
+    }
+
+    if let Some(parent) = destination.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(destination, data).await?;
+    Ok(())
+}
+
+/// Accepts the connections, counting them.
+async fn serve(listener: TcpListener, connections: Arc<AtomicUsize>) {
+    let body: Arc<Vec<u8>> = Arc::new((0..FILE_SIZE).map(|i| (i % 251 + 1) as u8).collect());
+
+    while let Ok((stream, _)) = listener.accept().await {
+        connections.fetch_add(1, Ordering::SeqCst);
+        tokio::spawn(handle(stream, body.clone()));
+    }
+}
+
+/// Answers the requests of a connection until it is closed, keeping it alive between them.
+async fn handle(stream: TcpStream, body: Arc<Vec<u8>>) -> std::io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+
+    loop {
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line).await? == 0 {
+            return Ok(());
+        }
+
+        let mut range = None;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).await? == 0 {
+                return Ok(());
+            }
+
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("range") {
+                    range = parse_range(value.trim(), body.len());
+                }
+            }
+        }
+
+        let mut response = match range {
+            Some((start, end)) => format!(
+                "HTTP/1.1 206 Partial Content\r\ncontent-range: bytes {}-{}/{}\r\ncontent-length: {}\r\n",
+                start,
+                end,
+                body.len(),
+                end - start + 1
+            ),
+            None => format!("HTTP/1.1 200 OK\r\ncontent-length: {}\r\n", body.len()),
+        };
+        response.push_str("accept-ranges: bytes\r\n\r\n");
+        writer.write_all(response.as_bytes()).await?;
+
+        if !request_line.starts_with("HEAD ") {
+            let content = match range {
+                Some((start, end)) => &body[start..=end],
+                None => &body[..],
+            };
+            writer.write_all(content).await?;
+        }
+    }
+}
+
+/// Parses a 'bytes=start-end' range, clamped to the body.
+fn parse_range(value: &str, length: usize) -> Option<(usize, usize)> {
+    let (start, end) = value.strip_prefix("bytes=")?.split_once('-')?;
+    let start: usize = start.parse().ok()?;
+    let end: usize = end
+        .parse()
+        .map_or(length - 1, |end: usize| end.min(length - 1));
+
+    (start <= end).then_some((start, end))
+}
@@ -0,0 +1,289 @@
+//! Download archive, to skip videos that have already been downloaded.
+//!
+//! The archive records one entry per downloaded video, keyed by the extractor and the video ID.
+//! The text mode is compatible with the file written by 'yt-dlp --download-archive',
+//! one "extractor id" line per video, so the same archive can be shared with 'yt-dlp'.
+//! With the `cache` feature, the archive can also be stored in a SQLite database.
+
+use crate::error::Result;
+use crate::model::Video;
+#[cfg(feature = "cache")]
+use rusqlite::{Connection, OpenFlags, params};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+#[cfg(feature = "cache")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The storage backend of a download archive.
+#[derive(Debug)]
+enum ArchiveBackend {
+    /// A text file in the 'yt-dlp' format.
+    Text(PathBuf),
+    /// A SQLite database.
+    #[cfg(feature = "cache")]
+    Sqlite(Mutex<Connection>),
+}
+
+/// A persistent archive of downloaded videos.
+#[derive(Debug)]
+pub struct DownloadArchive {
+    /// The path of the archive.
+    path: PathBuf,
+    /// The storage backend.
+    backend: ArchiveBackend,
+    /// The archived keys, loaded in memory for fast lookups.
+    entries: Mutex<HashSet<String>>,
+}
+
+impl DownloadArchive {
+    /// Opens a download archive, choosing the backend from the file extension.
+    /// Files ending with '.db', '.sqlite' or '.sqlite3' are opened as SQLite databases
+    /// when the `cache` feature is enabled, anything else as a 'yt-dlp' text archive.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the archive, created if it doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the archive could not be read or created.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::archive::DownloadArchive;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let archive = DownloadArchive::open("archive.txt")?;
+    ///
+    /// if !archive.contains("youtube", "dQw4w9WgXcQ") {
+    ///     // Download the video, then record it
+    ///     archive.record("youtube", "dQw4w9WgXcQ")?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        #[cfg(feature = "cache")]
+        {
+            let extension = path
+                .as_ref()
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .map(|extension| extension.to_lowercase());
+
+            if matches!(extension.as_deref(), Some("db" | "sqlite" | "sqlite3")) {
+                return Self::open_sqlite(path);
+            }
+        }
+
+        Self::open_text(path)
+    }
+
+    /// Opens a download archive in the 'yt-dlp --download-archive' text format.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the text file, created on the first record if it doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the existing file could not be read.
+    pub fn open_text(path: impl AsRef<Path>) -> Result<Self> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Opening text download archive {:?}", path.as_ref());
+
+        let path = path.as_ref().to_path_buf();
+        let mut entries = HashSet::new();
+
+        if path.exists() {
+            let content = std::fs::read_to_string(&path)?;
+            entries.extend(
+                content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(String::from),
+            );
+        }
+
+        Ok(Self {
+            path: path.clone(),
+            backend: ArchiveBackend::Text(path),
+            entries: Mutex::new(entries),
+        })
+    }
+
+    /// Opens a download archive stored in a SQLite database.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the database, created if it doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the database could not be opened or initialized.
+    #[cfg(feature = "cache")]
+    pub fn open_sqlite(path: impl AsRef<Path>) -> Result<Self> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Opening SQLite download archive {:?}", path.as_ref());
+
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let connection = Connection::open_with_flags(
+            &path,
+            OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE,
+        )?;
+
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS archive (
+                extractor TEXT NOT NULL,
+                id TEXT NOT NULL,
+                archived_at INTEGER NOT NULL,
+                PRIMARY KEY (extractor, id)
+            )",
+            [],
+        )?;
+
+        let entries = {
+            let mut stmt = connection.prepare("SELECT extractor, id FROM archive")?;
+            let rows = stmt.query_map([], |row| {
+                let extractor: String = row.get(0)?;
+                let id: String = row.get(1)?;
+                Ok(Self::key(&extractor, &id))
+            })?;
+
+            rows.collect::<std::result::Result<HashSet<_>, _>>()?
+        };
+
+        Ok(Self {
+            path,
+            backend: ArchiveBackend::Sqlite(Mutex::new(connection)),
+            entries: Mutex::new(entries),
+        })
+    }
+
+    /// Builds the archive key of a video, as written by 'yt-dlp'.
+    ///
+    /// # Arguments
+    ///
+    /// * `extractor` - The extractor key, e.g. 'Youtube'. It is lowercased.
+    /// * `id` - The ID of the video.
+    pub fn key(extractor: &str, id: &str) -> String {
+        format!("{} {}", extractor.to_lowercase(), id)
+    }
+
+    /// Returns the path of the archive.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the number of archived videos.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Returns true if no video has been archived yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Checks if a video has already been archived.
+    ///
+    /// # Arguments
+    ///
+    /// * `extractor` - The extractor key, e.g. 'youtube'.
+    /// * `id` - The ID of the video.
+    pub fn contains(&self, extractor: &str, id: &str) -> bool {
+        self.entries
+            .lock()
+            .unwrap()
+            .contains(&Self::key(extractor, id))
+    }
+
+    /// Checks if the given video has already been archived.
+    ///
+    /// # Arguments
+    ///
+    /// * `video` - The video to check.
+    pub fn contains_video(&self, video: &Video) -> bool {
+        self.contains(&video.extractor_info.extractor_key, &video.id)
+    }
+
+    /// Records a video in the archive. Recording an archived video does nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `extractor` - The extractor key, e.g. 'youtube'.
+    /// * `id` - The ID of the video.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the archive could not be written.
+    pub fn record(&self, extractor: &str, id: &str) -> Result<()> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Recording {} {} in download archive", extractor, id);
+
+        let key = Self::key(extractor, id);
+        let mut entries = self.entries.lock().unwrap();
+        if entries.contains(&key) {
+            return Ok(());
+        }
+
+        match &self.backend {
+            ArchiveBackend::Text(path) => {
+                let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+                writeln!(file, "{}", key)?;
+            }
+            #[cfg(feature = "cache")]
+            ArchiveBackend::Sqlite(connection) => {
+                let archived_at = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+
+                connection.lock().unwrap().execute(
+                    "INSERT OR IGNORE INTO archive (extractor, id, archived_at) VALUES (?, ?, ?)",
+                    params![extractor.to_lowercase(), id, archived_at],
+                )?;
+            }
+        }
+
+        entries.insert(key);
+        Ok(())
+    }
+
+    /// Records the given video in the archive.
+    ///
+    /// # Arguments
+    ///
+    /// * `video` - The downloaded video.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the archive could not be written.
+    pub fn record_video(&self, video: &Video) -> Result<()> {
+        self.record(&video.extractor_info.extractor_key, &video.id)
+    }
+}
+
+// Implementation of the Display trait for DownloadArchive
+impl fmt::Display for DownloadArchive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "DownloadArchive(path = {}, entries = {})",
+            self.path.display(),
+            self.len()
+        )
+    }
+}
@@ -4,12 +4,12 @@
 //! to avoid making repeated requests for the same videos and re-downloading the same files.
 
 use crate::error::Result;
-use crate::model::Video;
 use crate::model::format::Format;
 use crate::model::format_selector::{
     AudioCodecPreference, AudioQuality, VideoCodecPreference, VideoQuality,
 };
 use crate::model::thumbnail::Thumbnail;
+use crate::model::{Playlist, Video};
 use rusqlite::{Connection, OpenFlags, params};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -271,6 +271,18 @@ impl VideoCache {
             [],
         )?;
 
+        // Playlists are keyed by URL, since a channel can expose several tabs
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS playlists (
+                url TEXT PRIMARY KEY,
+                id TEXT NOT NULL,
+                title TEXT NOT NULL,
+                playlist_json TEXT NOT NULL,
+                cached_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
         Ok(Self {
             connection: Arc::new(Mutex::new(connection)),
             ttl: ttl.unwrap_or(24 * 60 * 60), // 24 hours by default
@@ -417,6 +429,105 @@ impl VideoCache {
             "DELETE FROM videos WHERE cached_at < ?",
             params![now - self.ttl],
         )?;
+        connection.execute(
+            "DELETE FROM playlists WHERE cached_at < ?",
+            params![now - self.ttl],
+        )?;
+
+        Ok(())
+    }
+
+    /// Retrieves a playlist from the cache by its URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the playlist to retrieve.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Some(Playlist)` if the playlist is in the cache and has not expired, otherwise `None`.
+    pub fn get_playlist(&self, url: &str) -> Option<Playlist> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Looking for playlist in cache: {}", url);
+
+        let connection = self.connection.lock().unwrap();
+
+        let (playlist_json, cached_at): (String, u64) = match connection.query_row(
+            "SELECT playlist_json, cached_at FROM playlists WHERE url = ?",
+            params![url],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ) {
+            Ok(row) => row,
+            Err(_) => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("Cache miss for playlist: {}", url);
+                return None;
+            }
+        };
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        if now - cached_at > self.ttl {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("Cache expired for playlist: {}", url);
+            return None;
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Cache hit for playlist: {}", url);
+
+        serde_json::from_str(&playlist_json).ok()
+    }
+
+    /// Puts a playlist in the cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the playlist.
+    /// * `playlist` - The playlist with all its flat entries.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the cache cannot be written to the database.
+    pub fn put_playlist(&self, url: String, playlist: &Playlist) -> Result<()> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Caching playlist: {}", url);
+
+        let playlist_json = serde_json::to_string(playlist)?;
+        let cached_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let connection = self.connection.lock().unwrap();
+
+        connection.execute(
+            "INSERT OR REPLACE INTO playlists (url, id, title, playlist_json, cached_at) VALUES (?, ?, ?, ?, ?)",
+            params![url, playlist.id, playlist.title, playlist_json, cached_at],
+        )?;
+
+        Ok(())
+    }
+
+    /// Removes a playlist from the cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the playlist to remove.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the cache cannot be written to the database.
+    pub fn remove_playlist(&self, url: &str) -> Result<()> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Removing playlist from cache: {}", url);
+
+        let connection = self.connection.lock().unwrap();
+
+        connection.execute("DELETE FROM playlists WHERE url = ?", params![url])?;
 
         Ok(())
     }
@@ -0,0 +1,94 @@
+//! The json3 caption format, YouTube's native timed text format.
+
+use super::{Cue, CueStyle, normalize_text};
+use crate::error::Result;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// A json3 document.
+#[derive(Debug, Deserialize)]
+struct Document {
+    /// The pens, the styles referenced by the events.
+    #[serde(default)]
+    pens: Vec<Pen>,
+    /// The events, either window definitions or displayed text.
+    #[serde(default)]
+    events: Vec<Event>,
+}
+
+/// A json3 pen.
+#[derive(Debug, Deserialize)]
+struct Pen {
+    #[serde(rename = "bAttr")]
+    bold: Option<u8>,
+    #[serde(rename = "iAttr")]
+    italic: Option<u8>,
+    #[serde(rename = "uAttr")]
+    underline: Option<u8>,
+    #[serde(rename = "fcForeColor")]
+    color: Option<u32>,
+}
+
+/// A json3 event.
+#[derive(Debug, Deserialize)]
+struct Event {
+    #[serde(rename = "tStartMs", default)]
+    start: u64,
+    #[serde(rename = "dDurationMs", default)]
+    duration: u64,
+    #[serde(rename = "pPenId")]
+    pen: Option<usize>,
+    #[serde(default)]
+    segs: Vec<Segment>,
+}
+
+/// A json3 text segment, usually a word of automatic captions.
+#[derive(Debug, Deserialize)]
+struct Segment {
+    #[serde(default)]
+    utf8: String,
+}
+
+/// Parses a json3 document.
+pub(crate) fn parse(content: &str) -> Result<Vec<Cue>> {
+    let document: Document = serde_json::from_str(content)?;
+
+    let cues = document
+        .events
+        .iter()
+        .filter(|event| !event.segs.is_empty())
+        .map(|event| {
+            let text: String = event.segs.iter().map(|seg| seg.utf8.as_str()).collect();
+            let start = Duration::from_millis(event.start);
+
+            Cue {
+                start,
+                end: start + Duration::from_millis(event.duration),
+                text: normalize_text(&text),
+                style: event
+                    .pen
+                    .and_then(|pen| document.pens.get(pen))
+                    .and_then(pen_style),
+            }
+        })
+        .collect();
+
+    Ok(cues)
+}
+
+/// Converts a pen to a cue style, ignoring the pens without any styling.
+fn pen_style(pen: &Pen) -> Option<CueStyle> {
+    let style = CueStyle {
+        bold: pen.bold == Some(1),
+        italic: pen.italic == Some(1),
+        underline: pen.underline == Some(1),
+        // White is the default color of captions
+        color: pen
+            .color
+            .map(|color| color & 0xFF_FF_FF)
+            .filter(|color| *color != 0xFF_FF_FF)
+            .map(|color| format!("#{:06X}", color)),
+    };
+
+    (style != CueStyle::default()).then_some(style)
+}
@@ -0,0 +1,437 @@
+//! Caption parsing and conversion, without ffmpeg.
+//!
+//! Every caption format served by YouTube (json3, srv1, srv2, srv3, ttml and vtt), as well as SRT,
+//! is parsed into a list of cues with their timing, text and optional styling.
+//! The cues can then be written as SRT, WebVTT or a plain text transcript.
+//!
+//! Automatic captions repeat the previous line in every cue, to display them as a rolling window.
+//! `Captions::merge_overlapping` removes the repeated words, so the transcript reads as plain text.
+
+use crate::error::{Error, Result};
+use crate::model::caption::Extension;
+use crate::utils::xml;
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+mod json3;
+mod srt;
+mod srv;
+mod ttml;
+mod vtt;
+
+/// The styling of a cue, when provided by the caption format.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CueStyle {
+    /// If the text is bold.
+    pub bold: bool,
+    /// If the text is italic.
+    pub italic: bool,
+    /// If the text is underlined.
+    pub underline: bool,
+    /// The color of the text, e.g. '#FFFFFF'.
+    pub color: Option<String>,
+}
+
+/// A caption cue, a text displayed between two timestamps.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Cue {
+    /// When the text is displayed.
+    pub start: Duration,
+    /// When the text is hidden.
+    pub end: Duration,
+    /// The text, with lines separated by '\n' and without any markup.
+    pub text: String,
+    /// The styling of the whole cue, if any.
+    pub style: Option<CueStyle>,
+}
+
+/// The parsed cues of a caption file, sorted by start time.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Captions {
+    /// The cues of the captions.
+    pub cues: Vec<Cue>,
+}
+
+impl Cue {
+    /// Creates a new cue without styling.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - When the text is displayed.
+    /// * `end` - When the text is hidden.
+    /// * `text` - The text of the cue.
+    pub fn new(start: Duration, end: Duration, text: impl Into<String>) -> Self {
+        Self {
+            start,
+            end,
+            text: text.into(),
+            style: None,
+        }
+    }
+
+    /// Returns the text wrapped in the SRT and WebVTT markup of its style.
+    fn styled_text(&self, text: &str, with_color: bool) -> String {
+        let Some(style) = &self.style else {
+            return text.to_string();
+        };
+
+        let mut styled = text.to_string();
+        if style.underline {
+            styled = format!("<u>{}</u>", styled);
+        }
+        if style.italic {
+            styled = format!("<i>{}</i>", styled);
+        }
+        if style.bold {
+            styled = format!("<b>{}</b>", styled);
+        }
+        if with_color && let Some(color) = &style.color {
+            styled = format!("<font color=\"{}\">{}</font>", color, styled);
+        }
+
+        styled
+    }
+}
+
+impl Captions {
+    /// Parses captions in the given format.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The content of the caption file.
+    /// * `extension` - The format of the caption file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the content is not valid for the format,
+    /// or if the format cannot be parsed, e.g. the live chat replay.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use yt_dlp::caption::Captions;
+    /// # use yt_dlp::model::caption::Extension;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let vtt = "WEBVTT\n\n00:00:01.000 --> 00:00:02.500\nHello <b>world</b>\n";
+    /// let captions = Captions::parse(vtt, &Extension::Vtt)?;
+    ///
+    /// assert_eq!(captions.cues.len(), 1);
+    /// assert_eq!(captions.cues[0].text, "Hello world");
+    /// assert_eq!(
+    ///     captions.to_srt(),
+    ///     "1\n00:00:01,000 --> 00:00:02,500\nHello world\n\n"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse(content: &str, extension: &Extension) -> Result<Self> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Parsing {} captions", extension);
+
+        let content = content.trim_start_matches('\u{feff}');
+        let mut cues = match extension {
+            Extension::Json3 => json3::parse(content)?,
+            Extension::Srv1 => srv::parse_srv1(content)?,
+            Extension::Srv2 => srv::parse_srv2(content)?,
+            Extension::Srv3 => srv::parse_srv3(content)?,
+            Extension::Ttml => ttml::parse(content)?,
+            Extension::Vtt => vtt::parse(content)?,
+            Extension::Srt => srt::parse(content)?,
+            Extension::Json | Extension::Unknown => {
+                return Err(Error::Caption(format!(
+                    "{} files cannot be parsed as captions",
+                    extension
+                )));
+            }
+        };
+
+        cues.retain(|cue| !cue.text.is_empty());
+        cues.sort_by_key(|cue| cue.start);
+
+        Ok(Self { cues })
+    }
+
+    /// Reads and parses a caption file, the format being guessed from its extension.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the caption file, e.g. 'video.en.json3'.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file could not be read,
+    /// if its extension is not a caption format, or if its content is not valid.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(Extension::from_extension)
+            .ok_or(Error::Caption(format!(
+                "unknown caption format for {}",
+                path.display()
+            )))?;
+
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(&content, &extension)
+    }
+
+    /// Merges the cues of rolling automatic captions.
+    /// The words repeated from the previous cue are removed, cues repeating the previous one
+    /// only extend it, and overlapping cues are trimmed so that only one cue is displayed at a time.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use yt_dlp::caption::{Captions, Cue};
+    /// # use std::time::Duration;
+    /// let mut captions = Captions {
+    ///     cues: vec![
+    ///         Cue::new(Duration::from_secs(0), Duration::from_secs(2), "never gonna"),
+    ///         Cue::new(Duration::from_secs(2), Duration::from_secs(4), "never gonna\ngive you up"),
+    ///         Cue::new(Duration::from_secs(4), Duration::from_secs(5), "give you up"),
+    ///     ],
+    /// };
+    ///
+    /// captions.merge_overlapping();
+    ///
+    /// assert_eq!(captions.to_text(), "never gonna\ngive you up\n");
+    /// assert_eq!(captions.cues[1].end, Duration::from_secs(5));
+    /// ```
+    pub fn merge_overlapping(&mut self) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Merging {} overlapping cues", self.cues.len());
+
+        let mut merged: Vec<Cue> = Vec::with_capacity(self.cues.len());
+
+        for mut cue in std::mem::take(&mut self.cues) {
+            let Some(previous) = merged.last_mut() else {
+                merged.push(cue);
+                continue;
+            };
+
+            if cue.start > previous.end {
+                merged.push(cue);
+                continue;
+            }
+
+            let previous_words: Vec<&str> = previous.text.split_whitespace().collect();
+            let words: Vec<&str> = cue.text.split_whitespace().collect();
+            let overlap = (1..=previous_words.len().min(words.len()))
+                .rev()
+                .find(|&count| previous_words[previous_words.len() - count..] == words[..count])
+                .unwrap_or(0);
+
+            // The cue only repeats the previous one
+            if overlap == words.len() {
+                previous.end = previous.end.max(cue.end);
+                continue;
+            }
+
+            if overlap > 0 {
+                cue.text = words[overlap..].join(" ");
+            }
+
+            // Cues starting together are displayed as one
+            if cue.start <= previous.start {
+                previous.text = format!("{}\n{}", previous.text, cue.text);
+                previous.end = previous.end.max(cue.end);
+                continue;
+            }
+
+            previous.end = previous.end.min(cue.start);
+            merged.push(cue);
+        }
+
+        self.cues = merged;
+    }
+
+    /// Writes the cues as an SRT file.
+    pub fn to_srt(&self) -> String {
+        let mut output = String::new();
+
+        for (index, cue) in self.cues.iter().enumerate() {
+            output.push_str(&format!(
+                "{}\n{} --> {}\n{}\n\n",
+                index + 1,
+                format_timestamp(cue.start, ','),
+                format_timestamp(cue.end, ','),
+                cue.styled_text(&cue.text, true)
+            ));
+        }
+
+        output
+    }
+
+    /// Writes the cues as a WebVTT file.
+    pub fn to_vtt(&self) -> String {
+        let mut output = String::from("WEBVTT\n\n");
+
+        for cue in &self.cues {
+            let text = cue
+                .text
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;");
+
+            output.push_str(&format!(
+                "{} --> {}\n{}\n\n",
+                format_timestamp(cue.start, '.'),
+                format_timestamp(cue.end, '.'),
+                cue.styled_text(&text, false)
+            ));
+        }
+
+        output
+    }
+
+    /// Writes the cues as a plain text transcript, one cue per line and without timestamps.
+    pub fn to_text(&self) -> String {
+        let mut output = String::new();
+
+        for cue in &self.cues {
+            output.push_str(&cue.text.lines().collect::<Vec<_>>().join(" "));
+            output.push('\n');
+        }
+
+        output
+    }
+
+    /// Writes the cues in the given format.
+    ///
+    /// # Arguments
+    ///
+    /// * `extension` - The format to write, either `Extension::Srt` or `Extension::Vtt`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the format cannot be written.
+    pub fn serialize(&self, extension: &Extension) -> Result<String> {
+        match extension {
+            Extension::Srt => Ok(self.to_srt()),
+            Extension::Vtt => Ok(self.to_vtt()),
+            _ => Err(Error::Caption(format!(
+                "captions cannot be written as {}",
+                extension
+            ))),
+        }
+    }
+}
+
+// Implementation of the Display trait for Cue
+impl fmt::Display for Cue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Cue(start={}, end={}, text={:?})",
+            format_timestamp(self.start, '.'),
+            format_timestamp(self.end, '.'),
+            self.text
+        )
+    }
+}
+
+// Implementation of the Display trait for Captions
+impl fmt::Display for Captions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Captions(cues={})", self.cues.len())
+    }
+}
+
+/// Formats a timestamp as 'HH:MM:SS.mmm', with the given separator before the milliseconds.
+fn format_timestamp(timestamp: Duration, separator: char) -> String {
+    let millis = timestamp.as_millis();
+
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        separator,
+        millis % 1000
+    )
+}
+
+/// Parses a clock timestamp such as '01:02:03.456', '02:03.456' or '01:02:03,456'.
+fn parse_clock(timestamp: &str) -> Option<Duration> {
+    let timestamp = timestamp.trim().replace(',', ".");
+    let mut parts = timestamp.rsplitn(3, ':');
+
+    let seconds: f64 = parts.next()?.parse().ok()?;
+    let minutes: u64 = parts.next()?.parse().ok()?;
+    let hours: u64 = match parts.next() {
+        Some(hours) => hours.parse().ok()?,
+        None => 0,
+    };
+
+    if !seconds.is_finite() || seconds < 0.0 {
+        return None;
+    }
+
+    Some(Duration::from_secs(hours * 3600 + minutes * 60) + Duration::from_secs_f64(seconds))
+}
+
+/// Normalizes the text of a cue: lines are trimmed, and empty lines are removed.
+fn normalize_text(text: &str) -> String {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Removes the markup tags of an SRT or WebVTT cue.
+/// The tags wrapping the whole cue, e.g. '<i>...</i>', are returned as its style,
+/// while the tags styling only a part of the cue are dropped.
+fn strip_tags(text: &str) -> (String, Option<CueStyle>) {
+    let mut style = CueStyle::default();
+    let mut wrapped = text.trim();
+
+    // Peel the tags wrapping the whole cue
+    while let Some(inner) = wrapped.strip_prefix('<') {
+        let Some(end) = inner.find('>') else {
+            break;
+        };
+
+        let tag = inner[..end].trim().to_lowercase();
+        let name = tag.split([' ', '.']).next().unwrap_or_default().to_string();
+        let Some(content) = inner[end + 1..].strip_suffix(&format!("</{}>", name)) else {
+            break;
+        };
+
+        match name.as_str() {
+            "b" => style.bold = true,
+            "i" => style.italic = true,
+            "u" => style.underline = true,
+            "font" => {
+                style.color = tag
+                    .split("color=")
+                    .nth(1)
+                    .map(|color| color.trim_matches(|c| c == '"' || c == '\'').to_string());
+            }
+            _ => {}
+        }
+        wrapped = content.trim();
+    }
+
+    let mut output = String::with_capacity(wrapped.len());
+    let mut rest = wrapped;
+    while let Some(start) = rest.find('<') {
+        output.push_str(&rest[..start]);
+
+        match rest[start..].find('>') {
+            Some(end) => rest = &rest[start + end + 1..],
+            None => {
+                output.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    output.push_str(rest);
+
+    let text = xml::decode_entities(&output).replace('\u{a0}', " ");
+    let style = (style != CueStyle::default()).then_some(style);
+
+    (normalize_text(&text), style)
+}
@@ -0,0 +1,42 @@
+//! The SRT (SubRip) caption format.
+
+use super::{Cue, parse_clock, strip_tags};
+use crate::error::{Error, Result};
+
+/// Parses an SRT document, with numbered cues and '00:00:01,500 --> 00:00:03,500' timings.
+pub(crate) fn parse(content: &str) -> Result<Vec<Cue>> {
+    let content = content.replace("\r\n", "\n").replace('\r', "\n");
+    let mut cues = Vec::new();
+
+    for block in content.split("\n\n") {
+        let mut lines = block.lines().skip_while(|line| line.trim().is_empty());
+
+        // The cue number is optional in practice
+        let timing = match lines.next() {
+            Some(line) if line.contains("-->") => line,
+            Some(_) => match lines.next() {
+                Some(line) if line.contains("-->") => line,
+                _ => continue,
+            },
+            None => continue,
+        };
+
+        let Some((start, end)) = timing.split_once("-->") else {
+            continue;
+        };
+        let end = end.split_whitespace().next().unwrap_or_default();
+        let (Some(start), Some(end)) = (parse_clock(start), parse_clock(end)) else {
+            return Err(Error::Caption(format!("invalid SRT timing: {}", timing)));
+        };
+
+        let (text, style) = strip_tags(&lines.collect::<Vec<_>>().join("\n"));
+        cues.push(Cue {
+            start,
+            end,
+            text,
+            style,
+        });
+    }
+
+    Ok(cues)
+}
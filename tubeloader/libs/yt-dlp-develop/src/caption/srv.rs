@@ -0,0 +1,112 @@
+//! The srv1, srv2 and srv3 caption formats, YouTube's XML timed text formats.
+
+use super::{Cue, CueStyle, normalize_text};
+use crate::error::Result;
+use crate::utils::xml::{self, XmlEvent};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Parses a srv1 document, with '<text start="1.5" dur="2.0">' elements timed in seconds.
+/// The text of srv1 is escaped twice, e.g. '&amp;#39;' for an apostrophe.
+pub(crate) fn parse_srv1(content: &str) -> Result<Vec<Cue>> {
+    let events = xml::parse(content)?;
+
+    let cues = xml::elements(&events, "text")
+        .into_iter()
+        .filter_map(|(element, text)| {
+            let start = seconds(element.attribute("start")?)?;
+            let duration = element
+                .attribute("dur")
+                .and_then(seconds)
+                .unwrap_or_default();
+
+            Some(Cue::new(
+                start,
+                start + duration,
+                normalize_text(&xml::decode_entities(&text)),
+            ))
+        })
+        .collect();
+
+    Ok(cues)
+}
+
+/// Parses a srv2 document, with '<text t="1500" d="2000">' elements timed in milliseconds.
+pub(crate) fn parse_srv2(content: &str) -> Result<Vec<Cue>> {
+    let events = xml::parse(content)?;
+
+    let cues = xml::elements(&events, "text")
+        .into_iter()
+        .filter_map(|(element, text)| {
+            let (start, end) = millis_timing(element)?;
+            Some(Cue::new(start, end, normalize_text(&text)))
+        })
+        .collect();
+
+    Ok(cues)
+}
+
+/// Parses a srv3 document, with '<p t="1500" d="2000">' paragraphs timed in milliseconds,
+/// made of '<s>' word segments, and styled by the pens defined in the head.
+pub(crate) fn parse_srv3(content: &str) -> Result<Vec<Cue>> {
+    let events = xml::parse(content)?;
+
+    let pens: HashMap<&str, CueStyle> = events
+        .iter()
+        .filter(|event| matches!(event, XmlEvent::Start { name: "pen", .. }))
+        .filter_map(|pen| Some((pen.attribute("id")?, pen_style(pen)?)))
+        .collect();
+
+    let cues = xml::elements(&events, "p")
+        .into_iter()
+        .filter_map(|(element, text)| {
+            let (start, end) = millis_timing(element)?;
+
+            Some(Cue {
+                start,
+                end,
+                text: normalize_text(&text),
+                style: element
+                    .attribute("p")
+                    .and_then(|pen| pens.get(pen))
+                    .cloned(),
+            })
+        })
+        .collect();
+
+    Ok(cues)
+}
+
+/// Reads the 't' and 'd' attributes of an element, in milliseconds.
+fn millis_timing(element: &XmlEvent) -> Option<(Duration, Duration)> {
+    let start = Duration::from_millis(element.attribute("t")?.parse().ok()?);
+    let duration = element
+        .attribute("d")
+        .and_then(|d| d.parse().ok())
+        .map(Duration::from_millis)
+        .unwrap_or_default();
+
+    Some((start, start + duration))
+}
+
+/// Parses a number of seconds, e.g. '1.5'.
+fn seconds(value: &str) -> Option<Duration> {
+    let seconds: f64 = value.parse().ok()?;
+    (seconds.is_finite() && seconds >= 0.0).then(|| Duration::from_secs_f64(seconds))
+}
+
+/// Converts a srv3 pen to a cue style, ignoring the pens without any styling.
+fn pen_style(pen: &XmlEvent) -> Option<CueStyle> {
+    let style = CueStyle {
+        bold: pen.attribute("b") == Some("1"),
+        italic: pen.attribute("i") == Some("1"),
+        underline: pen.attribute("u") == Some("1"),
+        // White is the default color of captions
+        color: pen
+            .attribute("fc")
+            .filter(|color| !color.eq_ignore_ascii_case("#FFFFFF"))
+            .map(str::to_uppercase),
+    };
+
+    (style != CueStyle::default()).then_some(style)
+}
@@ -0,0 +1,95 @@
+//! The TTML caption format, the W3C Timed Text Markup Language.
+
+use super::{Cue, CueStyle, normalize_text, parse_clock};
+use crate::error::Result;
+use crate::utils::xml::{self, XmlEvent};
+use std::time::Duration;
+
+/// The frame rate used for 'HH:MM:SS:FF' timestamps when the document does not define one.
+const DEFAULT_FRAME_RATE: f64 = 30.0;
+
+/// Parses a TTML document, with '<p begin="00:00:01.500" end="00:00:03.500">' paragraphs.
+pub(crate) fn parse(content: &str) -> Result<Vec<Cue>> {
+    let events = xml::parse(content)?;
+
+    let root = events
+        .iter()
+        .find(|event| matches!(event, XmlEvent::Start { name: "tt", .. }));
+    let frame_rate = root
+        .and_then(|root| root.attribute("frameRate"))
+        .and_then(|rate| rate.parse().ok())
+        .unwrap_or(DEFAULT_FRAME_RATE);
+    let tick_rate = root
+        .and_then(|root| root.attribute("tickRate"))
+        .and_then(|rate| rate.parse().ok())
+        .unwrap_or(1.0);
+    let rates = (frame_rate, tick_rate);
+
+    let cues = xml::elements(&events, "p")
+        .into_iter()
+        .filter_map(|(element, text)| {
+            let start = parse_time(element.attribute("begin")?, rates)?;
+            let end = match element.attribute("end") {
+                Some(end) => parse_time(end, rates)?,
+                None => start + parse_time(element.attribute("dur")?, rates)?,
+            };
+
+            Some(Cue {
+                start,
+                end,
+                text: normalize_text(&text),
+                style: style(element),
+            })
+        })
+        .collect();
+
+    Ok(cues)
+}
+
+/// Parses a TTML time expression: a clock time such as '00:00:01.500' or '00:00:01:15',
+/// or an offset time such as '1.5s', '1500ms', '2m', '1h', '15f' or '300t'.
+fn parse_time(time: &str, (frame_rate, tick_rate): (f64, f64)) -> Option<Duration> {
+    let time = time.trim();
+
+    let units = [
+        ("ms", 0.001),
+        ("h", 3600.0),
+        ("m", 60.0),
+        ("s", 1.0),
+        ("f", 1.0 / frame_rate),
+        ("t", 1.0 / tick_rate),
+    ];
+    for (unit, scale) in units {
+        if let Some(value) = time.strip_suffix(unit)
+            && let Ok(value) = value.parse::<f64>()
+        {
+            let seconds = value * scale;
+            return (seconds.is_finite() && seconds >= 0.0)
+                .then(|| Duration::from_secs_f64(seconds));
+        }
+    }
+
+    // 'HH:MM:SS:FF' clock times end with a number of frames
+    let parts: Vec<&str> = time.split(':').collect();
+    if parts.len() == 4 {
+        let frames: f64 = parts[3].parse().ok()?;
+        let clock = parse_clock(&parts[..3].join(":"))?;
+        return Some(clock + Duration::from_secs_f64(frames / frame_rate));
+    }
+
+    parse_clock(time)
+}
+
+/// Reads the inline styling attributes of a paragraph.
+fn style(element: &XmlEvent) -> Option<CueStyle> {
+    let style = CueStyle {
+        bold: element.attribute("fontWeight") == Some("bold"),
+        italic: element.attribute("fontStyle") == Some("italic"),
+        underline: element
+            .attribute("textDecoration")
+            .is_some_and(|decoration| decoration.contains("underline")),
+        color: element.attribute("color").map(String::from),
+    };
+
+    (style != CueStyle::default()).then_some(style)
+}
@@ -0,0 +1,61 @@
+//! The WebVTT caption format.
+
+use super::{Cue, parse_clock, strip_tags};
+use crate::error::{Error, Result};
+
+/// Parses a WebVTT document.
+/// The cue settings, the notes, and the style and region blocks are ignored,
+/// as well as the inline timestamps of automatic captions, e.g. '<00:00:01.500><c> word</c>'.
+pub(crate) fn parse(content: &str) -> Result<Vec<Cue>> {
+    let content = content.replace("\r\n", "\n").replace('\r', "\n");
+    if !content.starts_with("WEBVTT") {
+        return Err(Error::Caption(
+            "WebVTT files must start with 'WEBVTT'".to_string(),
+        ));
+    }
+
+    let mut cues = Vec::new();
+
+    // The first block is the header
+    for block in content.split("\n\n").skip(1) {
+        let mut lines = block.lines().skip_while(|line| line.trim().is_empty());
+
+        let Some(first) = lines.next() else {
+            continue;
+        };
+        if ["NOTE", "STYLE", "REGION"]
+            .iter()
+            .any(|keyword| first.starts_with(keyword))
+        {
+            continue;
+        }
+
+        // The cue identifier is optional
+        let timing = if first.contains("-->") {
+            first
+        } else {
+            match lines.next() {
+                Some(line) if line.contains("-->") => line,
+                _ => continue,
+            }
+        };
+
+        let Some((start, end)) = timing.split_once("-->") else {
+            continue;
+        };
+        let end = end.split_whitespace().next().unwrap_or_default();
+        let (Some(start), Some(end)) = (parse_clock(start), parse_clock(end)) else {
+            return Err(Error::Caption(format!("invalid WebVTT timing: {}", timing)));
+        };
+
+        let (text, style) = strip_tags(&lines.collect::<Vec<_>>().join("\n"));
+        cues.push(Cue {
+            start,
+            end,
+            text,
+            style,
+        });
+    }
+
+    Ok(cues)
+}
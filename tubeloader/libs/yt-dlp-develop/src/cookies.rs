@@ -0,0 +1,300 @@
+//! Cookies, to download age-restricted or members-only videos with an authenticated session.
+//!
+//! The cookies are read from a Netscape 'cookies.txt' file, as exported by browser extensions
+//! or written by 'yt-dlp --cookies'. The same file is given to 'yt-dlp', and the cookies are
+//! sent by the HTTP clients downloading the formats, thumbnails and subtitles.
+//! The values of the cookies are secrets: they are never printed, neither by `Debug` nor by `Display`.
+
+use crate::error::{Error, Result};
+use reqwest::Url;
+use reqwest::cookie::CookieStore;
+use reqwest::header::HeaderValue;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// The prefix of the lines of cookies only readable by HTTP requests, which are not comments.
+const HTTP_ONLY_PREFIX: &str = "#HttpOnly_";
+
+/// A cookie of a Netscape 'cookies.txt' file.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Cookie {
+    /// The domain of the cookie, e.g. '.youtube.com'.
+    pub domain: String,
+    /// If the cookie is also sent to the subdomains of its domain.
+    pub include_subdomains: bool,
+    /// The path of the cookie, e.g. '/'.
+    pub path: String,
+    /// If the cookie is only sent over HTTPS.
+    pub secure: bool,
+    /// The expiration date of the cookie, as a Unix timestamp, or None for session cookies.
+    pub expires: Option<i64>,
+    /// The name of the cookie.
+    pub name: String,
+    /// The value of the cookie, never printed.
+    value: String,
+}
+
+impl Cookie {
+    /// Returns the value of the cookie. Be careful not to log it.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Checks if the cookie is expired at the given date.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The date, as a Unix timestamp.
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires.is_some_and(|expires| expires < now)
+    }
+
+    /// Checks if the cookie must be sent with a request to the given URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the request.
+    pub fn matches(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let host = host.to_lowercase();
+        let domain = self.domain.trim_start_matches('.').to_lowercase();
+
+        let domain_matches =
+            host == domain || (self.include_subdomains && host.ends_with(&format!(".{}", domain)));
+        let path_matches = url.path() == self.path
+            || url.path().starts_with(&self.path)
+                && (self.path.ends_with('/') || url.path()[self.path.len()..].starts_with('/'));
+
+        domain_matches && path_matches && (!self.secure || url.scheme() == "https")
+    }
+}
+
+// Implementation of the Debug trait for Cookie, without the value
+impl fmt::Debug for Cookie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cookie")
+            .field("domain", &self.domain)
+            .field("include_subdomains", &self.include_subdomains)
+            .field("path", &self.path)
+            .field("secure", &self.secure)
+            .field("expires", &self.expires)
+            .field("name", &self.name)
+            .field("value", &"<redacted>")
+            .finish()
+    }
+}
+
+// Implementation of the Display trait for Cookie
+impl fmt::Display for Cookie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Cookie(domain = {}, name = {})", self.domain, self.name)
+    }
+}
+
+/// The cookies of a Netscape 'cookies.txt' file.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Cookies {
+    /// The path of the file, given to 'yt-dlp'.
+    path: PathBuf,
+    /// The cookies of the file.
+    cookies: Vec<Cookie>,
+}
+
+impl Cookies {
+    /// Reads the cookies of a Netscape 'cookies.txt' file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file could not be read, or if a line is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::cookies::Cookies;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let cookies = Cookies::from_file("cookies.txt")?;
+    /// println!("{} cookies loaded", cookies.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Loading cookies from {:?}", path.as_ref());
+
+        let content = std::fs::read_to_string(path.as_ref())?;
+        let cookies = Self::parse(&content)?;
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Loaded {} cookies", cookies.len());
+
+        Ok(Self {
+            path: path.as_ref().to_path_buf(),
+            cookies,
+        })
+    }
+
+    /// Parses the content of a Netscape 'cookies.txt' file.
+    /// Each line has 7 fields separated by tabs: domain, subdomains flag, path, secure flag,
+    /// expiration date, name and value. Comments and empty lines are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The content of the file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a line is invalid. The error gives the line number,
+    /// but never its content.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use yt_dlp::cookies::Cookies;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let content = "# Netscape HTTP Cookie File\n\
+    ///     .youtube.com\tTRUE\t/\tTRUE\t0\tSID\tsecret\n\
+    ///     #HttpOnly_.youtube.com\tTRUE\t/\tTRUE\t1999999999\tHSID\tsecret";
+    ///
+    /// let cookies = Cookies::parse(content)?;
+    /// assert_eq!(cookies.len(), 2);
+    /// assert_eq!(cookies[1].name, "HSID");
+    /// assert!(!format!("{:?}", cookies).contains("secret"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse(content: &str) -> Result<Vec<Cookie>> {
+        let mut cookies = Vec::new();
+
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim_end_matches(['\r', '\n']);
+            let line = line.strip_prefix(HTTP_ONLY_PREFIX).unwrap_or(line);
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = |reason: &str| Error::Cookies(format!("line {}: {}", index + 1, reason));
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [
+                domain,
+                include_subdomains,
+                path,
+                secure,
+                expires,
+                name,
+                value,
+            ] = fields[..]
+            else {
+                return Err(invalid("expected 7 fields separated by tabs"));
+            };
+
+            let expires: i64 = expires
+                .trim()
+                .parse()
+                .map_err(|_| invalid("invalid expiration date"))?;
+
+            cookies.push(Cookie {
+                domain: domain.trim().to_string(),
+                include_subdomains: parse_flag(include_subdomains)
+                    .ok_or(invalid("invalid flag"))?,
+                path: path.trim().to_string(),
+                secure: parse_flag(secure).ok_or(invalid("invalid flag"))?,
+                expires: (expires > 0).then_some(expires),
+                name: name.to_string(),
+                value: value.to_string(),
+            });
+        }
+
+        Ok(cookies)
+    }
+
+    /// Returns the path of the file, given to 'yt-dlp' with '--cookies'.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the cookies of the file.
+    pub fn cookies(&self) -> &[Cookie] {
+        &self.cookies
+    }
+
+    /// Returns the number of cookies.
+    pub fn len(&self) -> usize {
+        self.cookies.len()
+    }
+
+    /// Checks if the file has no cookies.
+    pub fn is_empty(&self) -> bool {
+        self.cookies.is_empty()
+    }
+
+    /// Builds the value of the 'Cookie' header of a request to the given URL, from the cookies
+    /// matching it and not expired. The value is marked as sensitive, so that it is not logged.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the request.
+    pub fn header_for(&self, url: &Url) -> Option<HeaderValue> {
+        let now = chrono::Utc::now().timestamp();
+        let pairs: Vec<String> = self
+            .cookies
+            .iter()
+            .filter(|cookie| !cookie.is_expired(now) && cookie.matches(url))
+            .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+            .collect();
+
+        if pairs.is_empty() {
+            return None;
+        }
+
+        let mut header = HeaderValue::from_str(&pairs.join("; ")).ok()?;
+        header.set_sensitive(true);
+        Some(header)
+    }
+}
+
+// Implementation of the Debug trait for Cookies, without the values
+impl fmt::Debug for Cookies {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cookies")
+            .field("path", &self.path)
+            .field("cookies", &self.cookies)
+            .finish()
+    }
+}
+
+// Implementation of the Display trait for Cookies
+impl fmt::Display for Cookies {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Cookies(path = {:?}, count = {})",
+            self.path,
+            self.cookies.len()
+        )
+    }
+}
+
+// Implementation of the CookieStore trait for Cookies, so that HTTP clients send them.
+// The cookies set by the responses are ignored, the file is only written by 'yt-dlp'.
+impl CookieStore for Cookies {
+    fn set_cookies(&self, _cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, _url: &Url) {}
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        self.header_for(url)
+    }
+}
+
+/// Parses a 'TRUE' or 'FALSE' flag of a Netscape cookie.
+fn parse_flag(flag: &str) -> Option<bool> {
+    match flag.trim().to_uppercase().as_str() {
+        "TRUE" => Some(true),
+        "FALSE" => Some(false),
+        _ => None,
+    }
+}
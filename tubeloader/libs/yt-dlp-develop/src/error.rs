@@ -39,6 +39,36 @@ pub enum Error {
     /// An error occurred while running a command.
     #[error("Failed to execute command: {0}")]
     Command(String),
+    /// The video is unavailable, e.g. deleted, removed for a copyright claim, or never existed.
+    #[error("Video unavailable: {0}")]
+    VideoUnavailable(String),
+    /// The video is private.
+    #[error("Private video: {0}")]
+    Private(String),
+    /// The video is age-restricted, and can only be downloaded with the cookies of an adult account.
+    #[error("Age-restricted video: {0}")]
+    AgeRestricted(String),
+    /// The video is not available in the country of the network.
+    #[error("Video not available in your country: {0}")]
+    GeoBlocked(String),
+    /// The video is reserved to the members of the channel.
+    #[error("Members-only video: {0}")]
+    MembersOnly(String),
+    /// The live stream or premiere has not started yet.
+    #[error("Live stream has not started yet: {0}")]
+    LiveNotStarted(String),
+    /// YouTube refused the requests because too many were sent.
+    #[error("Rate limited by YouTube (retry after: {retry_after:?})")]
+    RateLimited {
+        /// The time to wait before retrying, when YouTube tells it.
+        retry_after: Option<Duration>,
+    },
+    /// YouTube asks to sign in, e.g. to confirm that the requests are not sent by a bot.
+    #[error("Sign in required: {0}")]
+    SignInRequired(String),
+    /// Another error reported by 'yt-dlp'.
+    #[error("yt-dlp error: {0}")]
+    ExtractorError(String),
     /// An error occurred while fetching a video.
     #[error("Failed to fetch video: {0}")]
     Video(String),
@@ -48,6 +78,12 @@ pub enum Error {
     /// An error occurred due to a timeout.
     #[error("Operation timed out after {0:?}")]
     Timeout(Duration),
+    /// An error occurred because a process did not write any output for too long.
+    #[error("Process stalled, no output for {0:?}")]
+    IdleTimeout(Duration),
+    /// The operation was cancelled through its cancellation token.
+    #[error("Operation cancelled")]
+    Cancelled,
     /// An error occurred due to missing URL in format.
     #[error("Format {0} has no URL available")]
     MissingUrl(String),
@@ -57,9 +93,36 @@ pub enum Error {
     /// An error occurred due to incompatible format.
     #[error("Format {0} is not compatible: {1}")]
     IncompatibleFormat(String, String),
+    /// An error occurred while parsing an output template.
+    #[error("Invalid output template: {0}")]
+    Template(String),
+    /// An error occurred while parsing a format selector.
+    #[error("Invalid format selector: {0}")]
+    FormatSelector(String),
+    /// An error occurred while parsing a format sort order.
+    #[error("Invalid format sort: {0}")]
+    FormatSort(String),
+    /// An error occurred while parsing a rate limit.
+    #[error("Invalid rate limit: {0}")]
+    RateLimit(String),
+    /// An error occurred while parsing an XML document.
+    #[error("Invalid XML: {0}")]
+    Xml(String),
+    /// An error occurred while parsing an HLS playlist or a DASH manifest.
+    #[error("Invalid manifest: {0}")]
+    Manifest(String),
+    /// An error occurred while parsing a cookies file. The message never contains the cookie values.
+    #[error("Invalid cookies: {0}")]
+    Cookies(String),
+    /// An error occurred while parsing or writing captions.
+    #[error("Invalid captions: {0}")]
+    Caption(String),
     /// An error occurred due to missing thumbnail.
     #[error("No thumbnail available for video")]
     MissingThumbnail,
+    /// An error occurred due to missing chapters.
+    #[error("No chapters available for video {0}")]
+    MissingChapters(String),
 
     /// An error occurred due to missing format.
     #[error("Not found: {0}")]
@@ -69,3 +132,170 @@ pub enum Error {
     #[error("An unknown error occurred: {0}")]
     Unknown(String),
 }
+
+impl Error {
+    /// Parses the stderr of a failed 'yt-dlp' process into a typed error.
+    /// The last 'ERROR:' line is classified, with its extractor and video ID prefix removed,
+    /// and `None` is returned when there is no such line.
+    ///
+    /// # Arguments
+    ///
+    /// * `stderr` - The stderr of the process.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use yt_dlp::error::Error;
+    /// # use std::time::Duration;
+    /// // More samples are classified by tests/stderr.rs, from tests/fixtures/yt_dlp_stderr.txt
+    /// let error = Error::from_yt_dlp_stderr("ERROR: [youtube] dQw4w9WgXcQ: Video unavailable");
+    /// assert!(matches!(error, Some(Error::VideoUnavailable(_))));
+    ///
+    /// let stderr = "WARNING: [youtube] dQw4w9WgXcQ: nsig extraction failed\n\
+    ///     ERROR: [youtube] dQw4w9WgXcQ: The current session has been rate-limited by YouTube for up to an hour. \
+    ///     It is recommended to use `-t sleep` to add a delay between video requests";
+    /// assert!(matches!(
+    ///     Error::from_yt_dlp_stderr(stderr),
+    ///     Some(Error::RateLimited { retry_after: Some(delay) }) if delay == Duration::from_secs(3600)
+    /// ));
+    /// assert!(Error::from_yt_dlp_stderr("[download] 100% of 10.00MiB").is_none());
+    /// ```
+    pub fn from_yt_dlp_stderr(stderr: &str) -> Option<Self> {
+        let line = stderr
+            .lines()
+            .rev()
+            .find_map(|line| line.trim().strip_prefix("ERROR:"))?
+            .trim();
+        let message = strip_extractor_prefix(line).to_string();
+        let lower = message.to_lowercase().replace('\u{2019}', "'");
+        let contains_any =
+            |patterns: &[&str]| patterns.iter().any(|pattern| lower.contains(pattern));
+
+        let error = if contains_any(&[
+            "http error 429",
+            "too many requests",
+            "rate-limited",
+            "rate limited",
+        ]) {
+            // YouTube tells the duration of the session rate limits
+            let retry_after = lower
+                .contains("up to an hour")
+                .then_some(Duration::from_secs(3600));
+            Error::RateLimited { retry_after }
+        } else if contains_any(&["private video", "video is private"]) {
+            Error::Private(message)
+        } else if contains_any(&["members-only", "members only", "channel's members"]) {
+            Error::MembersOnly(message)
+        } else if contains_any(&[
+            "confirm your age",
+            "age-restricted",
+            "age restricted",
+            "inappropriate for some users",
+        ]) {
+            Error::AgeRestricted(message)
+        } else if contains_any(&[
+            "in your country",
+            "from your location",
+            "geo-restricted",
+            "geo restricted",
+        ]) {
+            Error::GeoBlocked(message)
+        } else if contains_any(&[
+            "not a bot",
+            "sign in",
+            "login required",
+            "requires authentication",
+        ]) {
+            Error::SignInRequired(message)
+        } else if contains_any(&[
+            "live event will begin",
+            "premieres in",
+            "premiere will begin",
+        ]) {
+            Error::LiveNotStarted(message)
+        } else if contains_any(&[
+            "video unavailable",
+            "video is unavailable",
+            "has been removed",
+            "no longer available",
+            "does not exist",
+            "has been terminated",
+        ]) {
+            Error::VideoUnavailable(message)
+        } else {
+            Error::ExtractorError(message)
+        };
+
+        Some(error)
+    }
+
+    /// Checks if the operation which failed with this error may succeed if retried later,
+    /// e.g. after a network failure or a rate limit, unlike for a private or deleted video.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use yt_dlp::error::Error;
+    /// # use std::time::Duration;
+    /// assert!(Error::RateLimited { retry_after: None }.is_retryable());
+    /// assert!(Error::Timeout(Duration::from_secs(30)).is_retryable());
+    /// assert!(Error::IdleTimeout(Duration::from_secs(60)).is_retryable());
+    /// assert!(Error::ExtractorError("Unable to download API page: HTTP Error 503".to_string()).is_retryable());
+    ///
+    /// assert!(!Error::Private("Private video".to_string()).is_retryable());
+    /// assert!(!Error::Cancelled.is_retryable());
+    /// assert!(!Error::Command("Process failed with code 1: Invalid data found".to_string()).is_retryable());
+    /// assert!(!Error::ExtractorError("Requested format is not available".to_string()).is_retryable());
+    /// assert!(!Error::ExtractorError("Unable to download webpage: HTTP Error 403: Forbidden".to_string()).is_retryable());
+    /// assert!(!Error::ExtractorError("Unable to download webpage: HTTP Error 404: Not Found".to_string()).is_retryable());
+    /// ```
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::RateLimited { .. }
+            | Error::Timeout(_)
+            | Error::IdleTimeout(_)
+            | Error::Runtime(_) => true,
+            Error::Reqwest(e) => e
+                .status()
+                .is_none_or(|status| status.is_server_error() || status.as_u16() == 429),
+            Error::IO(e) => matches!(
+                e.kind(),
+                std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::Interrupted
+                    | std::io::ErrorKind::UnexpectedEof
+            ),
+            // Only the network failures reported by 'yt-dlp' are transient, not the 403 or 404 responses
+            Error::ExtractorError(message) => {
+                let message = message.to_lowercase();
+                [
+                    "http error 5",
+                    "http error 429",
+                    "timed out",
+                    "connection",
+                    "incomplete",
+                    "temporary failure",
+                ]
+                .iter()
+                .any(|pattern| message.contains(pattern))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Removes the '[extractor] ID: ' prefix of a 'yt-dlp' error message, if any.
+fn strip_extractor_prefix(message: &str) -> &str {
+    let Some((_, rest)) = message
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once("] "))
+    else {
+        return message;
+    };
+
+    match rest.split_once(": ") {
+        Some((id, message)) if !id.contains(' ') => message,
+        _ => rest,
+    }
+}
@@ -0,0 +1,400 @@
+//! The lifecycle events of the downloads, published for library consumers.
+//!
+//! `Youtube` and its `DownloadManager` share an `EventBus`, on which every stage of a download is published:
+//! the fetching of the video information, the download of each stream, the merge, the tagging, the caching
+//! and the cleanup. Each event is keyed by the ID of its download, which is the one returned by the download manager
+//! for queued downloads, and is announced by a `Started` event for the downloads run by `Youtube`.
+
+use crate::error::Error;
+use crate::fetcher::ProgressCallback;
+use crate::fetcher::download_manager::DownloadPriority;
+use crate::postprocessor::merge::MergePlan;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::broadcast;
+
+/// The number of events kept for the subscribers which are late, before they lag behind.
+pub const DEFAULT_EVENT_CAPACITY: usize = 1024;
+/// The number of bytes downloaded between two progress events of a stream.
+const PROGRESS_STEP: u64 = 1024 * 1024;
+
+/// A stage of the lifecycle of a download.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DownloadEvent {
+    /// The download was added to the queue of the download manager.
+    Queued {
+        /// The URL to download.
+        url: String,
+        /// The destination path.
+        destination: PathBuf,
+        /// The priority of the download.
+        priority: DownloadPriority,
+    },
+    /// The download was paused, keeping its partial file.
+    Paused,
+    /// The paused download was queued again.
+    Resumed,
+    /// The download was started by `Youtube`, announcing its ID.
+    Started {
+        /// The URL of the video.
+        url: String,
+        /// The name of the output file.
+        output: String,
+    },
+    /// The information of the video is being fetched.
+    FetchingInfo {
+        /// The URL of the video.
+        url: String,
+    },
+    /// The information of the video was fetched.
+    InfoFetched {
+        /// The ID of the video.
+        video_id: String,
+        /// The title of the video.
+        title: String,
+    },
+    /// The download of a stream started.
+    StreamStarted {
+        /// The stream, e.g. the ID of its format.
+        stream: String,
+    },
+    /// Bytes of a stream were downloaded.
+    StreamProgress {
+        /// The stream, e.g. the ID of its format.
+        stream: String,
+        /// The downloaded size, in bytes.
+        downloaded_bytes: u64,
+        /// The total size, in bytes, or 0 if unknown.
+        total_bytes: u64,
+    },
+    /// The download of a stream is complete.
+    StreamCompleted {
+        /// The stream, e.g. the ID of its format.
+        stream: String,
+        /// The path of the downloaded stream.
+        path: PathBuf,
+    },
+    /// A request failed, and is retried.
+    Retrying {
+        /// The stream, e.g. the ID of its format.
+        stream: String,
+        /// The number of the failed attempt, from 1.
+        attempt: usize,
+        /// The reason of the failure.
+        reason: String,
+    },
+    /// The streams are being merged with FFmpeg.
+    Merging {
+        /// The path of the merged file.
+        output: PathBuf,
+        /// How the streams are merged: which ones are copied, and which ones are transcoded.
+        plan: MergePlan,
+    },
+    /// The metadata of the video is being written to a file.
+    Tagging {
+        /// The path of the file.
+        path: PathBuf,
+    },
+    /// A file is being stored in the download cache.
+    Caching {
+        /// The path of the file.
+        path: PathBuf,
+    },
+    /// The temporary files are being removed.
+    CleaningUp,
+    /// The download is complete.
+    Completed {
+        /// The path of the downloaded file.
+        path: PathBuf,
+    },
+    /// The download failed.
+    Failed {
+        /// The reason of the failure.
+        reason: String,
+        /// If the download may succeed if retried later.
+        retryable: bool,
+    },
+    /// The download was canceled.
+    Canceled,
+}
+
+impl DownloadEvent {
+    /// Checks if the event ends its download: no other event is published for it afterwards.
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            DownloadEvent::Completed { .. }
+                | DownloadEvent::Failed { .. }
+                | DownloadEvent::Canceled
+        )
+    }
+}
+
+// Implementation of the Display trait for DownloadEvent
+impl fmt::Display for DownloadEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DownloadEvent::Queued { url, priority, .. } => {
+                write!(f, "Queued {} with {:?} priority", url, priority)
+            }
+            DownloadEvent::Paused => write!(f, "Paused"),
+            DownloadEvent::Resumed => write!(f, "Resumed"),
+            DownloadEvent::Started { url, output } => write!(f, "Started {} into {}", url, output),
+            DownloadEvent::FetchingInfo { url } => write!(f, "Fetching information of {}", url),
+            DownloadEvent::InfoFetched { video_id, title } => {
+                write!(f, "Fetched information of {} ({})", video_id, title)
+            }
+            DownloadEvent::StreamStarted { stream } => write!(f, "Downloading stream {}", stream),
+            DownloadEvent::StreamProgress {
+                stream,
+                downloaded_bytes,
+                total_bytes,
+            } => write!(
+                f,
+                "Stream {}: {}/{} bytes",
+                stream, downloaded_bytes, total_bytes
+            ),
+            DownloadEvent::StreamCompleted { stream, path } => {
+                write!(f, "Downloaded stream {} into {:?}", stream, path)
+            }
+            DownloadEvent::Retrying {
+                stream,
+                attempt,
+                reason,
+            } => write!(
+                f,
+                "Retrying stream {} after attempt {}: {}",
+                stream, attempt, reason
+            ),
+            DownloadEvent::Merging { output, plan } => {
+                write!(f, "Merging streams into {:?} with {}", output, plan)
+            }
+            DownloadEvent::Tagging { path } => write!(f, "Writing metadata to {:?}", path),
+            DownloadEvent::Caching { path } => write!(f, "Caching {:?}", path),
+            DownloadEvent::CleaningUp => write!(f, "Removing temporary files"),
+            DownloadEvent::Completed { path } => write!(f, "Completed {:?}", path),
+            DownloadEvent::Failed { reason, .. } => write!(f, "Failed: {}", reason),
+            DownloadEvent::Canceled => write!(f, "Canceled"),
+        }
+    }
+}
+
+/// An event, with the ID of its download.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadUpdate {
+    /// The ID of the download.
+    pub download_id: u64,
+    /// The event.
+    pub event: DownloadEvent,
+}
+
+/// The channel on which the events of the downloads are published.
+///
+/// # Examples
+///
+/// ```rust
+/// # use yt_dlp::events::{DownloadEvent, EventBus};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let bus = EventBus::default();
+/// let mut receiver = bus.subscribe();
+///
+/// let emitter = bus.emitter(7);
+/// emitter.emit(DownloadEvent::CleaningUp);
+///
+/// let update = receiver.recv().await?;
+/// assert_eq!(update.download_id, 7);
+/// assert_eq!(update.event, DownloadEvent::CleaningUp);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    /// The sender of the events, cloned by each emitter.
+    sender: broadcast::Sender<DownloadUpdate>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_CAPACITY)
+    }
+}
+
+impl EventBus {
+    /// Creates a new channel, keeping the given number of events for the late subscribers.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The number of events kept, at least 1.
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Subscribes to the events published from now on.
+    /// A subscriber which does not keep up receives a `Lagged` error, and then the most recent events.
+    pub fn subscribe(&self) -> broadcast::Receiver<DownloadUpdate> {
+        self.sender.subscribe()
+    }
+
+    /// Publishes an event of a download. Nothing happens if there is no subscriber.
+    ///
+    /// # Arguments
+    ///
+    /// * `download_id` - The ID of the download.
+    /// * `event` - The event.
+    pub fn publish(&self, download_id: u64, event: DownloadEvent) {
+        #[cfg(feature = "tracing")]
+        tracing::trace!("Download {}: {}", download_id, event);
+
+        let _ = self.sender.send(DownloadUpdate { download_id, event });
+    }
+
+    /// Creates an emitter publishing the events of a download.
+    ///
+    /// # Arguments
+    ///
+    /// * `download_id` - The ID of the download.
+    pub fn emitter(&self, download_id: u64) -> EventEmitter {
+        EventEmitter {
+            bus: self.clone(),
+            download_id,
+        }
+    }
+}
+
+/// Publishes the events of a single download.
+#[derive(Debug, Clone)]
+pub struct EventEmitter {
+    /// The channel on which the events are published.
+    bus: EventBus,
+    /// The ID of the download.
+    download_id: u64,
+}
+
+impl EventEmitter {
+    /// Returns the ID of the download.
+    pub fn download_id(&self) -> u64 {
+        self.download_id
+    }
+
+    /// Publishes an event of the download.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The event.
+    pub fn emit(&self, event: DownloadEvent) {
+        self.bus.publish(self.download_id, event);
+    }
+
+    /// Publishes the final event of the download, from its result.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - The result of the download, with the path of the downloaded file.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use yt_dlp::events::{DownloadEvent, EventBus};
+    /// # use yt_dlp::error::Result;
+    /// # use std::path::PathBuf;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let bus = EventBus::default();
+    /// let mut receiver = bus.subscribe();
+    ///
+    /// let result: Result<PathBuf> = Ok(PathBuf::from("video.mp4"));
+    /// bus.emitter(1).finish(result.as_deref());
+    ///
+    /// let update = receiver.recv().await?;
+    /// assert!(update.event.is_final());
+    /// assert_eq!(update.event, DownloadEvent::Completed { path: PathBuf::from("video.mp4") });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn finish(&self, result: std::result::Result<&Path, &Error>) {
+        let event = match result {
+            Ok(path) => DownloadEvent::Completed {
+                path: path.to_path_buf(),
+            },
+            Err(Error::Cancelled) => DownloadEvent::Canceled,
+            Err(error) => DownloadEvent::Failed {
+                reason: error.to_string(),
+                retryable: error.is_retryable(),
+            },
+        };
+
+        self.emit(event);
+    }
+
+    /// Creates a progress callback publishing the progress of a stream.
+    /// The events are published every megabyte, and once the stream is complete.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The stream, e.g. the ID of its format.
+    pub fn stream_progress(&self, stream: impl AsRef<str>) -> ProgressCallback {
+        let emitter = self.clone();
+        let stream = stream.as_ref().to_string();
+        let last_reported = AtomicU64::new(0);
+
+        Arc::new(move |downloaded_bytes, total_bytes| {
+            let last = last_reported.load(Ordering::Relaxed);
+            let is_complete = total_bytes > 0 && downloaded_bytes >= total_bytes;
+            if !is_complete && downloaded_bytes >= last && downloaded_bytes - last < PROGRESS_STEP {
+                return;
+            }
+
+            last_reported.store(downloaded_bytes, Ordering::Relaxed);
+            emitter.emit(DownloadEvent::StreamProgress {
+                stream: stream.clone(),
+                downloaded_bytes,
+                total_bytes,
+            });
+        })
+    }
+}
+
+/// Publishes the events of a stream downloaded by a fetcher.
+#[derive(Debug, Clone)]
+pub(crate) struct StreamEvents {
+    /// The emitter of the events of the download.
+    emitter: EventEmitter,
+    /// The name of the stream, e.g. the ID of its format.
+    stream: String,
+}
+
+impl StreamEvents {
+    /// Creates the events of a stream of a download.
+    pub(crate) fn new(emitter: EventEmitter, stream: impl AsRef<str>) -> Self {
+        Self {
+            emitter,
+            stream: stream.as_ref().to_string(),
+        }
+    }
+
+    /// Returns a progress callback publishing the progress events, and calling the given callback if any.
+    pub(crate) fn progress(&self, callback: Option<ProgressCallback>) -> ProgressCallback {
+        let events = self.emitter.stream_progress(&self.stream);
+
+        match callback {
+            Some(callback) => Arc::new(move |downloaded, total| {
+                callback(downloaded, total);
+                events(downloaded, total);
+            }),
+            None => events,
+        }
+    }
+
+    /// Publishes the failure of an attempt which is retried.
+    pub(crate) fn retry(&self, attempt: usize, error: &Error) {
+        self.emitter.emit(DownloadEvent::Retrying {
+            stream: self.stream.clone(),
+            attempt,
+            reason: error.to_string(),
+        });
+    }
+}
@@ -1,10 +1,40 @@
 //! A tool for executing commands.
 
 use crate::error::{Error, Result};
+use futures_util::Stream;
+use std::collections::VecDeque;
+use std::fmt;
 use std::path::PathBuf;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Child;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+// Re-export of the cancellation token, to cancel streamed processes without depending on 'tokio-util'
+pub use tokio_util::sync::CancellationToken;
+
+/// The number of lines buffered between a streamed process and its consumer.
+const LINE_BUFFER: usize = 256;
+/// The number of stderr lines kept to report the error of a failed streamed process.
+const STDERR_TAIL: usize = 64;
+/// The arguments whose value is hidden from the debug output of an executor, since it can hold credentials.
+const SECRET_ARGS: [&str; 7] = [
+    "--proxy",
+    "--geo-verification-proxy",
+    "-p",
+    "--password",
+    "--video-password",
+    "--ap-password",
+    "--add-headers",
+];
 
 /// Represents a command executor.
+/// The values of the arguments and environment variables which can hold credentials, such as '--proxy',
+/// are hidden from its debug output.
 ///
 /// # Example
 ///
@@ -21,6 +51,7 @@ use std::time::Duration;
 ///     executable_path: PathBuf::from("yt-dlp"),
 ///     timeout: Duration::from_secs(30),
 ///     args: utils::to_owned(args),
+///     env: Vec::new(),
 /// };
 ///
 /// let output = executor.execute().await?;
@@ -28,7 +59,7 @@ use std::time::Duration;
 ///
 /// # Ok(())
 /// # }
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub struct Executor {
     /// The path to the command executable.
     pub executable_path: PathBuf,
@@ -37,6 +68,61 @@ pub struct Executor {
 
     /// The arguments to pass to the command.
     pub args: Vec<String>,
+    /// The environment variables to set for the process, in addition to the inherited ones.
+    pub env: Vec<(String, String)>,
+}
+
+// Implementation of the Debug trait for Executor, without the values of the secret arguments
+impl fmt::Debug for Executor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Executor")
+            .field("executable_path", &self.executable_path)
+            .field("timeout", &self.timeout)
+            .field("args", &redacted_args(&self.args))
+            .field("env", &redacted_env(&self.env))
+            .finish()
+    }
+}
+
+/// Returns the arguments, with the values of the secret ones hidden,
+/// whether they are given as a separate argument or as '--option=value'.
+fn redacted_args(args: &[String]) -> Vec<String> {
+    let mut redacted = Vec::with_capacity(args.len());
+    let mut is_secret_value = false;
+
+    for arg in args {
+        if is_secret_value {
+            redacted.push("<redacted>".to_string());
+            is_secret_value = false;
+            continue;
+        }
+
+        match arg.split_once('=') {
+            Some((name, _)) if SECRET_ARGS.contains(&name) => {
+                redacted.push(format!("{}=<redacted>", name));
+            }
+            _ => {
+                is_secret_value = SECRET_ARGS.contains(&arg.as_str());
+                redacted.push(arg.clone());
+            }
+        }
+    }
+
+    redacted
+}
+
+/// Returns the environment variables, with the values of the proxies hidden.
+fn redacted_env(env: &[(String, String)]) -> Vec<(&str, &str)> {
+    env.iter()
+        .map(|(name, value)| {
+            let name_lower = name.to_lowercase();
+            if name_lower.ends_with("_proxy") && name_lower != "no_proxy" {
+                (name.as_str(), "<redacted>")
+            } else {
+                (name.as_str(), value.as_str())
+            }
+        })
+        .collect()
 }
 
 /// Represents the output of a process.
@@ -50,12 +136,66 @@ pub struct ProcessOutput {
     pub code: i32,
 }
 
+/// Represents a line written by a streamed process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProcessLine {
+    /// A line written on stdout, without its line ending.
+    Stdout(String),
+    /// A line written on stderr, without its line ending.
+    Stderr(String),
+}
+
+impl ProcessLine {
+    /// Returns the content of the line, whichever stream it was written on.
+    pub fn text(&self) -> &str {
+        match self {
+            ProcessLine::Stdout(line) | ProcessLine::Stderr(line) => line,
+        }
+    }
+}
+
+/// Represents a running process whose output is streamed line by line.
+///
+/// The lines are yielded as they are written, through the `Stream` implementation,
+/// and `wait` returns the exit code once the process is finished.
+/// Dropping the stream does not stop the process: its cancellation token must be cancelled.
+#[derive(Debug)]
+pub struct ProcessStream {
+    /// The lines written by the process.
+    lines: mpsc::Receiver<ProcessLine>,
+    /// The task reading the output of the process and waiting for it.
+    handle: JoinHandle<Result<i32>>,
+}
+
+impl ProcessStream {
+    /// Waits for the process to finish, discarding the lines which were not read yet.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the process was cancelled, timed out or stalled,
+    /// or if it failed. As with `Executor::execute`, the error reported by 'yt-dlp' on stderr is returned as a typed error, if any.
+    pub async fn wait(self) -> Result<i32> {
+        // The reader task must not wait for lines which will never be read
+        drop(self.lines);
+        self.handle.await?
+    }
+}
+
+impl Stream for ProcessStream {
+    type Item = ProcessLine;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.lines.poll_recv(cx)
+    }
+}
+
 impl Executor {
     /// Executes the command and returns the output.
     ///
     /// # Errors
     ///
     /// This function will return an error if the command could not be executed, or if the process timed out.
+    /// When the process fails, the error reported by 'yt-dlp' on stderr is returned as a typed error, if any.
     pub async fn execute(&self) -> Result<ProcessOutput> {
         #[cfg(feature = "tracing")]
         tracing::debug!("Executing command: {:?}", self);
@@ -71,6 +211,7 @@ impl Executor {
         }
 
         command.args(&self.args);
+        command.envs(self.env.iter().map(|(name, value)| (name, value)));
         let mut child = command.spawn()?;
 
         // Continually read from stdout so that it does not fill up with large output and hang forever.
@@ -144,9 +285,243 @@ impl Executor {
             });
         }
 
+        // The errors reported by 'yt-dlp' are typed, the ones of other commands are kept as is
+        if let Some(error) = Error::from_yt_dlp_stderr(&stderr) {
+            return Err(error);
+        }
+
         Err(Error::Command(format!(
             "Process failed with code {}: {}",
             code, stderr
         )))
     }
+
+    /// Starts the command and streams its stdout and stderr lines as they are written,
+    /// e.g. to follow the progress of a 'yt-dlp' download given the '--newline' argument.
+    ///
+    /// The process runs in its own process group, which is killed with all its children when
+    /// the cancellation token is cancelled, when the total timeout of the executor is reached,
+    /// or when the process writes nothing for longer than the idle timeout.
+    ///
+    /// # Arguments
+    ///
+    /// * `cancellation` - The token cancelling the process.
+    /// * `idle_timeout` - The longest time without any output, without limit when `None`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the command could not be started.
+    /// The errors of the process itself are returned by `ProcessStream::wait`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use yt_dlp::utils;
+    /// # use std::path::PathBuf;
+    /// # use std::time::Duration;
+    /// # use futures_util::StreamExt;
+    /// # use yt_dlp::executor::{CancellationToken, Executor, ProcessLine};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let args = vec!["--newline", "-o", "video.mp4", "https://www.youtube.com/watch?v=dQw4w9WgXcQ"];
+    ///
+    /// let executor = Executor {
+    ///     executable_path: PathBuf::from("yt-dlp"),
+    ///     timeout: Duration::from_secs(3600),
+    ///     args: utils::to_owned(args),
+    ///     env: Vec::new(),
+    /// };
+    ///
+    /// // Give up the download after ten minutes
+    /// let cancellation = CancellationToken::new();
+    /// let token = cancellation.clone();
+    /// tokio::spawn(async move {
+    ///     tokio::time::sleep(Duration::from_secs(600)).await;
+    ///     token.cancel();
+    /// });
+    ///
+    /// let mut stream = executor.execute_streaming(cancellation, Some(Duration::from_secs(120)))?;
+    /// while let Some(line) = stream.next().await {
+    ///     if let ProcessLine::Stdout(line) = line {
+    ///         println!("{}", line);
+    ///     }
+    /// }
+    ///
+    /// let code = stream.wait().await?;
+    /// println!("Exited with code {}", code);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn execute_streaming(
+        &self,
+        cancellation: CancellationToken,
+        idle_timeout: Option<Duration>,
+    ) -> Result<ProcessStream> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Executing streamed command: {:?}", self);
+
+        let mut command = tokio::process::Command::new(&self.executable_path);
+        command.stdout(std::process::Stdio::piped());
+        command.stderr(std::process::Stdio::piped());
+        command.kill_on_drop(true);
+
+        // The process leads its own group, so that its children are killed with it
+        #[cfg(unix)]
+        command.process_group(0);
+
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            command.creation_flags(0x08000000);
+        }
+
+        command.args(&self.args);
+        command.envs(self.env.iter().map(|(name, value)| (name, value)));
+        let mut child = command.spawn()?;
+
+        let stdout_handle = child
+            .stdout
+            .take()
+            .ok_or_else(|| Error::Command("Failed to capture stdout".to_string()))?;
+        let stderr_handle = child
+            .stderr
+            .take()
+            .ok_or_else(|| Error::Command("Failed to capture stderr".to_string()))?;
+
+        let (sender, lines) = mpsc::channel(LINE_BUFFER);
+        let timeout = self.timeout;
+        let handle = tokio::spawn(async move {
+            let result = stream_process(
+                &mut child,
+                (stdout_handle, stderr_handle),
+                sender,
+                &cancellation,
+                (timeout, idle_timeout),
+            )
+            .await;
+
+            if result.is_err() {
+                kill_process_group(&mut child).await;
+            }
+            result
+        });
+
+        Ok(ProcessStream { lines, handle })
+    }
+}
+
+/// Forwards the output lines of a process until it exits, enforcing its cancellation and timeouts.
+async fn stream_process(
+    child: &mut Child,
+    (stdout, stderr): (impl AsyncRead + Unpin, impl AsyncRead + Unpin),
+    sender: mpsc::Sender<ProcessLine>,
+    cancellation: &CancellationToken,
+    (timeout, idle_timeout): (Duration, Option<Duration>),
+) -> Result<i32> {
+    let deadline = Instant::now() + timeout;
+    let mut last_output = Instant::now();
+
+    // The lines are split on '\n' only, and decoded lossily, as progress lines may be cut
+    let mut stdout = BufReader::new(stdout).split(b'\n');
+    let mut stderr = BufReader::new(stderr).split(b'\n');
+    let (mut stdout_done, mut stderr_done) = (false, false);
+    let mut stderr_tail = VecDeque::with_capacity(STDERR_TAIL);
+
+    while !stdout_done || !stderr_done {
+        let idle_deadline = idle_timeout.map(|idle_timeout| last_output + idle_timeout);
+
+        let line = tokio::select! {
+            _ = cancellation.cancelled() => return Err(Error::Cancelled),
+            _ = tokio::time::sleep_until(deadline) => return Err(Error::Timeout(timeout)),
+            _ = tokio::time::sleep_until(idle_deadline.unwrap_or(deadline)), if idle_deadline.is_some() => {
+                return Err(Error::IdleTimeout(idle_timeout.unwrap_or_default()));
+            }
+            line = stdout.next_segment(), if !stdout_done => match line? {
+                Some(line) => ProcessLine::Stdout(decode_line(line)),
+                None => {
+                    stdout_done = true;
+                    continue;
+                }
+            },
+            line = stderr.next_segment(), if !stderr_done => match line? {
+                Some(line) => ProcessLine::Stderr(decode_line(line)),
+                None => {
+                    stderr_done = true;
+                    continue;
+                }
+            },
+        };
+        last_output = Instant::now();
+
+        if let ProcessLine::Stderr(line) = &line {
+            if stderr_tail.len() == STDERR_TAIL {
+                stderr_tail.pop_front();
+            }
+            stderr_tail.push_back(line.clone());
+        }
+
+        // The consumer may have stopped reading, the output is still drained to not block the process
+        let _ = sender.send(line).await;
+    }
+
+    let exit_status = tokio::select! {
+        _ = cancellation.cancelled() => return Err(Error::Cancelled),
+        _ = tokio::time::sleep_until(deadline) => return Err(Error::Timeout(timeout)),
+        status = child.wait() => status?,
+    };
+
+    let code = exit_status.code().unwrap_or(-1);
+    if exit_status.success() {
+        return Ok(code);
+    }
+
+    let stderr = Vec::from(stderr_tail).join("\n");
+    if let Some(error) = Error::from_yt_dlp_stderr(&stderr) {
+        return Err(error);
+    }
+
+    Err(Error::Command(format!(
+        "Process failed with code {}: {}",
+        code, stderr
+    )))
+}
+
+/// Decodes an output line, removing the carriage return of Windows line endings.
+fn decode_line(mut line: Vec<u8>) -> String {
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+
+    String::from_utf8_lossy(&line).into_owned()
+}
+
+/// Kills a process and all the processes of its group, then waits for it to exit.
+async fn kill_process_group(child: &mut Child) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!("Killing process {:?} and its children", child.id());
+
+    if let Some(pid) = child.id() {
+        #[cfg(unix)]
+        {
+            // The process group has the ID of its leader, a negative ID targets the whole group
+            // SAFETY: 'kill' has no memory safety requirements
+            let _ = unsafe { libc::kill(-(pid as libc::pid_t), libc::SIGKILL) };
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+
+            let _ = tokio::process::Command::new("taskkill")
+                .args(["/PID", &pid.to_string(), "/T", "/F"])
+                .creation_flags(0x08000000)
+                .status()
+                .await;
+        }
+    }
+
+    if let Err(_e) = child.kill().await {
+        #[cfg(feature = "tracing")]
+        tracing::error!("Failed to kill process: {}", _e);
+    }
 }
@@ -2,10 +2,12 @@
 
 use crate::error::{Error, Result};
 use crate::fetcher::deps::{Asset, WantedRelease};
+use crate::network::{HttpClient, NetworkConfig};
 use crate::utils::file_system;
 use crate::utils::platform::{Architecture, Platform};
 use std::fmt;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// URL templates for FFmpeg builds based on platform and architecture
 #[derive(Debug, Clone)]
@@ -64,7 +66,12 @@ struct Extraction {
 /// # }
 /// ```
 #[derive(Clone, Debug, Default)]
-pub struct BuildFetcher;
+pub struct BuildFetcher {
+    /// The network configuration of the download.
+    network: Arc<NetworkConfig>,
+    /// The HTTP client shared by the downloads, if any.
+    client: Option<HttpClient>,
+}
 
 impl fmt::Display for BuildFetcher {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -75,7 +82,35 @@ impl fmt::Display for BuildFetcher {
 impl BuildFetcher {
     /// Create a new fetcher for ffmpeg.
     pub fn new() -> Self {
-        Self
+        Self {
+            network: Arc::default(),
+            client: None,
+        }
+    }
+
+    /// Configures the proxy, certificates, timeouts and user agent of the download.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The network configuration.
+    pub fn with_network(mut self, network: Arc<NetworkConfig>) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Sends the requests through a shared HTTP client, reusing its connections.
+    /// Its network configuration replaces the one of the fetcher.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The shared client, or None to build a client for each request.
+    pub fn with_client(mut self, client: Option<HttpClient>) -> Self {
+        if let Some(client) = &client {
+            self.network = client.network().clone();
+        }
+
+        self.client = client;
+        self
     }
 
     /// Fetch the ffmpeg binary for the current platform and architecture.
@@ -114,6 +149,8 @@ impl BuildFetcher {
         Ok(WantedRelease {
             url: asset.download_url.clone(),
             name: asset.name.clone(),
+            network: self.network.clone(),
+            client: self.client.clone(),
         })
     }
 
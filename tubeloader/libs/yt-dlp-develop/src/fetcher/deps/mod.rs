@@ -4,12 +4,14 @@ use crate::error::Result;
 use crate::fetcher::Fetcher;
 use crate::fetcher::deps::ffmpeg::BuildFetcher;
 use crate::fetcher::deps::youtube::GitHubFetcher;
+use crate::network::{HttpClient, NetworkConfig};
 use crate::utils::file_system;
 use crate::{ternary, utils};
 use derive_more::Constructor;
 use serde::Deserialize;
 use std::fmt;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 pub mod ffmpeg;
 pub mod youtube;
@@ -31,10 +33,14 @@ pub mod youtube;
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Constructor, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct LibraryInstaller {
     /// The destination directory for the libraries.
     pub destination: PathBuf,
+    /// The network configuration of the downloads.
+    pub network: Arc<NetworkConfig>,
+    /// The HTTP client shared by the downloads, if any.
+    pub client: Option<HttpClient>,
 }
 
 /// The installed libraries.
@@ -64,6 +70,44 @@ pub struct Libraries {
 }
 
 impl LibraryInstaller {
+    /// Create a new installer, downloading the libraries to the given directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - The destination directory for the libraries.
+    pub fn new(destination: PathBuf) -> Self {
+        Self {
+            destination,
+            network: Arc::default(),
+            client: None,
+        }
+    }
+
+    /// Configures the proxy, certificates, timeouts and user agent of the downloads.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The network configuration.
+    pub fn with_network(mut self, network: Arc<NetworkConfig>) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Sends the requests through a shared HTTP client, reusing its connections.
+    /// Its network configuration replaces the one of the installer.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The shared client, or None to build a client for each request.
+    pub fn with_client(mut self, client: Option<HttpClient>) -> Self {
+        if let Some(client) = &client {
+            self.network = client.network().clone();
+        }
+
+        self.client = client;
+        self
+    }
+
     /// Install yt-dlp from the main repository.
     pub async fn install_youtube(&self, custom_name: Option<String>) -> Result<PathBuf> {
         self.install_youtube_from_repo("yt-dlp", "yt-dlp", None, custom_name)
@@ -88,7 +132,9 @@ impl LibraryInstaller {
 
         file_system::create_dir(self.destination.clone())?;
 
-        let fetcher = GitHubFetcher::new(owner, repo);
+        let fetcher = GitHubFetcher::new(owner, repo)
+            .with_network(self.network.clone())
+            .with_client(self.client.clone());
 
         let name = custom_name.unwrap_or(String::from("yt-dlp"));
         let path = self.destination.join(utils::find_executable(&name));
@@ -109,7 +155,9 @@ impl LibraryInstaller {
 
         file_system::create_dir(self.destination.clone())?;
 
-        let fetcher = BuildFetcher::new();
+        let fetcher = BuildFetcher::new()
+            .with_network(self.network.clone())
+            .with_client(self.client.clone());
         let archive = self.destination.join("ffmpeg-release.zip");
 
         let release = fetcher.fetch_binary().await?;
@@ -221,6 +269,10 @@ pub struct WantedRelease {
     pub url: String,
     /// The name of the release asset.
     pub name: String,
+    /// The network configuration of the download.
+    pub network: Arc<NetworkConfig>,
+    /// The HTTP client shared by the downloads, if any.
+    pub client: Option<HttpClient>,
 }
 
 impl fmt::Display for WantedRelease {
@@ -264,7 +316,9 @@ impl WantedRelease {
             destination.as_ref().display()
         );
 
-        let fetcher = Fetcher::new(&self.url);
+        let fetcher = Fetcher::new(&self.url)
+            .with_network(self.network.clone())
+            .with_client(self.client.clone());
         fetcher.fetch_asset(destination).await
     }
 }
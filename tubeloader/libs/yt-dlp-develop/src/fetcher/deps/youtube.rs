@@ -3,9 +3,11 @@
 use crate::error::{Error, Result};
 use crate::fetcher::Fetcher;
 use crate::fetcher::deps::{Asset, Release, WantedRelease};
+use crate::network::{HttpClient, NetworkConfig};
 use crate::utils::platform::Architecture;
 use crate::utils::platform::Platform;
 use std::fmt;
+use std::sync::Arc;
 
 const BASE_ASSET_NAME: &str = "yt-dlp";
 
@@ -32,6 +34,10 @@ pub struct GitHubFetcher {
     owner: String,
     /// The name of the GitHub repository.
     repo: String,
+    /// The network configuration of the requests.
+    network: Arc<NetworkConfig>,
+    /// The HTTP client shared by the requests, if any.
+    client: Option<HttpClient>,
 }
 
 impl fmt::Display for GitHubFetcher {
@@ -51,9 +57,36 @@ impl GitHubFetcher {
         Self {
             owner: owner.as_ref().to_string(),
             repo: repo.as_ref().to_string(),
+            network: Arc::default(),
+            client: None,
         }
     }
 
+    /// Configures the proxy, certificates, timeouts and user agent of the requests.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The network configuration.
+    pub fn with_network(mut self, network: Arc<NetworkConfig>) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Sends the requests through a shared HTTP client, reusing its connections.
+    /// Its network configuration replaces the one of the fetcher.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The shared client, or None to build a client for each request.
+    pub fn with_client(mut self, client: Option<HttpClient>) -> Self {
+        if let Some(client) = &client {
+            self.network = client.network().clone();
+        }
+
+        self.client = client;
+        self
+    }
+
     /// Fetch the latest release for the current platform.
     ///
     /// # Arguments
@@ -107,6 +140,8 @@ impl GitHubFetcher {
         Ok(WantedRelease {
             name: asset.name.clone(),
             url: asset.download_url.clone(),
+            network: self.network.clone(),
+            client: self.client.clone(),
         })
     }
 
@@ -124,7 +159,9 @@ impl GitHubFetcher {
             self.owner, self.repo
         );
 
-        let fetcher = Fetcher::new(&url);
+        let fetcher = Fetcher::new(&url)
+            .with_network(self.network.clone())
+            .with_client(self.client.clone());
         let response = fetcher.fetch_json(auth_token).await?;
 
         let release: Release = serde_json::from_value(response)?;
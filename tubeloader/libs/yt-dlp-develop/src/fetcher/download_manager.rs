@@ -3,20 +3,31 @@
 //! This module provides a download manager that allows:
 //! - Limiting the number of concurrent downloads
 //! - Managing a download queue with priorities
+//! - Pausing, resuming and reprioritizing downloads, and changing the limit, while the queue runs
 //! - Resuming interrupted downloads
+//! - Persisting the queue, to restore it after a crash or a reboot
+//! - Limiting the bandwidth of all the downloads, and of each download
+//! - Sharing one HTTP client between the downloads, to reuse their connections
 //! - Optimizing memory usage
 
+use crate::cookies::Cookies;
 use crate::error::Result;
+use crate::events::{DownloadEvent, DownloadUpdate, EventBus};
 use crate::fetcher::Fetcher;
-use std::cmp::Ordering;
+use crate::fetcher::queue_store::{QueueStore, QueuedDownload};
+use crate::network::{HttpClient, NetworkConfig};
+use crate::rate_limit::RateLimiter;
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, min};
 use std::collections::{BinaryHeap, HashMap};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use tokio::sync::{Mutex, Semaphore};
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, RwLock};
+use tokio::sync::{Mutex, OwnedSemaphorePermit, Semaphore, broadcast};
 use tokio::task::JoinHandle;
 
 /// Download priority
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DownloadPriority {
     /// Low priority
     Low = 0,
@@ -42,6 +53,7 @@ impl DownloadPriority {
 }
 
 /// Download task
+#[derive(Clone)]
 struct DownloadTask {
     /// URL to download
     url: String,
@@ -54,6 +66,8 @@ struct DownloadTask {
     /// Progress callback
     #[allow(clippy::type_complexity)]
     progress_callback: Option<Arc<dyn Fn(u64, u64) + Send + Sync>>,
+    /// Bandwidth limiter of this download, kept when the download is paused and resumed
+    rate_limiter: Arc<RateLimiter>,
 }
 
 impl std::fmt::Debug for DownloadTask {
@@ -63,6 +77,7 @@ impl std::fmt::Debug for DownloadTask {
             .field("destination", &self.destination)
             .field("priority", &self.priority)
             .field("id", &self.id)
+            .field("rate_limit", &self.rate_limiter.rate())
             .field(
                 "progress_callback",
                 &format_args!(
@@ -78,6 +93,40 @@ impl std::fmt::Debug for DownloadTask {
     }
 }
 
+/// A download task in progress
+struct RunningTask {
+    /// The task, queued again when the download is resumed after a pause
+    task: DownloadTask,
+    /// The handle of the download
+    handle: JoinHandle<Result<()>>,
+}
+
+/// The limit of concurrent downloads, which can be changed while the queue runs
+#[derive(Debug)]
+struct ConcurrencyLimit {
+    /// Maximum number of concurrent downloads
+    max: usize,
+    /// Number of permits to forget once released by the downloads in progress, after the limit was lowered
+    excess: usize,
+}
+
+/// The permit of a download in progress, released when the download finishes or is aborted
+struct DownloadPermit {
+    /// The permit, taken when released
+    permit: Option<OwnedSemaphorePermit>,
+    /// The limit of concurrent downloads, whose excess is applied on release
+    concurrency: Arc<std::sync::Mutex<ConcurrencyLimit>>,
+}
+
+// Implementation of the Drop trait for DownloadPermit, also run when the download is aborted by cancel or pause
+impl Drop for DownloadPermit {
+    fn drop(&mut self) {
+        if let Some(permit) = self.permit.take() {
+            release(permit, &self.concurrency);
+        }
+    }
+}
+
 impl PartialEq for DownloadTask {
     fn eq(&self, other: &Self) -> bool {
         self.id == other.id
@@ -118,6 +167,10 @@ pub struct ManagerConfig {
     pub retry_attempts: usize,
     /// Maximum buffer size per download (in bytes)
     pub max_buffer_size: usize,
+    /// Bandwidth limit shared by all the downloads (in bytes per second), None for unlimited
+    pub rate_limit: Option<u64>,
+    /// Bandwidth limit of each download (in bytes per second), None for unlimited
+    pub download_rate_limit: Option<u64>,
 }
 
 impl Default for ManagerConfig {
@@ -128,12 +181,14 @@ impl Default for ManagerConfig {
             parallel_segments: 4,
             retry_attempts: 3,
             max_buffer_size: 1024 * 1024 * 10, // 10 MB
+            rate_limit: None,
+            download_rate_limit: None,
         }
     }
 }
 
 /// Download status
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DownloadStatus {
     /// Queued
     Queued,
@@ -153,6 +208,8 @@ pub enum DownloadStatus {
     },
     /// Download canceled
     Canceled,
+    /// Download paused, its partial file and downloaded segments are kept to resume it
+    Paused,
 }
 
 /// Download manager
@@ -163,22 +220,36 @@ pub struct DownloadManager {
     queue: Arc<Mutex<BinaryHeap<DownloadTask>>>,
     /// Semaphore to limit the number of concurrent downloads
     semaphore: Arc<Semaphore>,
+    /// The current limit of concurrent downloads
+    concurrency: Arc<std::sync::Mutex<ConcurrencyLimit>>,
     /// Counter to generate unique IDs
     next_id: Arc<Mutex<u64>>,
     /// Download statuses
     statuses: Arc<Mutex<HashMap<u64, DownloadStatus>>>,
     /// Download tasks in progress
-    tasks: Arc<Mutex<HashMap<u64, JoinHandle<Result<()>>>>>,
+    tasks: Arc<Mutex<HashMap<u64, RunningTask>>>,
+    /// Paused download tasks
+    paused: Arc<Mutex<HashMap<u64, DownloadTask>>>,
+    /// The cookies sent with the requests of the downloads
+    cookies: Arc<RwLock<Option<Arc<Cookies>>>>,
+    /// The network configuration of the downloads
+    network: Arc<RwLock<Arc<NetworkConfig>>>,
+    /// The HTTP client shared by the downloads, built on the first download if not given
+    client: Arc<RwLock<Option<HttpClient>>>,
+    /// The channel on which the download events are published
+    events: EventBus,
+    /// The persistent storage of the queue, if enabled
+    store: Option<Arc<QueueStore>>,
+    /// Bandwidth limiter shared by all the downloads
+    rate_limiter: Arc<RateLimiter>,
 }
 
 impl std::fmt::Debug for DownloadManager {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("DownloadManager")
             .field("config", &self.config)
-            .field(
-                "max_concurrent_downloads",
-                &self.config.max_concurrent_downloads,
-            )
+            .field("max_concurrent_downloads", &self.max_concurrent())
+            .field("rate_limit", &self.rate_limiter.rate())
             .finish_non_exhaustive()
     }
 }
@@ -201,9 +272,323 @@ impl DownloadManager {
             config: config.clone(),
             queue: Arc::new(Mutex::new(BinaryHeap::new())),
             semaphore: Arc::new(Semaphore::new(config.max_concurrent_downloads)),
+            concurrency: Arc::new(std::sync::Mutex::new(ConcurrencyLimit {
+                max: config.max_concurrent_downloads,
+                excess: 0,
+            })),
             next_id: Arc::new(Mutex::new(0)),
             statuses: Arc::new(Mutex::new(HashMap::new())),
             tasks: Arc::new(Mutex::new(HashMap::new())),
+            paused: Arc::new(Mutex::new(HashMap::new())),
+            cookies: Arc::new(RwLock::new(None)),
+            network: Arc::new(RwLock::new(Arc::default())),
+            client: Arc::new(RwLock::new(None)),
+            events: EventBus::default(),
+            store: None,
+            rate_limiter: Arc::new(RateLimiter::new(config.rate_limit)),
+        }
+    }
+
+    /// Create a new download manager with custom configuration, recording its queue in a persistent store.
+    /// The queue recorded by a previous run is restored, and its unfinished downloads are resumed
+    /// from the segments already downloaded, with the priority and bandwidth limit they had.
+    ///
+    /// The queue is recorded in a SQLite database with the `cache` feature, and in a JSON file otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The download manager configuration
+    /// * `path` - The path of the file where the queue is recorded
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the queue cannot be opened or read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::fetcher::download_manager::{DownloadManager, ManagerConfig};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let manager = DownloadManager::with_persistence(ManagerConfig::default(), "output/queue.db").await?;
+    ///
+    /// // The downloads of the previous run are resumed, and the new ones survive a restart
+    /// let id = manager.enqueue("https://example.com/video.mp4", "output/video.mp4", None).await;
+    /// manager.wait_for_completion(id).await;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn with_persistence(
+        config: ManagerConfig,
+        path: impl AsRef<Path> + std::fmt::Debug,
+    ) -> Result<Self> {
+        let manager = Self::restore(config, path)?;
+        manager.process_queue();
+
+        Ok(manager)
+    }
+
+    /// Create a new download manager with the queue restored from a persistent store, without starting it
+    pub(crate) fn restore(
+        config: ManagerConfig,
+        path: impl AsRef<Path> + std::fmt::Debug,
+    ) -> Result<Self> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Restoring download queue from {:?}", path);
+
+        let store = QueueStore::open(path)?;
+        let downloads = store.load()?;
+
+        let mut queue = BinaryHeap::new();
+        let mut paused = HashMap::new();
+        let mut statuses = HashMap::new();
+        for download in &downloads {
+            let task = DownloadTask {
+                url: download.url.clone(),
+                destination: download.destination.clone(),
+                priority: download.priority,
+                id: download.id,
+                progress_callback: None,
+                rate_limiter: Arc::new(RateLimiter::new(download.rate_limit)),
+            };
+
+            if download.status == DownloadStatus::Paused {
+                // The download stays paused until it is resumed
+                paused.insert(download.id, task);
+                statuses.insert(download.id, DownloadStatus::Paused);
+            } else if download.is_unfinished() {
+                // The download is queued again, and resumed from its segments already downloaded
+                queue.push(task);
+                statuses.insert(download.id, DownloadStatus::Queued);
+                store.update_status(download.id, &DownloadStatus::Queued)?;
+            } else {
+                statuses.insert(download.id, download.status.clone());
+            }
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            "Restored {} downloads, {} to resume",
+            downloads.len(),
+            queue.len()
+        );
+
+        let next_id = downloads.iter().map(|download| download.id + 1).max();
+
+        let mut manager = Self::with_config(config);
+        manager.queue = Arc::new(Mutex::new(queue));
+        manager.paused = Arc::new(Mutex::new(paused));
+        manager.statuses = Arc::new(Mutex::new(statuses));
+        manager.next_id = Arc::new(Mutex::new(next_id.unwrap_or(0)));
+        manager.store = Some(Arc::new(store));
+
+        Ok(manager)
+    }
+
+    /// Get the download manager configuration
+    pub fn config(&self) -> &ManagerConfig {
+        &self.config
+    }
+
+    /// Get the current maximum number of concurrent downloads
+    pub fn max_concurrent(&self) -> usize {
+        self.concurrency
+            .lock()
+            .map(|limit| limit.max)
+            .unwrap_or(self.config.max_concurrent_downloads)
+    }
+
+    /// Set the maximum number of concurrent downloads, while the queue runs.
+    /// When the limit is lowered, the downloads in progress are not interrupted,
+    /// and no download is started until they are below the new limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `max` - The maximum number of concurrent downloads, at least 1
+    pub fn set_max_concurrent(&self, max: usize) {
+        let max = max.max(1);
+        let Ok(mut limit) = self.concurrency.lock() else {
+            return;
+        };
+
+        if max > limit.max {
+            // The permits still owed by the downloads in progress are kept instead of added
+            let added = max - limit.max;
+            let kept = min(added, limit.excess);
+            limit.excess -= kept;
+            self.semaphore.add_permits(added - kept);
+        } else {
+            // The available permits are removed now, and the others once released by the downloads in progress
+            let removed = limit.max - max;
+            let forgotten = self.semaphore.forget_permits(removed);
+            limit.excess += removed - forgotten;
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Limiting downloads to {} concurrent downloads", max);
+
+        limit.max = max;
+    }
+
+    /// Get the bandwidth limiter shared by all the downloads
+    pub fn rate_limiter(&self) -> &Arc<RateLimiter> {
+        &self.rate_limiter
+    }
+
+    /// Set the bandwidth limit shared by all the downloads, applied to the downloads in progress too
+    ///
+    /// # Arguments
+    ///
+    /// * `rate` - The limit in bytes per second, or None for unlimited
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::fetcher::download_manager::{DownloadManager, ManagerConfig};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config = ManagerConfig {
+    ///     rate_limit: Some(10 * 1024 * 1024),
+    ///     download_rate_limit: Some(2 * 1024 * 1024),
+    ///     ..ManagerConfig::default()
+    /// };
+    /// let manager = DownloadManager::with_config(config);
+    /// let id = manager.enqueue("https://example.com/video.mp4", "output/video.mp4", None).await;
+    ///
+    /// // Lower the limits while downloading
+    /// manager.set_rate_limit(Some(5 * 1024 * 1024));
+    /// manager.set_download_rate_limit(id, Some(512 * 1024)).await;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_rate_limit(&self, rate: Option<u64>) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Limiting the bandwidth of the downloads to {:?} B/s", rate);
+
+        self.rate_limiter.set_rate(rate);
+    }
+
+    /// Share a bandwidth limiter with the downloads, e.g. the one of the manager this one replaces
+    pub(crate) fn set_rate_limiter(&mut self, rate_limiter: Arc<RateLimiter>) {
+        self.rate_limiter = rate_limiter;
+    }
+
+    /// Set the bandwidth limit of a download, queued, paused or in progress
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the download
+    /// * `rate` - The limit in bytes per second, or None for unlimited
+    ///
+    /// # Returns
+    ///
+    /// true if the limit was changed, false if the download doesn't exist or is already finished
+    pub async fn set_download_rate_limit(&self, id: u64, rate: Option<u64>) -> bool {
+        let limiter = {
+            let tasks = self.tasks.lock().await;
+            tasks
+                .get(&id)
+                .map(|running| running.task.rate_limiter.clone())
+        };
+
+        let limiter = match limiter {
+            Some(limiter) => Some(limiter),
+            None => {
+                let paused = self.paused.lock().await;
+                paused.get(&id).map(|task| task.rate_limiter.clone())
+            }
+        };
+
+        let limiter = match limiter {
+            Some(limiter) => Some(limiter),
+            None => {
+                let queue = self.queue.lock().await;
+                queue
+                    .iter()
+                    .find(|task| task.id == id)
+                    .map(|task| task.rate_limiter.clone())
+            }
+        };
+
+        match limiter {
+            Some(limiter) => {
+                limiter.set_rate(rate);
+                persist(&self.store, |store| store.update_rate_limit(id, rate));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Get the channel on which the download events are published
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// Subscribe to the events of the downloads, published from now on
+    ///
+    /// # Returns
+    ///
+    /// A receiver of the events, keyed by download ID
+    pub fn subscribe(&self) -> broadcast::Receiver<DownloadUpdate> {
+        self.events.subscribe()
+    }
+
+    /// Generate a new unique download ID, shared by the queued downloads and the ones run by `Youtube`
+    pub(crate) async fn next_id(&self) -> u64 {
+        let mut id_guard = self.next_id.lock().await;
+        let id = *id_guard;
+        *id_guard += 1;
+        id
+    }
+
+    /// Set the cookies sent with the requests of the downloads started from now on
+    ///
+    /// # Arguments
+    ///
+    /// * `cookies` - The cookies, or None to send no cookies
+    pub fn set_cookies(&self, cookies: Option<Arc<Cookies>>) {
+        if let Ok(mut current) = self.cookies.write() {
+            *current = cookies;
+        }
+
+        // The shared client is built again with the new cookies
+        if let Ok(mut client) = self.client.write() {
+            *client = None;
+        }
+    }
+
+    /// Set the network configuration of the downloads started from now on
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The proxy, certificates, timeouts and user agent of the downloads
+    pub fn set_network(&self, network: Arc<NetworkConfig>) {
+        if let Ok(mut current) = self.network.write() {
+            *current = network;
+        }
+
+        // The shared client is built again with the new configuration
+        if let Ok(mut client) = self.client.write() {
+            *client = None;
+        }
+    }
+
+    /// Set the HTTP client shared by the downloads started from now on, e.g. the one of `Youtube`.
+    /// Its network configuration and cookies replace the ones of the manager.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The shared client
+    pub fn set_client(&self, client: HttpClient) {
+        if let Ok(mut current) = self.cookies.write() {
+            *current = client.cookies().cloned();
+        }
+        if let Ok(mut current) = self.network.write() {
+            *current = client.network().clone();
+        }
+        if let Ok(mut current) = self.client.write() {
+            *current = Some(client);
         }
     }
 
@@ -224,10 +609,7 @@ impl DownloadManager {
         destination: impl AsRef<Path>,
         priority: Option<DownloadPriority>,
     ) -> u64 {
-        let mut id_guard = self.next_id.lock().await;
-        let id = *id_guard;
-        *id_guard += 1;
-        drop(id_guard);
+        let id = self.next_id().await;
 
         let task = DownloadTask {
             url: url.as_ref().to_string(),
@@ -235,22 +617,9 @@ impl DownloadManager {
             priority: priority.unwrap_or(DownloadPriority::Normal),
             id,
             progress_callback: None,
+            rate_limiter: Arc::new(RateLimiter::new(self.config.download_rate_limit)),
         };
-
-        // Add the task to the queue
-        {
-            let mut queue = self.queue.lock().await;
-            queue.push(task);
-        }
-
-        // Update status
-        {
-            let mut statuses = self.statuses.lock().await;
-            statuses.insert(id, DownloadStatus::Queued);
-        }
-
-        // Start the queue processor
-        self.process_queue();
+        self.add_task(task).await;
 
         id
     }
@@ -277,10 +646,7 @@ impl DownloadManager {
     where
         F: Fn(u64, u64) + Send + Sync + 'static,
     {
-        let mut id_guard = self.next_id.lock().await;
-        let id = *id_guard;
-        *id_guard += 1;
-        drop(id_guard);
+        let id = self.next_id().await;
 
         let task = DownloadTask {
             url: url.as_ref().to_string(),
@@ -288,7 +654,31 @@ impl DownloadManager {
             priority: priority.unwrap_or(DownloadPriority::Normal),
             id,
             progress_callback: Some(Arc::new(progress_callback)),
+            rate_limiter: Arc::new(RateLimiter::new(self.config.download_rate_limit)),
+        };
+        self.add_task(task).await;
+
+        id
+    }
+
+    /// Add a task to the queue, record it, and start the queue processor
+    async fn add_task(&self, task: DownloadTask) {
+        let id = task.id;
+        let event = DownloadEvent::Queued {
+            url: task.url.clone(),
+            destination: task.destination.clone(),
+            priority: task.priority,
         };
+        persist(&self.store, |store| {
+            store.save(&QueuedDownload {
+                id,
+                url: task.url.clone(),
+                destination: task.destination.clone(),
+                priority: task.priority,
+                rate_limit: task.rate_limiter.rate(),
+                status: DownloadStatus::Queued,
+            })
+        });
 
         // Add the task to the queue
         {
@@ -301,11 +691,10 @@ impl DownloadManager {
             let mut statuses = self.statuses.lock().await;
             statuses.insert(id, DownloadStatus::Queued);
         }
+        self.events.publish(id, event);
 
         // Start the queue processor
         self.process_queue();
-
-        id
     }
 
     /// Get the status of a download
@@ -333,49 +722,278 @@ impl DownloadManager {
     /// true if the download was canceled, false if it doesn't exist or is already completed
     pub async fn cancel(&self, id: u64) -> bool {
         // Check if the download is in progress
-        let task_handle = {
+        let running = {
             let mut tasks = self.tasks.lock().await;
             tasks.remove(&id)
         };
 
-        // If the download is in progress, cancel it
-        if let Some(handle) = task_handle {
-            handle.abort();
+        // If the download is in progress, cancel it and wait for it to stop, so that it records no status afterwards
+        if let Some(running) = running {
+            running.handle.abort();
+            wait_aborted(running.handle).await;
 
             // Update status
             let mut statuses = self.statuses.lock().await;
             statuses.insert(id, DownloadStatus::Canceled);
+            persist(&self.store, |store| {
+                store.update_status(id, &DownloadStatus::Canceled)
+            });
+            self.events.publish(id, DownloadEvent::Canceled);
 
             return true;
         }
 
+        // Check if the download is in the queue, or paused
+        let removed_from_queue =
+            self.take_queued(id).await.is_some() || self.paused.lock().await.remove(&id).is_some();
+
+        if removed_from_queue {
+            // Update status
+            let mut statuses = self.statuses.lock().await;
+            statuses.insert(id, DownloadStatus::Canceled);
+            persist(&self.store, |store| {
+                store.update_status(id, &DownloadStatus::Canceled)
+            });
+            self.events.publish(id, DownloadEvent::Canceled);
+            return true;
+        }
+
+        false
+    }
+
+    /// Pause a download, queued or in progress.
+    /// The partial file and the downloaded segments of a download in progress are kept,
+    /// so that it continues from them once resumed.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the download to pause
+    ///
+    /// # Returns
+    ///
+    /// true if the download was paused, false if it doesn't exist or is already finished or paused
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::fetcher::download_manager::{DownloadManager, DownloadPriority};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let manager = DownloadManager::new();
+    /// let id = manager.enqueue("https://example.com/video.mp4", "output/video.mp4", None).await;
+    ///
+    /// manager.pause(id).await;
+    /// manager.set_priority(id, DownloadPriority::High).await;
+    /// manager.resume(id).await;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn pause(&self, id: u64) -> bool {
+        // Check if the download is in progress
+        let running = {
+            let mut tasks = self.tasks.lock().await;
+            tasks.remove(&id)
+        };
+
+        if let Some(running) = running {
+            // Stop the download, its segments downloaded so far are recorded in its parts file,
+            // and wait for it so that it writes nothing once paused
+            running.handle.abort();
+            wait_aborted(running.handle).await;
+
+            // The download may have finished before being stopped
+            let statuses = self.statuses.lock().await;
+            if !matches!(statuses.get(&id), Some(DownloadStatus::Downloading { .. })) {
+                return false;
+            }
+            drop(statuses);
+
+            self.mark_paused(running.task).await;
+            return true;
+        }
+
         // Check if the download is in the queue
-        let removed_from_queue = {
+        match self.take_queued(id).await {
+            Some(task) => {
+                self.mark_paused(task).await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Resume a paused download, which is queued again with its priority
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the download to resume
+    ///
+    /// # Returns
+    ///
+    /// true if the download was resumed, false if it doesn't exist or is not paused
+    pub async fn resume(&self, id: u64) -> bool {
+        let task = {
+            let mut paused = self.paused.lock().await;
+            paused.remove(&id)
+        };
+
+        match task {
+            Some(task) => {
+                self.requeue(task).await;
+                self.process_queue();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Pause all the downloads, queued or in progress
+    ///
+    /// # Returns
+    ///
+    /// The number of downloads paused
+    pub async fn pause_all(&self) -> usize {
+        // The queued downloads are paused first, so that none of them starts meanwhile
+        let queued = {
             let mut queue = self.queue.lock().await;
-            let len_before = queue.len();
+            queue.drain().collect::<Vec<_>>()
+        };
+        let mut count = queued.len();
+        for task in queued {
+            self.mark_paused(task).await;
+        }
 
-            // Create a new queue without the task to cancel
-            let mut new_queue = BinaryHeap::new();
-            for task in queue.drain() {
-                if task.id != id {
-                    new_queue.push(task);
-                }
+        let running = {
+            let tasks = self.tasks.lock().await;
+            tasks.keys().copied().collect::<Vec<_>>()
+        };
+        for id in running {
+            if self.pause(id).await {
+                count += 1;
             }
+        }
 
-            // Replace the queue
-            *queue = new_queue;
+        count
+    }
 
-            len_before > queue.len()
+    /// Resume all the paused downloads
+    ///
+    /// # Returns
+    ///
+    /// The number of downloads resumed
+    pub async fn resume_all(&self) -> usize {
+        let tasks = {
+            let mut paused = self.paused.lock().await;
+            paused.drain().map(|(_, task)| task).collect::<Vec<_>>()
         };
 
-        if removed_from_queue {
-            // Update status
-            let mut statuses = self.statuses.lock().await;
-            statuses.insert(id, DownloadStatus::Canceled);
-            return true;
+        let count = tasks.len();
+        for task in tasks {
+            self.requeue(task).await;
         }
 
-        false
+        if count > 0 {
+            self.process_queue();
+        }
+
+        count
+    }
+
+    /// Change the priority of a queued or paused download
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the download
+    /// * `priority` - The new priority
+    ///
+    /// # Returns
+    ///
+    /// true if the priority was changed, false if the download doesn't exist or is already started or finished
+    pub async fn set_priority(&self, id: u64, priority: DownloadPriority) -> bool {
+        let updated = {
+            let mut queue = self.queue.lock().await;
+
+            // The queue is rebuilt, to order the task with its new priority
+            let mut tasks = std::mem::take(&mut *queue).into_vec();
+            let task = tasks.iter_mut().find(|task| task.id == id);
+            let found = task.is_some();
+            if let Some(task) = task {
+                task.priority = priority;
+            }
+            *queue = BinaryHeap::from(tasks);
+
+            found
+        };
+
+        let updated = updated || {
+            let mut paused = self.paused.lock().await;
+            match paused.get_mut(&id) {
+                Some(task) => {
+                    task.priority = priority;
+                    true
+                }
+                None => false,
+            }
+        };
+
+        if updated {
+            persist(&self.store, |store| store.update_priority(id, priority));
+        }
+
+        updated
+    }
+
+    /// Remove a task from the queue
+    async fn take_queued(&self, id: u64) -> Option<DownloadTask> {
+        let mut queue = self.queue.lock().await;
+
+        // Create a new queue without the task to remove
+        let mut taken = None;
+        let mut new_queue = BinaryHeap::new();
+        for task in queue.drain() {
+            if task.id == id {
+                taken = Some(task);
+            } else {
+                new_queue.push(task);
+            }
+        }
+
+        // Replace the queue
+        *queue = new_queue;
+
+        taken
+    }
+
+    /// Keep a task aside until it is resumed, and record it as paused
+    async fn mark_paused(&self, task: DownloadTask) {
+        let id = task.id;
+        {
+            let mut paused = self.paused.lock().await;
+            paused.insert(id, task);
+        }
+
+        let mut statuses = self.statuses.lock().await;
+        statuses.insert(id, DownloadStatus::Paused);
+        persist(&self.store, |store| {
+            store.update_status(id, &DownloadStatus::Paused)
+        });
+        self.events.publish(id, DownloadEvent::Paused);
+    }
+
+    /// Add a paused task back to the queue, and record it as queued
+    async fn requeue(&self, task: DownloadTask) {
+        let id = task.id;
+        {
+            let mut queue = self.queue.lock().await;
+            queue.push(task);
+        }
+
+        let mut statuses = self.statuses.lock().await;
+        statuses.insert(id, DownloadStatus::Queued);
+        persist(&self.store, |store| {
+            store.update_status(id, &DownloadStatus::Queued)
+        });
+        self.events.publish(id, DownloadEvent::Resumed);
     }
 
     /// Wait for a download to complete
@@ -406,12 +1024,19 @@ impl DownloadManager {
     }
 
     /// Process the download queue
-    fn process_queue(&self) {
+    pub(crate) fn process_queue(&self) {
         let queue_clone = self.queue.clone();
         let semaphore_clone = self.semaphore.clone();
         let statuses_clone = self.statuses.clone();
         let tasks_clone = self.tasks.clone();
         let config_clone = self.config.clone();
+        let cookies_clone = self.cookies.clone();
+        let network_clone = self.network.clone();
+        let client_clone = self.client.clone();
+        let events_clone = self.events.clone();
+        let store_clone = self.store.clone();
+        let concurrency_clone = self.concurrency.clone();
+        let rate_limiter_clone = self.rate_limiter.clone();
 
         tokio::spawn(async move {
             loop {
@@ -438,96 +1063,152 @@ impl DownloadManager {
 
                 // Update status
                 {
+                    let status = DownloadStatus::Downloading {
+                        downloaded_bytes: 0,
+                        total_bytes: 0,
+                    };
+                    persist(&store_clone, |store| store.update_status(task.id, &status));
+
                     let mut statuses = statuses_clone.lock().await;
-                    statuses.insert(
-                        task.id,
-                        DownloadStatus::Downloading {
-                            downloaded_bytes: 0,
-                            total_bytes: 0,
-                        },
-                    );
+                    statuses.insert(task.id, status);
                 }
 
-                // Create a fetcher for this task
+                // Create a fetcher for this task, publishing its events
+                let emitter = events_clone.emitter(task.id);
+                let stream = task
+                    .destination
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| task.url.clone());
+                emitter.emit(DownloadEvent::StreamStarted {
+                    stream: stream.clone(),
+                });
+
                 let mut fetcher = Fetcher::new(&task.url)
                     .with_segment_size(config_clone.segment_size)
                     .with_parallel_segments(config_clone.parallel_segments)
-                    .with_retry_attempts(config_clone.retry_attempts);
+                    .with_retry_attempts(config_clone.retry_attempts)
+                    .with_cookies(
+                        cookies_clone
+                            .read()
+                            .map(|cookies| cookies.clone())
+                            .unwrap_or_default(),
+                    )
+                    .with_network(
+                        network_clone
+                            .read()
+                            .map(|network| network.clone())
+                            .unwrap_or_default(),
+                    )
+                    .with_client(shared_client(&client_clone, &cookies_clone, &network_clone))
+                    .with_events(emitter.clone(), &stream)
+                    .with_rate_limiter(rate_limiter_clone.clone())
+                    .with_rate_limiter(task.rate_limiter.clone());
+
+                // Keep the task, to queue it again if the download is paused
+                let running = task.clone();
 
-                // Add progress callback if available
+                // Track the progress in the status, record it once per segment, and call the task callback if any
                 let task_id = task.id;
                 let statuses_for_callback = statuses_clone.clone();
+                let store_for_callback = store_clone.clone();
+                let persist_step = config_clone.segment_size as u64;
+                let last_persisted = AtomicU64::new(0);
+                let callback = task.progress_callback;
 
-                if let Some(callback) = task.progress_callback {
-                    fetcher = fetcher.with_progress_callback(move |downloaded, total| {
-                        // Update status with progress
-                        let mut statuses = statuses_for_callback.blocking_lock();
-                        statuses.insert(
-                            task_id,
-                            DownloadStatus::Downloading {
-                                downloaded_bytes: downloaded,
-                                total_bytes: total,
-                            },
-                        );
-
-                        // Call the original callback
+                fetcher = fetcher.with_progress_callback(move |downloaded, total| {
+                    let status = DownloadStatus::Downloading {
+                        downloaded_bytes: downloaded,
+                        total_bytes: total,
+                    };
+
+                    // The callback runs inside the download, so the status is not updated while it is being read,
+                    // nor once the download is paused or canceled
+                    let updated = match statuses_for_callback.try_lock() {
+                        Ok(mut statuses) => match statuses.get_mut(&task_id) {
+                            Some(current @ DownloadStatus::Downloading { .. }) => {
+                                *current = status.clone();
+                                true
+                            }
+                            _ => false,
+                        },
+                        Err(_) => false,
+                    };
+
+                    let last = last_persisted.load(std::sync::atomic::Ordering::Relaxed);
+                    let is_complete = total > 0 && downloaded >= total;
+                    if updated && (is_complete || downloaded.saturating_sub(last) >= persist_step) {
+                        last_persisted.store(downloaded, std::sync::atomic::Ordering::Relaxed);
+                        persist(&store_for_callback, |store| {
+                            store.update_status(task_id, &status)
+                        });
+                    }
+
+                    // Call the original callback
+                    if let Some(callback) = &callback {
                         callback(downloaded, total);
-                    });
-                } else {
-                    // Default callback that just updates the status
-                    let statuses_for_callback = statuses_clone.clone();
-                    fetcher = fetcher.with_progress_callback(move |downloaded, total| {
-                        let mut statuses = statuses_for_callback.blocking_lock();
-                        statuses.insert(
-                            task_id,
-                            DownloadStatus::Downloading {
-                                downloaded_bytes: downloaded,
-                                total_bytes: total,
-                            },
-                        );
-                    });
-                }
+                    }
+                });
 
                 // Launch the download in a separate task
                 let destination = task.destination.clone();
                 let statuses_for_task = statuses_clone.clone();
                 let tasks_for_task = tasks_clone.clone();
+                let store_for_task = store_clone.clone();
+                // The permit is released at the end of the download, or when it is aborted
+                let permit = DownloadPermit {
+                    permit: Some(permit),
+                    concurrency: concurrency_clone.clone(),
+                };
 
+                // The task is registered before it starts, so that it is removed once finished
+                let mut tasks = tasks_clone.lock().await;
                 let handle = tokio::spawn(async move {
-                    // The permit will be released automatically when it is drop at the end of this closure
-                    let _permit = permit;
-
                     // Download the file
                     let result = fetcher.fetch_asset(&destination).await;
 
                     // Update status based on result
+                    let status = match &result {
+                        Ok(_) => DownloadStatus::Completed,
+                        Err(e) => DownloadStatus::Failed {
+                            reason: e.to_string(),
+                        },
+                    };
+                    // The status is recorded without await point, so that an aborted download records none
                     let mut statuses = statuses_for_task.lock().await;
-                    match &result {
-                        Ok(_) => {
-                            statuses.insert(task_id, DownloadStatus::Completed);
-                        }
-                        Err(e) => {
-                            statuses.insert(
-                                task_id,
-                                DownloadStatus::Failed {
-                                    reason: e.to_string(),
-                                },
-                            );
-                        }
+                    persist(&store_for_task, |store| {
+                        store.update_status(task_id, &status)
+                    });
+                    statuses.insert(task_id, status);
+                    drop(statuses);
+
+                    // Publish the events once the status is up to date
+                    if result.is_ok() {
+                        emitter.emit(DownloadEvent::StreamCompleted {
+                            stream,
+                            path: destination.clone(),
+                        });
                     }
+                    emitter.finish(result.as_ref().map(|_| destination.as_path()));
 
                     // Remove the task from the list of tasks in progress
                     let mut tasks = tasks_for_task.lock().await;
                     tasks.remove(&task_id);
+                    drop(tasks);
+
+                    drop(permit);
 
                     result
                 });
 
-                // Store the task handle
-                {
-                    let mut tasks = tasks_clone.lock().await;
-                    tasks.insert(task_id, handle);
-                }
+                tasks.insert(
+                    task_id,
+                    RunningTask {
+                        task: running,
+                        handle,
+                    },
+                );
+                drop(tasks);
 
                 // Continue processing the queue if there are remaining tasks
                 let queue_empty = {
@@ -542,3 +1223,68 @@ impl DownloadManager {
         });
     }
 }
+
+/// Record a change of the queue in the persistent store, if any.
+/// A failure to record is logged, and does not stop the downloads.
+fn persist(store: &Option<Arc<QueueStore>>, change: impl FnOnce(&QueueStore) -> Result<()>) {
+    if let Some(store) = store {
+        if let Err(_e) = change(store) {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("Failed to record the download queue: {}", _e);
+        }
+    }
+}
+
+/// Wait for an aborted download to stop, reporting it if it panicked instead
+async fn wait_aborted(handle: JoinHandle<Result<()>>) {
+    if let Err(_e) = handle.await
+        && !_e.is_cancelled()
+    {
+        #[cfg(feature = "tracing")]
+        tracing::warn!("A download panicked while being stopped: {}", _e);
+    }
+}
+
+/// Get the HTTP client shared by the downloads, building it on the first download.
+/// If it cannot be built, each download builds its own client and reports the error.
+fn shared_client(
+    client: &RwLock<Option<HttpClient>>,
+    cookies: &RwLock<Option<Arc<Cookies>>>,
+    network: &RwLock<Arc<NetworkConfig>>,
+) -> Option<HttpClient> {
+    if let Some(client) = client.read().ok().and_then(|client| client.clone()) {
+        return Some(client);
+    }
+
+    let mut client = client.write().ok()?;
+    if client.is_none() {
+        let cookies = cookies
+            .read()
+            .map(|cookies| cookies.clone())
+            .unwrap_or_default();
+        let network = network
+            .read()
+            .map(|network| network.clone())
+            .unwrap_or_default();
+
+        match HttpClient::new(network, cookies) {
+            Ok(built) => *client = Some(built),
+            Err(_e) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("Failed to build the shared HTTP client: {}", _e);
+            }
+        }
+    }
+
+    client.clone()
+}
+
+/// Release the permit of a finished download, or forget it if the limit of concurrent downloads was lowered
+fn release(permit: OwnedSemaphorePermit, concurrency: &std::sync::Mutex<ConcurrencyLimit>) {
+    if let Ok(mut limit) = concurrency.lock() {
+        if limit.excess > 0 {
+            limit.excess -= 1;
+            permit.forget();
+        }
+    }
+}
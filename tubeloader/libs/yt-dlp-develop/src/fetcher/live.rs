@@ -0,0 +1,410 @@
+//! Tools for recording live streams from YouTube.
+//!
+//! Live streams which have not started yet can be waited for, by polling their status.
+//! Once started, their HLS playlist is recorded until the stream ends or the wanted duration is reached,
+//! and the recording is remuxed into a seekable file with the metadata of the video.
+
+use crate::error::{Error, Result};
+use crate::executor::Executor;
+use crate::fetcher::ProgressCallback;
+use crate::fetcher::manifest::ManifestFetcher;
+use crate::metadata::MetadataManager;
+use crate::model::Video;
+use crate::{Youtube, utils};
+use std::fmt::{self, Display};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// The options of a live stream recording.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiveOptions {
+    /// If the part of the stream still available is recorded, instead of starting from now.
+    from_start: bool,
+    /// The duration to record, until the stream ends when `None`.
+    max_duration: Option<Duration>,
+    /// If streams which have not started yet are waited for, instead of returning an error.
+    wait_for_start: bool,
+    /// The shortest interval between two checks of a stream which has not started yet.
+    poll_interval: Duration,
+    /// The longest interval between two checks of a stream which has not started yet.
+    max_poll_interval: Duration,
+    /// The longest time to wait for a stream to start, without limit when `None`.
+    max_wait: Option<Duration>,
+}
+
+impl Default for LiveOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LiveOptions {
+    /// Creates new options, recording from now until the stream ends, without waiting for it to start.
+    pub fn new() -> Self {
+        Self {
+            from_start: false,
+            max_duration: None,
+            wait_for_start: false,
+            poll_interval: Duration::from_secs(30),
+            max_poll_interval: Duration::from_secs(600),
+            max_wait: None,
+        }
+    }
+
+    /// Records the part of the stream still listed by its playlist, instead of starting from now.
+    /// YouTube usually keeps the last hours of a stream available.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_start` - If the recording starts from the oldest available fragment.
+    pub fn with_from_start(mut self, from_start: bool) -> Self {
+        self.from_start = from_start;
+        self
+    }
+
+    /// Stops the recording once the given duration is recorded.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - The duration to record.
+    pub fn with_max_duration(mut self, duration: Duration) -> Self {
+        self.max_duration = Some(duration);
+        self
+    }
+
+    /// Waits for streams which have not started yet, instead of returning an error.
+    ///
+    /// # Arguments
+    ///
+    /// * `wait` - If the recording waits for the stream to start.
+    pub fn with_wait_for_start(mut self, wait: bool) -> Self {
+        self.wait_for_start = wait;
+        self
+    }
+
+    /// Configures the interval between two checks of a stream which has not started yet.
+    /// The stream is checked at its scheduled start, then with an interval doubling from the shortest to the longest one.
+    ///
+    /// # Arguments
+    ///
+    /// * `min` - The shortest interval.
+    /// * `max` - The longest interval.
+    pub fn with_poll_interval(mut self, min: Duration, max: Duration) -> Self {
+        self.poll_interval = min;
+        self.max_poll_interval = max.max(min);
+        self
+    }
+
+    /// Gives up waiting for a stream which did not start after the given time.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_wait` - The longest time to wait.
+    pub fn with_max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = Some(max_wait);
+        self
+    }
+}
+
+// Implementation of the Display trait for LiveOptions
+impl fmt::Display for LiveOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "LiveOptions(from_start = {}, max_duration = {:?}, wait_for_start = {})",
+            self.from_start, self.max_duration, self.wait_for_start
+        )
+    }
+}
+
+impl Youtube {
+    /// Fetch the information of a live stream from the given URL, without using the cache.
+    /// Unlike `fetch_video_infos`, it does not fail for streams which have not started yet,
+    /// whose information has no formats.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the live stream.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the video information could not be fetched.
+    pub async fn fetch_live_infos(&self, url: String) -> Result<Video> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Fetching live stream information for {}", url);
+
+        self.dump_video_infos(&url, &["--ignore-no-formats-error"])
+            .await
+    }
+
+    /// Waits for a live stream to start, and returns its information once started.
+    /// The stream is checked at its scheduled start, or with an increasing interval when the start is unknown or past.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the live stream.
+    /// * `options` - The options of the recording, giving the intervals and the longest wait.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the video information could not be fetched,
+    /// or if the stream did not start before the longest wait.
+    pub async fn wait_for_live(&self, url: String, options: &LiveOptions) -> Result<Video> {
+        let started = Instant::now();
+        let mut interval = options.poll_interval;
+
+        loop {
+            let video = self.fetch_live_infos(url.clone()).await?;
+            if !video.is_upcoming() {
+                return Ok(video);
+            }
+
+            let now = chrono::Utc::now().timestamp();
+            let until_start = video
+                .release_timestamp
+                .filter(|start| *start > now)
+                .map(|start| Duration::from_secs((start - now) as u64));
+
+            let delay = match until_start {
+                Some(until_start) => until_start
+                    .min(options.max_poll_interval)
+                    .max(options.poll_interval),
+                None => {
+                    let delay = interval;
+                    interval = (interval * 2).min(options.max_poll_interval);
+                    delay
+                }
+            };
+
+            if let Some(max_wait) = options
+                .max_wait
+                .filter(|max_wait| started.elapsed() + delay > *max_wait)
+            {
+                return Err(Error::Timeout(max_wait));
+            }
+
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                "Live stream {} has not started yet, checking again in {:?}",
+                video.id,
+                delay
+            );
+
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Records a live stream from the given URL, and returns the path of the recording.
+    /// The recording is remuxed into a seekable file, whose container is given by the extension
+    /// of the output, e.g. 'mp4' or 'mkv', and the metadata of the video is added.
+    /// Be careful, this function lasts as long as the stream, unless a duration is given.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the live stream.
+    /// * `output` - The name of the file to save the recording to.
+    /// * `options` - The options of the recording.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the video is not a live stream, if it has not started
+    /// and is not waited for, or if the recording could not be downloaded or remuxed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use std::time::Duration;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::fetcher::live::LiveOptions;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=jfKfPfyJRdk");
+    /// let options = LiveOptions::new()
+    ///     .with_wait_for_start(true)
+    ///     .with_max_duration(Duration::from_secs(3600));
+    /// let recording = fetcher.record_live(url, "live.mp4", &options).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn record_live(
+        &self,
+        url: String,
+        output: impl AsRef<str> + fmt::Debug + Display,
+        options: &LiveOptions,
+    ) -> Result<PathBuf> {
+        self.record_live_internal(url, output, options, None).await
+    }
+
+    /// Records a live stream from the given URL with a progress callback, and returns the path of the recording.
+    /// The total size given to the callback is estimated from the duration to record, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the live stream.
+    /// * `output` - The name of the file to save the recording to.
+    /// * `options` - The options of the recording.
+    /// * `progress` - A function that will be called with the recorded size and total size.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the video is not a live stream, if it has not started
+    /// and is not waited for, or if the recording could not be downloaded or remuxed.
+    pub async fn record_live_with_progress<F>(
+        &self,
+        url: String,
+        output: impl AsRef<str> + fmt::Debug + Display,
+        options: &LiveOptions,
+        progress: F,
+    ) -> Result<PathBuf>
+    where
+        F: Fn(u64, u64) + Send + Sync + 'static,
+    {
+        self.record_live_internal(url, output, options, Some(Arc::new(progress)))
+            .await
+    }
+
+    /// Internal function that handles recording a live stream, with or without a progress callback.
+    async fn record_live_internal(
+        &self,
+        url: String,
+        output: impl AsRef<str> + fmt::Debug + Display,
+        options: &LiveOptions,
+        progress: Option<ProgressCallback>,
+    ) -> Result<PathBuf> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Recording live stream {} with {}", url, options);
+
+        let mut video = self.fetch_live_infos(url.clone()).await?;
+        if video.is_upcoming() {
+            if !options.wait_for_start {
+                return Err(Error::LiveNotStarted(format!(
+                    "the live stream {} has not started yet",
+                    video.id
+                )));
+            }
+            video = self.wait_for_live(url, options).await?;
+        }
+
+        if !video.is_live() {
+            return Err(Error::Video(format!(
+                "{} is not a live stream (live status: {})",
+                video.id, video.live_status
+            )));
+        }
+
+        let format = video
+            .best_live_format()
+            .ok_or_else(|| Error::MissingFormat("live".to_string()))?;
+        let mut fetcher = ManifestFetcher::for_format(format)
+            .ok_or_else(|| Error::MissingUrl(format.format_id.clone()))?
+            .with_parallel_fragments(4)
+            .with_retry_attempts(3)
+            .with_client(Some(self.http_client.clone()))
+            .with_rate_limiter(self.download_manager.rate_limiter().clone());
+        if let Some(progress) = progress {
+            fetcher =
+                fetcher.with_progress_callback(move |recorded, total| progress(recorded, total));
+        }
+
+        let path = self.output_dir.join(output.as_ref());
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or(Error::Path("Invalid output path".to_string()))?;
+        let part_path = path.with_file_name(format!("{}.part", file_name));
+
+        let recording = fetcher
+            .record_live(&part_path, options.from_start, options.max_duration)
+            .await?;
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            "Recorded {} fragments ({} bytes) of live stream {}",
+            recording.fragments,
+            recording.size,
+            video.id
+        );
+
+        // The recording is kept when it could not be remuxed, so that it is not lost
+        if let Err(e) = self
+            .finalize_live_recording(
+                &part_path,
+                &path,
+                recording.size,
+                recording.is_transport_stream,
+            )
+            .await
+        {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("Failed to remux live recording, keeping {:?}", part_path);
+
+            return Err(e);
+        }
+        if let Err(_e) = tokio::fs::remove_file(&part_path).await {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("Failed to remove live recording: {}", _e);
+        }
+
+        MetadataManager::add_metadata_with_format(&path, &video, Some(format), Some(format))
+            .await?;
+
+        Ok(path)
+    }
+
+    /// Remuxes a live recording into a seekable file, whose container is given by its extension.
+    /// MP4 and MOV files get their index at the start of the file, so that they can be played while downloaded.
+    /// The timeout of the remux grows with the size of the recording.
+    async fn finalize_live_recording(
+        &self,
+        recording: &Path,
+        output: &Path,
+        size: u64,
+        is_transport_stream: bool,
+    ) -> Result<()> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Remuxing live recording into {:?}", output);
+
+        let extension = output
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase())
+            .unwrap_or_default();
+        let is_mp4 = matches!(extension.as_str(), "mp4" | "m4v" | "mov");
+
+        let mut args = utils::to_owned(vec![
+            "-i",
+            &crate::postprocessor::path_arg(recording)?,
+            "-map",
+            "0",
+            "-c",
+            "copy",
+        ]);
+        // MPEG-TS streams carry AAC with ADTS headers, which MP4 files do not accept
+        if is_transport_stream && is_mp4 {
+            args.extend(utils::to_owned(vec!["-bsf:a", "aac_adtstoasc"]));
+        }
+        if is_mp4 {
+            args.extend(utils::to_owned(vec!["-movflags", "+faststart"]));
+        }
+        args.push(crate::postprocessor::path_arg(output)?);
+
+        let executor = Executor {
+            executable_path: self.libraries.ffmpeg.clone(),
+            timeout: crate::postprocessor::remux_timeout(self.timeout, size),
+            args,
+            env: Vec::new(),
+        };
+
+        executor.execute().await.map(|_| ())
+    }
+}
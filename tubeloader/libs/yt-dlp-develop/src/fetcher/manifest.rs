@@ -0,0 +1,1305 @@
+//! Tools for downloading formats made of fragments, listed in an HLS playlist or a DASH manifest.
+//!
+//! HLS master and media playlists are read, as well as DASH manifests whose representations use
+//! a `SegmentTemplate` (with or without a `SegmentTimeline`), a `SegmentList` or a single `BaseURL`.
+//! The fragments are downloaded in parallel, with retries, and written in order into a single file.
+//! Fragmented MP4 and WebM streams can be played as they are, while MPEG-TS streams must be remuxed.
+//!
+//! The HLS playlists of live streams can also be recorded, by reloading them until they end.
+
+use crate::cookies::Cookies;
+use crate::error::{Error, Result};
+use crate::events::{EventEmitter, StreamEvents};
+use crate::fetcher::ProgressCallback;
+use crate::model::format::{Format, Protocol};
+use crate::network::{HttpClient, NetworkConfig};
+use crate::rate_limit::{self, RateLimiter};
+use crate::utils::file_system;
+use crate::utils::xml::{self, XmlNode};
+use futures_util::{StreamExt, stream};
+use ordered_float::OrderedFloat;
+use reqwest::Url;
+use reqwest::header::RANGE;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+/// The first byte of every MPEG-TS packet.
+const TS_SYNC_BYTE: u8 = 0x47;
+/// The number of reloads of a live playlist without new fragments, after which the stream is considered ended.
+const MAX_STALE_RELOADS: usize = 10;
+
+/// The kinds of manifests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ManifestKind {
+    /// An HLS playlist, '.m3u8'.
+    Hls,
+    /// A DASH manifest, '.mpd'.
+    Dash,
+}
+
+impl ManifestKind {
+    /// Detects the kind of a manifest from its content.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The content of the manifest.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use yt_dlp::fetcher::manifest::ManifestKind;
+    /// assert_eq!(ManifestKind::detect("#EXTM3U\n#EXT-X-VERSION:3"), Some(ManifestKind::Hls));
+    /// assert_eq!(ManifestKind::detect("<?xml version=\"1.0\"?><MPD></MPD>"), Some(ManifestKind::Dash));
+    /// assert_eq!(ManifestKind::detect("<html></html>"), None);
+    /// ```
+    pub fn detect(content: &str) -> Option<Self> {
+        let content = content.trim_start_matches('\u{feff}').trim_start();
+
+        if content.starts_with("#EXTM3U") {
+            Some(ManifestKind::Hls)
+        } else if content.starts_with('<') && content.contains("MPD") {
+            Some(ManifestKind::Dash)
+        } else {
+            None
+        }
+    }
+}
+
+/// A fragment of a stream: a whole file, or a byte range of it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ManifestFragment {
+    /// The absolute URL of the fragment.
+    pub url: String,
+    /// The first and last bytes of the fragment, both included, if it is only a part of the file.
+    pub range: Option<(u64, u64)>,
+    /// The duration of the fragment, in seconds, if known.
+    pub duration: Option<OrderedFloat<f64>>,
+}
+
+/// The fragments of a stream, listed by an HLS media playlist or a DASH representation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct FragmentList {
+    /// The initialization fragment, written before the others, e.g. the header of fragmented MP4 streams.
+    pub init: Option<ManifestFragment>,
+    /// The media fragments, in playback order.
+    pub fragments: Vec<ManifestFragment>,
+    /// The sequence number of the first fragment, increasing as live playlists drop their old fragments.
+    pub media_sequence: u64,
+    /// If the list is complete, false for live streams still adding fragments.
+    pub ended: bool,
+    /// The maximum duration of the fragments, in seconds, if known.
+    pub target_duration: Option<u64>,
+}
+
+/// A variant stream of an HLS master playlist.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HlsVariant {
+    /// The absolute URL of the media playlist of the variant.
+    pub url: String,
+    /// The peak bitrate of the variant, in bits per second.
+    pub bandwidth: Option<u64>,
+    /// The width and height of the video, if any.
+    pub resolution: Option<(u32, u32)>,
+    /// The codecs of the variant, e.g. 'avc1.4d401f,mp4a.40.2'.
+    pub codecs: Option<String>,
+}
+
+/// An HLS playlist, listing either variant streams or fragments.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum HlsPlaylist {
+    /// A master playlist, listing the variants of a stream.
+    Master(Vec<HlsVariant>),
+    /// A media playlist, listing the fragments of a variant.
+    Media(FragmentList),
+}
+
+/// Parses an HLS playlist.
+///
+/// # Arguments
+///
+/// * `content` - The content of the playlist.
+/// * `base_url` - The URL of the playlist, to which relative URLs are resolved.
+///
+/// # Errors
+///
+/// This function will return an error if the playlist is invalid, or if its fragments are encrypted.
+///
+/// # Examples
+///
+/// ```rust
+/// # use yt_dlp::fetcher::manifest::{parse_hls_playlist, HlsPlaylist};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let playlist = "#EXTM3U
+/// #EXT-X-TARGETDURATION:4
+/// #EXT-X-MAP:URI=\"init.mp4\"
+/// #EXTINF:4.0,
+/// segment-1.m4s
+/// #EXTINF:4.0,
+/// segment-2.m4s
+/// #EXT-X-ENDLIST";
+///
+/// let HlsPlaylist::Media(list) = parse_hls_playlist(playlist, "https://example.com/hls/index.m3u8")? else {
+///     panic!("not a media playlist");
+/// };
+/// assert_eq!(list.init.unwrap().url, "https://example.com/hls/init.mp4");
+/// assert_eq!(list.fragments[1].url, "https://example.com/hls/segment-2.m4s");
+/// assert!(list.ended);
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse_hls_playlist(content: &str, base_url: &str) -> Result<HlsPlaylist> {
+    let base = parse_url(base_url)?;
+    let mut lines = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty());
+
+    let header = lines.next().map(|line| line.trim_start_matches('\u{feff}'));
+    if header != Some("#EXTM3U") {
+        return Err(Error::Manifest(
+            "HLS playlists must start with #EXTM3U".to_string(),
+        ));
+    }
+
+    let mut variants = Vec::new();
+    let mut list = FragmentList::default();
+    let mut variant_attributes = None;
+    let mut byte_range = None;
+    let mut duration = None;
+    // The URL and end of the previous byte range, where ranges without offset start
+    let mut previous_range: Option<(String, u64)> = None;
+
+    for line in lines {
+        if let Some(attributes) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+            variant_attributes = Some(parse_attribute_list(attributes));
+        } else if let Some(attributes) = line.strip_prefix("#EXT-X-MAP:") {
+            let attributes = parse_attribute_list(attributes);
+            let uri = attribute(&attributes, "URI")
+                .ok_or(Error::Manifest("#EXT-X-MAP without URI".to_string()))?;
+            let range = attribute(&attributes, "BYTERANGE")
+                .map(|range| parse_hls_byte_range(range, 0))
+                .transpose()?;
+
+            list.init = Some(ManifestFragment {
+                url: join_url(&base, uri)?,
+                range,
+                duration: None,
+            });
+        } else if let Some(info) = line.strip_prefix("#EXTINF:") {
+            let length = info.split_once(',').map_or(info, |(length, _)| length);
+            duration = length.trim().parse().ok().map(OrderedFloat);
+        } else if let Some(range) = line.strip_prefix("#EXT-X-BYTERANGE:") {
+            byte_range = Some(range);
+        } else if let Some(attributes) = line.strip_prefix("#EXT-X-KEY:") {
+            let attributes = parse_attribute_list(attributes);
+            let method = attribute(&attributes, "METHOD").unwrap_or("NONE");
+            if method != "NONE" {
+                return Err(Error::Manifest(format!(
+                    "encrypted HLS streams are not supported (METHOD={})",
+                    method
+                )));
+            }
+        } else if let Some(sequence) = line.strip_prefix("#EXT-X-MEDIA-SEQUENCE:") {
+            list.media_sequence = sequence
+                .trim()
+                .parse()
+                .map_err(|_| Error::Manifest(format!("invalid media sequence: {}", sequence)))?;
+        } else if let Some(target) = line.strip_prefix("#EXT-X-TARGETDURATION:") {
+            list.target_duration = target.trim().parse().ok();
+        } else if line == "#EXT-X-ENDLIST" {
+            list.ended = true;
+        } else if !line.starts_with('#') {
+            let url = join_url(&base, line)?;
+
+            if let Some(attributes) = variant_attributes.take() {
+                let resolution = attribute(&attributes, "RESOLUTION")
+                    .and_then(|resolution| resolution.split_once('x'))
+                    .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)));
+
+                variants.push(HlsVariant {
+                    url,
+                    bandwidth: attribute(&attributes, "BANDWIDTH")
+                        .and_then(|bandwidth| bandwidth.parse().ok()),
+                    resolution,
+                    codecs: attribute(&attributes, "CODECS").map(String::from),
+                });
+                continue;
+            }
+
+            let range = match byte_range.take() {
+                Some(range) => {
+                    let offset = match &previous_range {
+                        Some((previous_url, end)) if *previous_url == url => *end,
+                        _ => 0,
+                    };
+                    Some(parse_hls_byte_range(range, offset)?)
+                }
+                None => None,
+            };
+            previous_range = range.map(|(_, last)| (url.clone(), last + 1));
+
+            list.fragments.push(ManifestFragment {
+                url,
+                range,
+                duration: duration.take(),
+            });
+        }
+    }
+
+    if variants.is_empty() {
+        Ok(HlsPlaylist::Media(list))
+    } else {
+        Ok(HlsPlaylist::Master(variants))
+    }
+}
+
+/// Parses a DASH manifest, and lists the fragments of one of its representations.
+/// Only the first period containing the representation is read.
+///
+/// # Arguments
+///
+/// * `content` - The content of the manifest.
+/// * `base_url` - The URL of the manifest, to which relative URLs are resolved.
+/// * `representation_id` - The ID of the representation, e.g. the format ID given by 'yt-dlp',
+///   or None for the representation with the highest bandwidth.
+///
+/// # Errors
+///
+/// This function will return an error if the manifest is invalid, if the representation is not found,
+/// or if the number of its segments cannot be known.
+///
+/// # Examples
+///
+/// ```rust
+/// # use yt_dlp::fetcher::manifest::parse_dash_manifest;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let manifest = r#"<?xml version="1.0"?>
+/// <MPD xmlns="urn:mpeg:dash:schema:mpd:2011" type="static" mediaPresentationDuration="PT10S">
+///   <Period>
+///     <AdaptationSet mimeType="video/mp4">
+///       <SegmentTemplate initialization="$RepresentationID$/init.mp4" media="$RepresentationID$/$Number%03d$.m4s" duration="4"/>
+///       <Representation id="720p" bandwidth="2000000"/>
+///       <Representation id="360p" bandwidth="500000"/>
+///     </AdaptationSet>
+///   </Period>
+/// </MPD>"#;
+///
+/// let list = parse_dash_manifest(manifest, "https://example.com/dash/manifest.mpd", None)?;
+/// assert_eq!(list.init.unwrap().url, "https://example.com/dash/720p/init.mp4");
+/// assert_eq!(list.fragments.len(), 3);
+/// assert_eq!(list.fragments[2].url, "https://example.com/dash/720p/003.m4s");
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse_dash_manifest(
+    content: &str,
+    base_url: &str,
+    representation_id: Option<&str>,
+) -> Result<FragmentList> {
+    let events = xml::parse(content)?;
+    let mpd = xml::tree(&events)?;
+    if mpd.name != "MPD" {
+        return Err(Error::Manifest(
+            "DASH manifests must have a MPD root element".to_string(),
+        ));
+    }
+
+    let candidates: Vec<(&XmlNode, &XmlNode, &XmlNode)> = mpd
+        .children_named("Period")
+        .flat_map(|period| {
+            period
+                .children_named("AdaptationSet")
+                .flat_map(move |adaptation| {
+                    adaptation
+                        .children_named("Representation")
+                        .map(move |representation| (period, adaptation, representation))
+                })
+        })
+        .collect();
+
+    let (period, adaptation, representation) = match representation_id {
+        Some(wanted) => candidates
+            .into_iter()
+            .find(|(_, _, representation)| {
+                representation
+                    .attribute("id")
+                    .is_some_and(|id| matches_representation(wanted, id))
+            })
+            .ok_or_else(|| {
+                Error::FormatNotFound(format!("representation {} in DASH manifest", wanted))
+            })?,
+        None => candidates
+            .into_iter()
+            .rev()
+            .max_by_key(|(_, _, representation)| numeric_attribute(representation, "bandwidth"))
+            .ok_or(Error::Manifest(
+                "no representation in DASH manifest".to_string(),
+            ))?,
+    };
+
+    // Each level can set a base URL, relative to the one of its parent
+    let base = [&mpd, period, adaptation, representation]
+        .into_iter()
+        .try_fold(parse_url(base_url)?, |base, node| {
+            resolve_base_url(&base, node)
+        })?;
+
+    let period_duration = period
+        .attribute("duration")
+        .or(mpd.attribute("mediaPresentationDuration"))
+        .and_then(parse_iso8601_duration);
+    let levels = [representation, adaptation, period];
+
+    let (init, fragments) = if levels
+        .iter()
+        .any(|node| node.child("SegmentTemplate").is_some())
+    {
+        let template = SegmentTemplate {
+            levels: levels
+                .iter()
+                .filter_map(|node| node.child("SegmentTemplate"))
+                .collect(),
+        };
+        template.fragments(&base, representation, period_duration)?
+    } else if let Some(list) = levels.iter().find_map(|node| node.child("SegmentList")) {
+        let init = list
+            .child("Initialization")
+            .map(|init| dash_fragment(&base, init.attribute("sourceURL"), init.attribute("range")))
+            .transpose()?;
+        let fragments = list
+            .children_named("SegmentURL")
+            .map(|segment| {
+                dash_fragment(
+                    &base,
+                    segment.attribute("media"),
+                    segment.attribute("mediaRange"),
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+        (init, fragments)
+    } else {
+        // A single file, possibly indexed by a SegmentBase
+        let fragment = ManifestFragment {
+            url: base.to_string(),
+            range: None,
+            duration: period_duration.map(OrderedFloat),
+        };
+        (None, vec![fragment])
+    };
+
+    Ok(FragmentList {
+        init,
+        fragments,
+        media_sequence: 0,
+        ended: mpd.attribute("type") != Some("dynamic"),
+        target_duration: None,
+    })
+}
+
+/// A stream downloaded from a manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ManifestDownload {
+    /// The kind of the manifest.
+    pub kind: ManifestKind,
+    /// The number of downloaded fragments, including the initialization fragment.
+    pub fragments: usize,
+    /// The size of the downloaded stream, in bytes.
+    pub size: u64,
+    /// If the stream is a MPEG-TS stream, which must be remuxed to be played as an MP4 file.
+    pub is_transport_stream: bool,
+}
+
+/// The manifest fetcher downloads the fragments of a stream, listed by an HLS playlist or a DASH manifest.
+/// The fragments are downloaded in parallel, and written in order.
+pub struct ManifestFetcher {
+    /// The URL of the manifest.
+    url: String,
+    /// The ID of the representation to download from a DASH manifest.
+    representation_id: Option<String>,
+    /// The number of fragments downloaded at the same time.
+    parallel_fragments: usize,
+    /// The number of download attempts of each fragment in case of failure.
+    retry_attempts: usize,
+    /// Callback optional for tracking download progress
+    progress_callback: Option<ProgressCallback>,
+    /// The cookies sent with the requests.
+    cookies: Option<Arc<Cookies>>,
+    /// The proxy, certificates, timeouts and user agent of the requests.
+    network: Arc<NetworkConfig>,
+    /// The events of the downloaded stream.
+    events: Option<StreamEvents>,
+    /// The limiters of the bandwidth, e.g. the global and the per-download ones.
+    rate_limiters: Vec<Arc<RateLimiter>>,
+    /// The shared HTTP client, or None to build one for each download.
+    client: Option<HttpClient>,
+}
+
+impl fmt::Display for ManifestFetcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ManifestFetcher(url={}, fragments={})",
+            self.url, self.parallel_fragments
+        )
+    }
+}
+
+impl ManifestFetcher {
+    /// Creates a new fetcher for the given manifest URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the HLS playlist or DASH manifest.
+    pub fn new(url: impl AsRef<str>) -> Self {
+        Self {
+            url: url.as_ref().to_string(),
+            representation_id: None,
+            parallel_fragments: 4,
+            retry_attempts: 3,
+            progress_callback: None,
+            cookies: None,
+            network: Arc::default(),
+            events: None,
+            rate_limiters: Vec::new(),
+            client: None,
+        }
+    }
+
+    /// Creates a fetcher for a format, if it must be downloaded from a manifest:
+    /// HLS and DASH formats, and formats with only a manifest URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - The format to download.
+    pub fn for_format(format: &Format) -> Option<Self> {
+        let url = format.download_info.url.as_ref();
+        let manifest_url = format.download_info.manifest_url.as_ref();
+
+        match format.protocol {
+            // The URL of HLS formats is the media playlist of the variant
+            Protocol::M3U8Native | Protocol::M3U8 => url.or(manifest_url).map(Self::new),
+            Protocol::HttpDashSegments => manifest_url
+                .or(url)
+                .map(|url| Self::new(url).with_representation(&format.format_id)),
+            _ if url.is_none() => {
+                manifest_url.map(|url| Self::new(url).with_representation(&format.format_id))
+            }
+            _ => None,
+        }
+    }
+
+    /// Configures the representation to download from a DASH manifest.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the representation, or the format ID given by 'yt-dlp', e.g. 'dash-video=1000'.
+    pub fn with_representation(mut self, id: impl AsRef<str>) -> Self {
+        self.representation_id = Some(id.as_ref().to_string());
+        self
+    }
+
+    /// Configures the number of fragments downloaded at the same time.
+    ///
+    /// # Arguments
+    ///
+    /// * `fragments` - The number of parallel fragments.
+    pub fn with_parallel_fragments(mut self, fragments: usize) -> Self {
+        self.parallel_fragments = fragments;
+        self
+    }
+
+    /// Configures the number of download attempts of each fragment in case of failure.
+    ///
+    /// # Arguments
+    ///
+    /// * `attempts` - The number of attempts.
+    pub fn with_retry_attempts(mut self, attempts: usize) -> Self {
+        self.retry_attempts = attempts;
+        self
+    }
+
+    /// Configure a callback for tracking download progress.
+    /// The total size is estimated from the size of the fragments already downloaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - A function that will be called with the downloaded size and total size.
+    pub fn with_progress_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(u64, u64) + Send + Sync + 'static,
+    {
+        self.progress_callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Configures the cookies sent with the requests of the manifest and its fragments.
+    ///
+    /// # Arguments
+    ///
+    /// * `cookies` - The cookies, or None to send no cookies.
+    pub fn with_cookies(mut self, cookies: Option<Arc<Cookies>>) -> Self {
+        self.cookies = cookies;
+        self
+    }
+
+    /// Configures the proxy, certificates, timeouts and user agent of the requests.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The network configuration.
+    pub fn with_network(mut self, network: Arc<NetworkConfig>) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Sends the requests of the manifest and its fragments through a shared HTTP client, reusing its connections.
+    /// Its network configuration and cookies replace the ones of the fetcher.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The shared client, or None to build a client for each download.
+    pub fn with_client(mut self, client: Option<HttpClient>) -> Self {
+        if let Some(client) = &client {
+            self.network = client.network().clone();
+            self.cookies = client.cookies().cloned();
+        }
+
+        self.client = client;
+        self
+    }
+
+    /// Publishes the progress and the retries of the download as events of a stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `emitter` - The emitter of the events of the download.
+    /// * `stream` - The name of the downloaded stream, e.g. the ID of its format.
+    pub fn with_events(mut self, emitter: EventEmitter, stream: impl AsRef<str>) -> Self {
+        self.events = Some(StreamEvents::new(emitter, stream));
+        self
+    }
+
+    /// Limits the bandwidth of the download, shared by all its fragments.
+    /// Several limiters can be added, e.g. a global one shared with other downloads and a per-download one.
+    ///
+    /// # Arguments
+    ///
+    /// * `limiter` - The limiter of the bandwidth.
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiters.push(limiter);
+        self
+    }
+
+    /// Returns the callback reporting the progress, to the progress callback and as events.
+    fn progress(&self) -> Option<ProgressCallback> {
+        match &self.events {
+            Some(events) => Some(events.progress(self.progress_callback.clone())),
+            None => self.progress_callback.clone(),
+        }
+    }
+
+    /// Returns the shared HTTP client, or builds one with the network configuration and sending the configured cookies.
+    fn client(&self) -> Result<reqwest::Client> {
+        if let Some(client) = &self.client {
+            return Ok(client.client().clone());
+        }
+
+        let builder = self.network.client_builder()?;
+        let builder = match &self.cookies {
+            Some(cookies) => builder.cookie_provider(cookies.clone()),
+            None => builder,
+        };
+
+        Ok(builder.build()?)
+    }
+
+    /// Fetches the manifest and lists the fragments of the stream.
+    /// For HLS master playlists, the variant with the highest bandwidth is used.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the manifest could not be fetched or parsed.
+    pub async fn fetch_fragments(&self) -> Result<(ManifestKind, FragmentList)> {
+        let client = self.client()?;
+        let (kind, _, list) = self.resolve_fragments(&client).await?;
+
+        Ok((kind, list))
+    }
+
+    /// Fetches the manifest and lists the fragments of the stream, with the URL of the
+    /// manifest listing them, e.g. the media playlist of the chosen HLS variant.
+    async fn resolve_fragments(
+        &self,
+        client: &reqwest::Client,
+    ) -> Result<(ManifestKind, String, FragmentList)> {
+        let (url, content) = fetch_text(client, &self.url).await?;
+
+        match ManifestKind::detect(&content) {
+            Some(ManifestKind::Hls) => {
+                let variants = match parse_hls_playlist(&content, &url)? {
+                    HlsPlaylist::Media(list) => return Ok((ManifestKind::Hls, url, list)),
+                    HlsPlaylist::Master(variants) => variants,
+                };
+
+                let variant = variants
+                    .iter()
+                    .rev()
+                    .max_by_key(|variant| variant.bandwidth.unwrap_or(0))
+                    .ok_or(Error::Manifest("no variant in HLS playlist".to_string()))?;
+
+                #[cfg(feature = "tracing")]
+                tracing::debug!(
+                    "Using HLS variant {} ({:?} bps)",
+                    variant.url,
+                    variant.bandwidth
+                );
+
+                let (url, list) = fetch_media_playlist(client, &variant.url).await?;
+                Ok((ManifestKind::Hls, url, list))
+            }
+            Some(ManifestKind::Dash) => {
+                let list = parse_dash_manifest(&content, &url, self.representation_id.as_deref())?;
+                Ok((ManifestKind::Dash, url, list))
+            }
+            None => Err(Error::Manifest(format!(
+                "{} is neither an HLS playlist nor a DASH manifest",
+                url
+            ))),
+        }
+    }
+
+    /// Downloads the fragments of the stream, and writes them in order to the given destination.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - The path where to write the stream.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the manifest is invalid, if a fragment could not be
+    /// downloaded after every attempt, or if the stream could not be written.
+    pub async fn fetch_manifest(
+        &self,
+        destination: impl AsRef<Path> + fmt::Debug,
+    ) -> Result<ManifestDownload> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Fetching manifest {} to {:?}", self.url, destination);
+
+        let (kind, list) = self.fetch_fragments().await?;
+        let fragments: Vec<&ManifestFragment> =
+            list.init.iter().chain(list.fragments.iter()).collect();
+        let count = fragments.len() as u64;
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Downloading {} fragments from {:?} manifest", count, kind);
+
+        file_system::create_parent_dir(&destination)?;
+        let mut file = file_system::create_file(&destination).await?;
+
+        // Fragments are downloaded in parallel, but yielded in order
+        let client = self.client()?;
+        let mut results = stream::iter(fragments)
+            .map(|fragment| self.download_fragment_with_retry(&client, fragment))
+            .buffered(self.parallel_fragments.max(1));
+
+        let progress = self.progress();
+        let mut size = 0;
+        let mut downloaded = 0;
+        let mut first_byte = None;
+        while let Some(data) = results.next().await {
+            let data = data?;
+            if downloaded == 0 {
+                first_byte = data.first().copied();
+            }
+
+            file.write_all(&data).await?;
+            size += data.len() as u64;
+            downloaded += 1;
+
+            if let Some(callback) = &progress {
+                callback(size, size * count / downloaded);
+            }
+        }
+        file.flush().await?;
+
+        Ok(ManifestDownload {
+            kind,
+            fragments: downloaded as usize,
+            size,
+            is_transport_stream: list.init.is_none() && first_byte == Some(TS_SYNC_BYTE),
+        })
+    }
+
+    /// Records a live stream listed by an HLS playlist, and writes its fragments in order to the given destination.
+    /// The playlist is reloaded until it ends, the wanted duration is recorded, or it stops adding fragments.
+    /// Playlists which already ended are downloaded as a whole.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - The path where to write the stream.
+    /// * `from_start` - If the fragments still listed by the playlist are recorded, instead of starting from the latest one.
+    /// * `max_duration` - The duration to record, or None to record until the stream ends.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the playlist is invalid or is a live DASH manifest,
+    /// if a fragment could not be downloaded after every attempt, or if the stream could not be written.
+    /// If the playlist cannot be reloaded anymore, the recording stops without error.
+    pub async fn record_live(
+        &self,
+        destination: impl AsRef<Path> + fmt::Debug,
+        from_start: bool,
+        max_duration: Option<Duration>,
+    ) -> Result<ManifestDownload> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Recording live manifest {} to {:?}", self.url, destination);
+
+        let client = self.client()?;
+        let (kind, playlist_url, mut list) = self.resolve_fragments(&client).await?;
+        if kind == ManifestKind::Dash && !list.ended {
+            return Err(Error::Manifest(
+                "live DASH manifests are not supported".to_string(),
+            ));
+        }
+
+        file_system::create_parent_dir(&destination)?;
+        let mut file = file_system::create_file(&destination).await?;
+
+        let max_seconds = max_duration.map(|duration| duration.as_secs_f64());
+        // The sequence number of the next fragment to record
+        let mut next_sequence = if from_start || list.ended {
+            list.media_sequence
+        } else {
+            list.media_sequence + list.fragments.len().saturating_sub(1) as u64
+        };
+        let progress = self.progress();
+        let mut init_written = false;
+        let mut recorded = 0.0;
+        let mut size = 0;
+        let mut downloaded = 0;
+        let mut first_byte = None;
+        let mut stale_reloads = 0;
+        let mut failed_reloads = 0;
+
+        loop {
+            if list.media_sequence > next_sequence {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(
+                    "{} live fragments were dropped by the playlist before being recorded",
+                    list.media_sequence - next_sequence
+                );
+                next_sequence = list.media_sequence;
+            }
+
+            let mut fragments: Vec<&ManifestFragment> = Vec::new();
+            if !init_written {
+                fragments.extend(list.init.iter());
+                init_written = true;
+            }
+            let skipped = (next_sequence - list.media_sequence) as usize;
+            for fragment in list.fragments.iter().skip(skipped) {
+                if max_seconds.is_some_and(|max| recorded >= max) {
+                    break;
+                }
+
+                recorded += fragment
+                    .duration
+                    .map(|duration| *duration)
+                    .or(list.target_duration.map(|target| target as f64))
+                    .unwrap_or(0.0);
+                next_sequence += 1;
+                fragments.push(fragment);
+            }
+            let has_new_fragments = !fragments.is_empty();
+
+            let mut results = stream::iter(fragments)
+                .map(|fragment| self.download_fragment_with_retry(&client, fragment))
+                .buffered(self.parallel_fragments.max(1));
+
+            while let Some(data) = results.next().await {
+                let data = data?;
+                if downloaded == 0 {
+                    first_byte = data.first().copied();
+                }
+
+                file.write_all(&data).await?;
+                size += data.len() as u64;
+                downloaded += 1;
+
+                if let Some(callback) = &progress {
+                    let total = match max_seconds {
+                        Some(max) if recorded > 0.0 => (size as f64 * max / recorded) as u64,
+                        _ => size,
+                    };
+                    callback(size, total.max(size));
+                }
+            }
+            // The fragments borrow the playlist, which is replaced when reloaded
+            drop(results);
+            file.flush().await?;
+
+            if list.ended || max_seconds.is_some_and(|max| recorded >= max) {
+                break;
+            }
+
+            stale_reloads = if has_new_fragments {
+                0
+            } else {
+                stale_reloads + 1
+            };
+            if stale_reloads >= MAX_STALE_RELOADS {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("The live playlist stopped adding fragments, ending the recording");
+                break;
+            }
+
+            // Playlists are reloaded every target duration, or half of it when they did not change
+            let target = list.target_duration.unwrap_or(2).max(1) as f64;
+            let delay = if has_new_fragments {
+                target
+            } else {
+                target / 2.0
+            };
+            tokio::time::sleep(Duration::from_secs_f64(delay)).await;
+
+            match fetch_media_playlist(&client, &playlist_url).await {
+                Ok((_, reloaded)) => {
+                    list = reloaded;
+                    failed_reloads = 0;
+                }
+                Err(error) => {
+                    failed_reloads += 1;
+                    // The playlist of an ended stream is often removed instead of being completed
+                    if failed_reloads >= self.retry_attempts.max(1) {
+                        if downloaded == 0 {
+                            return Err(error);
+                        }
+
+                        #[cfg(feature = "tracing")]
+                        tracing::debug!("The live playlist is not available anymore: {}", error);
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(ManifestDownload {
+            kind,
+            fragments: downloaded,
+            size,
+            is_transport_stream: list.init.is_none() && first_byte == Some(TS_SYNC_BYTE),
+        })
+    }
+
+    /// Downloads a fragment, retrying with an exponential backoff in case of failure.
+    async fn download_fragment_with_retry(
+        &self,
+        client: &reqwest::Client,
+        fragment: &ManifestFragment,
+    ) -> Result<Vec<u8>> {
+        let attempts = self.retry_attempts.max(1);
+
+        for attempt in 0..attempts {
+            match download_fragment(client, fragment, &self.rate_limiters).await {
+                Ok(data) => return Ok(data),
+                Err(error) if attempt < attempts - 1 => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(
+                        "Fragment {} download failed (attempt {}): {}",
+                        fragment.url,
+                        attempt + 1,
+                        error
+                    );
+                    if let Some(events) = &self.events {
+                        events.retry(attempt + 1, &error);
+                    }
+
+                    tokio::time::sleep(tokio::time::Duration::from_millis(
+                        250 * 2u64.pow(attempt as u32),
+                    ))
+                    .await;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+
+        Err(Error::Unknown(format!(
+            "Failed to download fragment {} after {} attempts",
+            fragment.url, attempts
+        )))
+    }
+}
+
+/// The segment template of a representation, whose attributes are inherited from its adaptation set and period.
+struct SegmentTemplate<'n, 'a> {
+    /// The templates of the representation, adaptation set and period, from the most specific.
+    levels: Vec<&'n XmlNode<'a>>,
+}
+
+impl SegmentTemplate<'_, '_> {
+    /// Returns the value of an attribute, from the most specific template setting it.
+    fn attribute(&self, key: &str) -> Option<&str> {
+        self.levels
+            .iter()
+            .find_map(|template| template.attribute(key))
+    }
+
+    /// Returns a numeric attribute, or a default value if it is not set.
+    fn number(&self, key: &str, default: u64) -> Result<u64> {
+        match self.attribute(key) {
+            Some(value) => value.trim().parse().map_err(|_| {
+                Error::Manifest(format!("invalid SegmentTemplate {}: {}", key, value))
+            }),
+            None => Ok(default),
+        }
+    }
+
+    /// Lists the initialization and media fragments of the template.
+    fn fragments(
+        &self,
+        base: &Url,
+        representation: &XmlNode,
+        period_duration: Option<f64>,
+    ) -> Result<(Option<ManifestFragment>, Vec<ManifestFragment>)> {
+        let media = self.attribute("media").ok_or(Error::Manifest(
+            "SegmentTemplate without media attribute".to_string(),
+        ))?;
+        let start_number = self.number("startNumber", 1)?;
+        let timescale = self.number("timescale", 1)?.max(1);
+        let id = representation.attribute("id").unwrap_or_default();
+        let bandwidth = representation.attribute("bandwidth").unwrap_or_default();
+
+        // The number, start time and duration of each segment
+        let mut segments = Vec::new();
+        let timeline = self
+            .levels
+            .iter()
+            .find_map(|template| template.child("SegmentTimeline"));
+
+        if let Some(timeline) = timeline {
+            let end_time = period_duration.map(|duration| (duration * timescale as f64) as u64);
+            let entries: Vec<&XmlNode> = timeline.children_named("S").collect();
+            let mut time = 0;
+            let mut number = start_number;
+
+            for (index, entry) in entries.iter().enumerate() {
+                if let Some(start) = entry.attribute("t") {
+                    time = parse_number(start)?;
+                }
+                let duration = entry
+                    .attribute("d")
+                    .map(parse_number)
+                    .transpose()?
+                    .filter(|duration| *duration > 0)
+                    .ok_or(Error::Manifest(
+                        "SegmentTimeline entry without duration".to_string(),
+                    ))?;
+                let repeat: i64 = match entry.attribute("r") {
+                    Some(repeat) => repeat.trim().parse().map_err(|_| {
+                        Error::Manifest(format!("invalid SegmentTimeline repeat: {}", repeat))
+                    })?,
+                    None => 0,
+                };
+
+                let count = if repeat >= 0 {
+                    repeat as u64 + 1
+                } else {
+                    // A negative repeat lasts until the next entry, or the end of the period
+                    let until = entries
+                        .get(index + 1)
+                        .and_then(|next| next.attribute("t"))
+                        .and_then(|start| start.trim().parse().ok())
+                        .or(end_time)
+                        .ok_or(Error::Manifest(
+                            "the duration of the period is unknown".to_string(),
+                        ))?;
+                    until.saturating_sub(time).div_ceil(duration)
+                };
+
+                for _ in 0..count {
+                    segments.push((number, time, duration));
+                    number += 1;
+                    time += duration;
+                }
+            }
+        } else {
+            let duration = self.number("duration", 0)?;
+            if duration == 0 {
+                return Err(Error::Manifest(
+                    "SegmentTemplate without duration nor SegmentTimeline".to_string(),
+                ));
+            }
+            let period_duration = period_duration.ok_or(Error::Manifest(
+                "the duration of the period is unknown".to_string(),
+            ))?;
+
+            let count = (period_duration * timescale as f64 / duration as f64).ceil() as u64;
+            segments
+                .extend((0..count).map(|index| (start_number + index, index * duration, duration)));
+        }
+
+        let init = self
+            .attribute("initialization")
+            .map(|initialization| {
+                let url = expand_template(initialization, id, start_number, 0, bandwidth);
+                Ok::<_, Error>(ManifestFragment {
+                    url: join_url(base, &url)?,
+                    range: None,
+                    duration: None,
+                })
+            })
+            .transpose()?;
+
+        let fragments = segments
+            .into_iter()
+            .map(|(number, time, duration)| {
+                let url = expand_template(media, id, number, time, bandwidth);
+                Ok(ManifestFragment {
+                    url: join_url(base, &url)?,
+                    range: None,
+                    duration: Some(OrderedFloat(duration as f64 / timescale as f64)),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok((init, fragments))
+    }
+}
+
+/// Expands the identifiers of a DASH segment template, e.g. '$Number%05d$'.
+/// Unknown identifiers are kept as they are.
+fn expand_template(template: &str, id: &str, number: u64, time: u64, bandwidth: &str) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('$') {
+        output.push_str(&rest[..start]);
+        let identifier_start = &rest[start + 1..];
+
+        let Some(end) = identifier_start.find('$') else {
+            output.push_str(&rest[start..]);
+            return output;
+        };
+
+        let identifier = &identifier_start[..end];
+        let (name, width) = match identifier.split_once('%') {
+            Some((name, format)) => (
+                name,
+                format
+                    .trim_start_matches('0')
+                    .trim_end_matches('d')
+                    .parse()
+                    .unwrap_or(0),
+            ),
+            None => (identifier, 0),
+        };
+
+        let value = match name {
+            "" => Some("$".to_string()),
+            "RepresentationID" => Some(id.to_string()),
+            "Number" => Some(number.to_string()),
+            "Time" => Some(time.to_string()),
+            "Bandwidth" => Some(bandwidth.to_string()),
+            _ => None,
+        };
+
+        match value {
+            Some(value) => output.push_str(&format!("{:0>width$}", value, width = width)),
+            None => output.push_str(&rest[start..start + end + 2]),
+        }
+        rest = &identifier_start[end + 1..];
+    }
+    output.push_str(rest);
+
+    output
+}
+
+/// Builds a fragment of a DASH segment list, the whole base URL if the fragment has no URL.
+fn dash_fragment(base: &Url, url: Option<&str>, range: Option<&str>) -> Result<ManifestFragment> {
+    let url = match url {
+        Some(url) => join_url(base, url)?,
+        None => base.to_string(),
+    };
+
+    let range = range
+        .map(|range| {
+            let (first, last) = range
+                .split_once('-')
+                .ok_or(Error::Manifest(format!("invalid byte range: {}", range)))?;
+            Ok::<_, Error>((parse_number(first)?, parse_number(last)?))
+        })
+        .transpose()?;
+
+    Ok(ManifestFragment {
+        url,
+        range,
+        duration: None,
+    })
+}
+
+/// Checks if a representation ID matches a wanted ID, which can be a format ID such as 'dash-720p'.
+fn matches_representation(wanted: &str, id: &str) -> bool {
+    wanted == id
+        || wanted
+            .strip_suffix(id)
+            .is_some_and(|prefix| prefix.ends_with('-'))
+}
+
+/// Resolves the base URL of an element of a DASH manifest, from the base URL of its parent.
+fn resolve_base_url(base: &Url, node: &XmlNode) -> Result<Url> {
+    match node.child("BaseURL") {
+        Some(base_url) if !base_url.text.is_empty() => base
+            .join(&base_url.text)
+            .map_err(|e| Error::Manifest(format!("invalid BaseURL {}: {}", base_url.text, e))),
+        _ => Ok(base.clone()),
+    }
+}
+
+/// Returns a numeric attribute of an element, 0 if it is not set or invalid.
+fn numeric_attribute(node: &XmlNode, key: &str) -> u64 {
+    node.attribute(key)
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Parses an ISO 8601 duration of a DASH manifest into seconds, e.g. 'PT1H2M3.5S'.
+fn parse_iso8601_duration(duration: &str) -> Option<f64> {
+    let rest = duration.trim().strip_prefix('P')?;
+    let mut seconds = 0.0;
+    let mut number = String::new();
+    let mut in_time = false;
+
+    for character in rest.chars() {
+        match character {
+            'T' => in_time = true,
+            '0'..='9' | '.' => number.push(character),
+            unit => {
+                let value: f64 = number.parse().ok()?;
+                number.clear();
+
+                seconds += value
+                    * match (in_time, unit) {
+                        (false, 'Y') => 365.0 * 86_400.0,
+                        (false, 'M') => 30.0 * 86_400.0,
+                        (false, 'W') => 7.0 * 86_400.0,
+                        (false, 'D') => 86_400.0,
+                        (true, 'H') => 3_600.0,
+                        (true, 'M') => 60.0,
+                        (true, 'S') => 1.0,
+                        _ => return None,
+                    };
+            }
+        }
+    }
+
+    number.is_empty().then_some(seconds)
+}
+
+/// Parses the attribute list of an HLS tag, e.g. 'BANDWIDTH=1280000,CODECS="avc1.4d401f,mp4a.40.2"'.
+fn parse_attribute_list(list: &str) -> Vec<(&str, &str)> {
+    let mut attributes = Vec::new();
+    let mut rest = list.trim();
+
+    while let Some((key, value)) = rest.split_once('=') {
+        let value = value.trim_start();
+        let (value, remaining) = match value.strip_prefix('"') {
+            Some(quoted) => match quoted.find('"') {
+                Some(end) => (&quoted[..end], &quoted[end + 1..]),
+                None => (quoted, ""),
+            },
+            None => match value.find(',') {
+                Some(end) => (&value[..end], &value[end..]),
+                None => (value, ""),
+            },
+        };
+
+        attributes.push((key.trim(), value.trim()));
+        rest = remaining.trim_start().trim_start_matches(',').trim_start();
+    }
+
+    attributes
+}
+
+/// Returns the value of an attribute of an HLS tag.
+fn attribute<'a>(attributes: &[(&str, &'a str)], key: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map(|(_, value)| *value)
+}
+
+/// Parses an HLS byte range, 'length[@offset]', starting at the given offset if it has none.
+fn parse_hls_byte_range(range: &str, offset: u64) -> Result<(u64, u64)> {
+    let (length, offset) = match range.split_once('@') {
+        Some((length, offset)) => (parse_number(length)?, parse_number(offset)?),
+        None => (parse_number(range)?, offset),
+    };
+
+    if length == 0 {
+        return Err(Error::Manifest(format!("empty byte range: {}", range)));
+    }
+
+    Ok((offset, offset + length - 1))
+}
+
+/// Parses an unsigned number of a manifest.
+fn parse_number(value: &str) -> Result<u64> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| Error::Manifest(format!("invalid number: {}", value)))
+}
+
+/// Parses an absolute URL.
+fn parse_url(url: &str) -> Result<Url> {
+    Url::parse(url).map_err(|e| Error::Manifest(format!("invalid URL {}: {}", url, e)))
+}
+
+/// Resolves a URL of a manifest, relative to a base URL.
+fn join_url(base: &Url, url: &str) -> Result<String> {
+    base.join(url)
+        .map(String::from)
+        .map_err(|e| Error::Manifest(format!("invalid URL {}: {}", url, e)))
+}
+
+/// Fetches the text of a manifest, with its final URL after redirections.
+async fn fetch_text(client: &reqwest::Client, url: &str) -> Result<(String, String)> {
+    let response = client.get(url).send().await?.error_for_status()?;
+    let url = response.url().to_string();
+    let content = response.text().await?;
+
+    Ok((url, content))
+}
+
+/// Fetches an HLS media playlist, with its final URL after redirections.
+async fn fetch_media_playlist(
+    client: &reqwest::Client,
+    url: &str,
+) -> Result<(String, FragmentList)> {
+    let (url, content) = fetch_text(client, url).await?;
+
+    match parse_hls_playlist(&content, &url)? {
+        HlsPlaylist::Media(list) => Ok((url, list)),
+        HlsPlaylist::Master(_) => Err(Error::Manifest(
+            "nested HLS master playlists are not supported".to_string(),
+        )),
+    }
+}
+
+/// Downloads a fragment, or its byte range, throttled by the given limiters.
+async fn download_fragment(
+    client: &reqwest::Client,
+    fragment: &ManifestFragment,
+    rate_limiters: &[Arc<RateLimiter>],
+) -> Result<Vec<u8>> {
+    let mut request = client.get(&fragment.url);
+    if let Some((first, last)) = fragment.range {
+        request = request.header(RANGE, format!("bytes={}-{}", first, last));
+    }
+
+    let response = request.send().await?.error_for_status()?;
+    rate_limit::read_body(response, rate_limiters).await
+}
@@ -5,7 +5,11 @@
 //!
 //! The `blocking` module contains blocking functions for fetching data from YouTube.
 
+use crate::cookies::Cookies;
 use crate::error::{Error, Result};
+use crate::events::{EventEmitter, StreamEvents};
+use crate::network::{HttpClient, NetworkConfig};
+use crate::rate_limit::{self, RateLimiter};
 use crate::utils::file_system;
 use futures_util::{StreamExt, stream};
 use reqwest::header::{HeaderMap, HeaderValue, RANGE, USER_AGENT};
@@ -19,9 +23,18 @@ use tokio::sync::Mutex;
 
 pub mod deps;
 pub mod download_manager;
+pub mod live;
+pub mod manifest;
+pub mod playlist;
+pub mod queue_store;
+pub mod sponsorblock;
 pub mod streams;
+pub mod subtitles;
 pub mod thumbnail;
 
+/// A callback for tracking download progress, called with the downloaded size and total size.
+pub type ProgressCallback = Arc<dyn Fn(u64, u64) + Send + Sync>;
+
 /// Context for segment download operations
 struct SegmentContext {
     file: Arc<Mutex<tokio::fs::File>>,
@@ -45,6 +58,16 @@ pub struct Fetcher {
     /// Callback optional for tracking download progress
     #[allow(clippy::type_complexity)]
     progress_callback: Option<Arc<dyn Fn(u64, u64) + Send + Sync>>,
+    /// The cookies sent with the requests.
+    cookies: Option<Arc<Cookies>>,
+    /// The proxy, certificates, timeouts and user agent of the requests.
+    network: Arc<NetworkConfig>,
+    /// The events of the downloaded stream.
+    events: Option<StreamEvents>,
+    /// The limiters of the bandwidth, e.g. the global and the per-download ones.
+    rate_limiters: Vec<Arc<RateLimiter>>,
+    /// The shared HTTP client, or None to build one for each download.
+    client: Option<HttpClient>,
 }
 
 impl fmt::Display for Fetcher {
@@ -70,6 +93,11 @@ impl Fetcher {
             segment_size: 1024 * 1024 * 5, // 5 MB per segment by default
             retry_attempts: 3,
             progress_callback: None,
+            cookies: None,
+            network: Arc::default(),
+            events: None,
+            rate_limiters: Vec::new(),
+            client: None,
         }
     }
 
@@ -116,6 +144,88 @@ impl Fetcher {
         self
     }
 
+    /// Configures the cookies sent with the requests, e.g. for age-restricted formats.
+    ///
+    /// # Arguments
+    ///
+    /// * `cookies` - The cookies, or None to send no cookies.
+    pub fn with_cookies(mut self, cookies: Option<Arc<Cookies>>) -> Self {
+        self.cookies = cookies;
+        self
+    }
+
+    /// Configures the proxy, certificates, timeouts and user agent of the requests.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The network configuration.
+    pub fn with_network(mut self, network: Arc<NetworkConfig>) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Sends the requests through a shared HTTP client, reusing its connections.
+    /// Its network configuration and cookies replace the ones of the fetcher.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The shared client, or None to build a client for each download.
+    pub fn with_client(mut self, client: Option<HttpClient>) -> Self {
+        if let Some(client) = &client {
+            self.network = client.network().clone();
+            self.cookies = client.cookies().cloned();
+        }
+
+        self.client = client;
+        self
+    }
+
+    /// Publishes the progress and the retries of the download as events of a stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `emitter` - The emitter of the events of the download.
+    /// * `stream` - The name of the downloaded stream, e.g. the ID of its format.
+    pub fn with_events(mut self, emitter: EventEmitter, stream: impl AsRef<str>) -> Self {
+        self.events = Some(StreamEvents::new(emitter, stream));
+        self
+    }
+
+    /// Limits the bandwidth of the download, shared by all its segments.
+    /// Several limiters can be added, e.g. a global one shared with other downloads and a per-download one.
+    ///
+    /// # Arguments
+    ///
+    /// * `limiter` - The limiter of the bandwidth.
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiters.push(limiter);
+        self
+    }
+
+    /// Returns the callback reporting the progress, to the progress callback and as events.
+    fn progress(&self) -> Option<ProgressCallback> {
+        match &self.events {
+            Some(events) => Some(events.progress(self.progress_callback.clone())),
+            None => self.progress_callback.clone(),
+        }
+    }
+
+    /// Returns the shared HTTP client, or builds one with the network configuration and sending the configured cookies.
+    /// It is used by all the requests of a download, so that its segments reuse the same connections.
+    fn client(&self) -> Result<reqwest::Client> {
+        if let Some(client) = &self.client {
+            return Ok(client.client().clone());
+        }
+
+        let builder = self.network.client_builder()?;
+        let builder = match &self.cookies {
+            Some(cookies) => builder.cookie_provider(cookies.clone()),
+            None => builder,
+        };
+
+        Ok(builder.build()?)
+    }
+
     /// Fetch the data from the URL and return it as Serde value.
     ///
     /// # Arguments
@@ -130,7 +240,9 @@ impl Fetcher {
         tracing::debug!("Fetching JSON from {}", self.url);
 
         let mut headers = HeaderMap::new();
-        headers.insert(USER_AGENT, HeaderValue::from_static("rust-reqwest"));
+        if self.network.user_agent().is_none() {
+            headers.insert(USER_AGENT, HeaderValue::from_static("rust-reqwest"));
+        }
 
         if let Some(auth_token) = auth_token {
             let value = HeaderValue::from_str(&format!("Bearer {}", auth_token))
@@ -139,7 +251,7 @@ impl Fetcher {
             headers.insert(reqwest::header::AUTHORIZATION, value);
         }
 
-        let client = reqwest::Client::new();
+        let client = self.client()?;
         let response = client
             .get(&self.url)
             .headers(headers)
@@ -186,8 +298,8 @@ impl Fetcher {
             None
         };
 
-        // Check if the server supports range requests
-        let client = reqwest::Client::new();
+        // Check if the server supports range requests, with the client used by all the segments
+        let client = self.client()?;
         let head_response = client.head(&self.url).send().await?;
 
         // If the server does not support range requests, use the simple method
@@ -196,7 +308,7 @@ impl Fetcher {
             tracing::debug!(
                 "Server does not support range requests, falling back to simple download"
             );
-            return self.fetch_asset_simple(destination).await;
+            return self.fetch_asset_simple(destination, &client).await;
         }
 
         // Get the total file size
@@ -210,13 +322,18 @@ impl Fetcher {
             None => {
                 #[cfg(feature = "tracing")]
                 tracing::debug!("Content-Length header not found, falling back to simple download");
-                return self.fetch_asset_simple(destination).await;
+                return self.fetch_asset_simple(destination, &client).await;
             }
         };
 
-        // If the file exists and has the same size, it is already downloaded
+        // The segments already downloaded are tracked in a temporary file, until the download is complete
+        let temp_file_path = format!("{}.parts", destination.as_ref().display());
+        let is_partial = std::path::Path::new(&temp_file_path).exists();
+
+        // If the file exists and has the same size, it is already downloaded,
+        // unless it was allocated by an interrupted download
         if let Some(size) = file_size {
-            if size == content_length {
+            if size == content_length && !is_partial {
                 #[cfg(feature = "tracing")]
                 tracing::debug!("File already exists with correct size, skipping download");
                 return Ok(());
@@ -250,6 +367,11 @@ impl Fetcher {
             file
         };
 
+        // Mark the file as partial, so that an interrupted download is resumed instead of considered complete
+        if !is_partial {
+            tokio::fs::File::create(&temp_file_path).await?;
+        }
+
         // Create a mutex to share the file between tasks
         let file = Arc::new(Mutex::new(file));
 
@@ -270,9 +392,8 @@ impl Fetcher {
             ranges.push((start, end));
         }
 
-        // Create a temporary file to track downloaded segments
-        let temp_file_path = format!("{}.parts", destination.as_ref().display());
-        let downloaded_segments = if file_exists && std::path::Path::new(&temp_file_path).exists() {
+        // Read the segments already downloaded from the temporary file
+        let downloaded_segments = if file_exists && is_partial {
             // Read the downloaded segments from the temporary file
             match tokio::fs::read_to_string(&temp_file_path).await {
                 Ok(content) => {
@@ -328,6 +449,8 @@ impl Fetcher {
         // Create a temporary file to track downloaded segments
         let temp_file_path_clone = temp_file_path.clone();
         let downloaded_segments = Arc::new(Mutex::new(downloaded_segments));
+        let progress = self.progress();
+        let client = &client;
 
         // Create a stream of futures to download each segment
         let results = stream::iter(ranges_to_download)
@@ -335,7 +458,7 @@ impl Fetcher {
                 let url = self.url.clone();
                 let file_clone = Arc::clone(&file);
                 let downloaded_bytes_clone = Arc::clone(&downloaded_bytes);
-                let progress_callback = self.progress_callback.as_ref().map(Arc::clone);
+                let progress_callback = progress.as_ref().map(Arc::clone);
                 let downloaded_segments_clone = Arc::clone(&downloaded_segments);
                 let temp_file_path = temp_file_path_clone.clone();
 
@@ -343,6 +466,7 @@ impl Fetcher {
                     for attempt in 0..self.retry_attempts {
                         match self
                             .download_segment(
+                                client,
                                 &url,
                                 start,
                                 end,
@@ -382,8 +506,9 @@ impl Fetcher {
                                     attempt + 1,
                                     error
                                 );
-                                // Consume the error
-                                let _ = error;
+                                if let Some(events) = &self.events {
+                                    events.retry(attempt + 1, &error);
+                                }
 
                                 // Wait a bit before retrying (exponential backoff)
                                 tokio::time::sleep(tokio::time::Duration::from_millis(
@@ -413,7 +538,7 @@ impl Fetcher {
         }
 
         // Call the callback one last time to indicate that the download is complete
-        if let Some(callback) = &self.progress_callback {
+        if let Some(callback) = &progress {
             callback(total_bytes, total_bytes);
         }
 
@@ -453,13 +578,12 @@ impl Fetcher {
     /// Downloads a specific segment of the file.
     async fn download_segment(
         &self,
+        client: &reqwest::Client,
         url: &str,
         start: u64,
         end: u64,
         context: &SegmentContext,
     ) -> Result<()> {
-        let client = reqwest::Client::new();
-
         // Check if the segment is already downloaded by reading the file
         let mut file_guard = context.file.lock().await;
         file_guard.seek(std::io::SeekFrom::Start(start)).await?;
@@ -495,8 +619,8 @@ impl Fetcher {
             .await?
             .error_for_status()?;
 
-        // Read the data
-        let data = response.bytes().await?;
+        // Read the data, throttled by the bandwidth limits
+        let data = rate_limit::read_body(response, &self.rate_limiters).await?;
 
         // Acquire the mutex and write the data at the correct position
         let mut file_guard = context.file.lock().await;
@@ -519,7 +643,11 @@ impl Fetcher {
     }
 
     /// Simple download method without parallel optimizations.
-    async fn fetch_asset_simple(&self, destination: impl AsRef<Path>) -> Result<()> {
+    async fn fetch_asset_simple(
+        &self,
+        destination: impl AsRef<Path>,
+        client: &reqwest::Client,
+    ) -> Result<()> {
         #[cfg(feature = "tracing")]
         tracing::debug!("Using simple download for {}", self.url);
 
@@ -544,13 +672,10 @@ impl Fetcher {
             None
         };
 
-        // Create a client with a longer timeout
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .build()?;
-
-        // If the file exists, try to resume the download
-        let mut request = client.get(&self.url);
+        // If the file exists, try to resume the download, with a longer timeout
+        let mut request = client
+            .get(&self.url)
+            .timeout(std::time::Duration::from_secs(60));
 
         // Add Range header if the file exists and has some content
         if let Some(size) = file_size {
@@ -562,8 +687,10 @@ impl Fetcher {
             }
         }
 
-        // Add User-Agent header
-        request = request.header(USER_AGENT, "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36");
+        // Add User-Agent header, unless configured on the client
+        if self.network.user_agent().is_none() {
+            request = request.header(USER_AGENT, "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36");
+        }
 
         // Send the request
         let response = request.send().await?;
@@ -603,6 +730,7 @@ impl Fetcher {
         };
 
         let mut stream = response.bytes_stream();
+        let progress = self.progress();
 
         // Use a larger buffer to improve performance
         let mut buffer = Vec::with_capacity(1024 * 1024); // 1 MB buffer
@@ -627,13 +755,14 @@ impl Fetcher {
 
         while let Some(chunk) = stream.next().await {
             let chunk = chunk?;
+            rate_limit::throttle(&self.rate_limiters, chunk.len()).await;
             buffer.extend_from_slice(&chunk);
 
             // Update progress
             downloaded_bytes += chunk.len() as u64;
 
             // Call progress callback if available
-            if let Some(callback) = &self.progress_callback {
+            if let Some(callback) = &progress {
                 callback(downloaded_bytes, total_bytes);
             }
 
@@ -0,0 +1,252 @@
+//! Tools for fetching playlists and channels from YouTube.
+
+use crate::error::Error;
+use crate::executor::Executor;
+use crate::model::{Playlist, PlaylistEntry};
+use crate::{Youtube, utils};
+use futures_util::Stream;
+use futures_util::stream;
+use std::collections::VecDeque;
+
+/// The default number of entries fetched per page by `Youtube::fetch_playlist`.
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+
+/// The state of a lazily paged playlist.
+#[derive(Debug)]
+struct PlaylistPager {
+    /// The URL of the playlist.
+    url: String,
+    /// The number of entries fetched per page.
+    page_size: usize,
+    /// The 1-based index of the first entry of the next page.
+    next_start: usize,
+    /// The entries fetched but not yet yielded.
+    buffer: VecDeque<PlaylistEntry>,
+    /// The playlist assembled from every fetched page, to be cached once complete.
+    fetched: Option<Playlist>,
+    /// Whether the last page has been fetched.
+    finished: bool,
+}
+
+impl Youtube {
+    /// Fetch the flat playlist information from the given URL.
+    /// The URL can be a playlist, or a channel page such as '/@handle/videos'.
+    ///
+    /// Only the basic information of each entry is fetched, so this is much faster than
+    /// fetching every video of the playlist.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the playlist or channel page to fetch.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the playlist information could not be fetched,
+    /// or if the URL does not point to a playlist.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/playlist?list=PLlaN88a7y2_plecYoJxvRFTLHVbIVAOoc");
+    /// let playlist = fetcher.fetch_playlist_infos(url).await?;
+    ///
+    /// for entry in &playlist.entries {
+    ///     println!("{}", entry.video_url());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_playlist_infos(&self, url: String) -> crate::error::Result<Playlist> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Fetching playlist information from {}", url);
+
+        // Check if the playlist is in the cache
+        #[cfg(feature = "cache")]
+        if let Some(cache) = &self.cache
+            && let Some(playlist) = cache.get_playlist(&url)
+        {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("Using cached playlist information for {}", url);
+            return Ok(playlist);
+        }
+
+        let playlist = self.fetch_playlist_page(&url, None).await?;
+
+        // Put the playlist in the cache if caching is enabled
+        #[cfg(feature = "cache")]
+        if let Some(cache) = &self.cache
+            && let Err(_e) = cache.put_playlist(url.clone(), &playlist)
+        {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("Failed to cache playlist information: {}", _e);
+        }
+
+        Ok(playlist)
+    }
+
+    /// Lazily fetch the flat entries of a playlist or channel page, page by page.
+    /// A new page is only requested from 'yt-dlp' once the previous one has been consumed,
+    /// so large channels can be enumerated without fetching every entry up front.
+    ///
+    /// Once every page has been fetched, the complete playlist is put in the cache,
+    /// and later calls are served from it.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the playlist or channel page to fetch.
+    /// * `page_size` - The number of entries fetched per page, e.g. `DEFAULT_PAGE_SIZE`.
+    ///
+    /// # Errors
+    ///
+    /// The stream yields an error if a page could not be fetched, and ends right after it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::fetcher::playlist::DEFAULT_PAGE_SIZE;
+    /// # use futures_util::StreamExt;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/@YouTube/videos");
+    /// let entries = fetcher.fetch_playlist(url, DEFAULT_PAGE_SIZE);
+    /// futures_util::pin_mut!(entries);
+    ///
+    /// while let Some(entry) = entries.next().await {
+    ///     println!("{}", entry?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fetch_playlist(
+        &self,
+        url: String,
+        page_size: usize,
+    ) -> impl Stream<Item = crate::error::Result<PlaylistEntry>> + '_ {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Paging through playlist {} by {} entries", url, page_size);
+
+        let pager = PlaylistPager {
+            url,
+            page_size: page_size.max(1),
+            next_start: 1,
+            buffer: VecDeque::new(),
+            fetched: None,
+            finished: false,
+        };
+
+        stream::unfold(pager, move |mut pager| async move {
+            loop {
+                if let Some(entry) = pager.buffer.pop_front() {
+                    return Some((Ok(entry), pager));
+                }
+
+                if pager.finished {
+                    return None;
+                }
+
+                if let Err(e) = self.next_playlist_page(&mut pager).await {
+                    pager.finished = true;
+                    return Some((Err(e), pager));
+                }
+            }
+        })
+    }
+
+    /// Fetch the next page of a paged playlist into its buffer.
+    async fn next_playlist_page(&self, pager: &mut PlaylistPager) -> crate::error::Result<()> {
+        // The whole playlist may already be in the cache
+        #[cfg(feature = "cache")]
+        if pager.next_start == 1
+            && let Some(cache) = &self.cache
+            && let Some(playlist) = cache.get_playlist(&pager.url)
+        {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("Using cached playlist entries for {}", pager.url);
+
+            pager.buffer.extend(playlist.entries);
+            pager.finished = true;
+            return Ok(());
+        }
+
+        let end = pager.next_start + pager.page_size - 1;
+        let items = format!("{}:{}", pager.next_start, end);
+        let page = self.fetch_playlist_page(&pager.url, Some(&items)).await?;
+
+        pager.next_start = end + 1;
+        pager.finished = page.entries.len() < pager.page_size;
+        pager.buffer.extend(page.entries.iter().cloned());
+
+        match &mut pager.fetched {
+            Some(fetched) => fetched.entries.extend(page.entries),
+            None => pager.fetched = Some(page),
+        }
+
+        // Put the complete playlist in the cache if caching is enabled
+        #[cfg(feature = "cache")]
+        if pager.finished
+            && let (Some(cache), Some(playlist)) = (&self.cache, &pager.fetched)
+            && let Err(_e) = cache.put_playlist(pager.url.clone(), playlist)
+        {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("Failed to cache playlist information: {}", _e);
+        }
+
+        Ok(())
+    }
+
+    /// Run 'yt-dlp' on a playlist URL, optionally restricted to the given '--playlist-items'.
+    async fn fetch_playlist_page(
+        &self,
+        url: &str,
+        items: Option<&str>,
+    ) -> crate::error::Result<Playlist> {
+        let mut playlist_args = vec!["--no-progress", "--flat-playlist", "--dump-single-json"];
+        if let Some(items) = items {
+            playlist_args.push("--playlist-items");
+            playlist_args.push(items);
+        }
+        playlist_args.push(url);
+
+        let mut final_args = self.yt_dlp_args();
+        final_args.append(&mut utils::to_owned(playlist_args));
+
+        let executor = Executor {
+            executable_path: self.libraries.youtube.clone(),
+            timeout: self.timeout,
+            args: final_args,
+            env: self.network.yt_dlp_env(),
+        };
+
+        let output = executor.execute().await?;
+
+        let value: serde_json::Value =
+            serde_json::from_str(&output.stdout).map_err(Error::Serde)?;
+        if value.get("_type").and_then(|t| t.as_str()) != Some("playlist") {
+            return Err(Error::Video(format!("{} is not a playlist", url)));
+        }
+
+        serde_json::from_value(value).map_err(Error::Serde)
+    }
+}
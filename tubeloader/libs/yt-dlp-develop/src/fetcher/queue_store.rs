@@ -0,0 +1,447 @@
+//! Persistent storage of the download manager queue.
+//!
+//! The queue is recorded in a SQLite database when the `cache` feature is enabled, and in a JSON file otherwise.
+//! Every change of a download is written as soon as it happens, so that a queue interrupted by a crash or a reboot
+//! can be restored, and its unfinished downloads resumed from their segments already downloaded.
+
+use crate::error::Result;
+use crate::fetcher::download_manager::{DownloadPriority, DownloadStatus};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "cache")] {
+        use rusqlite::{Connection, OpenFlags, params};
+        use std::time::{SystemTime, UNIX_EPOCH};
+    } else {
+        use std::collections::BTreeMap;
+        use std::io::Write;
+    }
+}
+
+/// A download recorded in the persistent queue.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueuedDownload {
+    /// The ID of the download.
+    pub id: u64,
+    /// The URL to download.
+    pub url: String,
+    /// The destination path.
+    pub destination: PathBuf,
+    /// The priority of the download.
+    pub priority: DownloadPriority,
+    /// The bandwidth limit of the download in bytes per second, or None for unlimited.
+    #[serde(default)]
+    pub rate_limit: Option<u64>,
+    /// The last recorded status of the download, with the bytes done if it was downloading.
+    pub status: DownloadStatus,
+}
+
+impl QueuedDownload {
+    /// Checks if the download has not finished yet, and must be resumed when the queue is restored.
+    pub fn is_unfinished(&self) -> bool {
+        matches!(
+            self.status,
+            DownloadStatus::Queued | DownloadStatus::Downloading { .. }
+        )
+    }
+}
+
+/// The persistent storage of the download queue.
+#[derive(Debug)]
+pub struct QueueStore {
+    /// The SQLite connection.
+    #[cfg(feature = "cache")]
+    connection: Mutex<Connection>,
+    /// The path of the JSON file.
+    #[cfg(not(feature = "cache"))]
+    path: PathBuf,
+    /// The recorded downloads, written to the JSON file on every change.
+    #[cfg(not(feature = "cache"))]
+    downloads: Mutex<BTreeMap<u64, QueuedDownload>>,
+}
+
+#[cfg(feature = "cache")]
+impl QueueStore {
+    /// Opens the queue stored in the given SQLite database, creating it if needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the database file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the parent directory cannot be created or the database cannot be initialized.
+    pub fn open(path: impl AsRef<Path> + std::fmt::Debug) -> Result<Self> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Opening download queue in {:?}", path);
+
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let connection = Connection::open_with_flags(
+            path.as_ref(),
+            OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE,
+        )?;
+
+        // The write-ahead log keeps the database consistent if the process is killed during a write
+        connection
+            .pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+
+        // Initialize the database schema
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS downloads (
+                id INTEGER PRIMARY KEY,
+                url TEXT NOT NULL,
+                destination TEXT NOT NULL,
+                priority INTEGER NOT NULL,
+                status TEXT NOT NULL,
+                reason TEXT,
+                downloaded_bytes INTEGER NOT NULL DEFAULT 0,
+                total_bytes INTEGER NOT NULL DEFAULT 0,
+                updated_at INTEGER NOT NULL,
+                rate_limit INTEGER
+            )",
+            [],
+        )?;
+
+        // The rate limit column is missing from the queues recorded before it was added
+        if connection
+            .prepare("SELECT rate_limit FROM downloads LIMIT 0")
+            .is_err()
+        {
+            connection.execute("ALTER TABLE downloads ADD COLUMN rate_limit INTEGER", [])?;
+        }
+
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    /// Records a download, replacing the previous record with the same ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `download` - The download to record.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the download cannot be written to the database.
+    pub fn save(&self, download: &QueuedDownload) -> Result<()> {
+        let (status, reason, downloaded_bytes, total_bytes) = status_columns(&download.status);
+
+        let connection = self.connection.lock().unwrap();
+        connection.execute(
+            "INSERT OR REPLACE INTO downloads
+                (id, url, destination, priority, status, reason, downloaded_bytes, total_bytes, updated_at, rate_limit)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                download.id,
+                download.url,
+                download.destination.to_string_lossy(),
+                download.priority as i32,
+                status,
+                reason,
+                downloaded_bytes,
+                total_bytes,
+                now(),
+                download.rate_limit
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Records the new status of a download.
+    /// The bytes done are only updated by a `Downloading` status, and are kept by the others.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the download.
+    /// * `status` - The new status.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the status cannot be written to the database.
+    pub fn update_status(&self, id: u64, status: &DownloadStatus) -> Result<()> {
+        let (status, reason, downloaded_bytes, total_bytes) = status_columns(status);
+
+        let connection = self.connection.lock().unwrap();
+        connection.execute(
+            "UPDATE downloads SET status = ?, reason = ?,
+                downloaded_bytes = CASE WHEN ? = 'downloading' THEN ? ELSE downloaded_bytes END,
+                total_bytes = CASE WHEN ? = 'downloading' THEN ? ELSE total_bytes END,
+                updated_at = ?
+                WHERE id = ?",
+            params![
+                status,
+                reason,
+                status,
+                downloaded_bytes,
+                status,
+                total_bytes,
+                now(),
+                id
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Records the new priority of a download.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the download.
+    /// * `priority` - The new priority.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the priority cannot be written to the database.
+    pub fn update_priority(&self, id: u64, priority: DownloadPriority) -> Result<()> {
+        let connection = self.connection.lock().unwrap();
+        connection.execute(
+            "UPDATE downloads SET priority = ?, updated_at = ? WHERE id = ?",
+            params![priority as i32, now(), id],
+        )?;
+
+        Ok(())
+    }
+
+    /// Records the new bandwidth limit of a download.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the download.
+    /// * `rate` - The new limit in bytes per second, or None for unlimited.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the limit cannot be written to the database.
+    pub fn update_rate_limit(&self, id: u64, rate: Option<u64>) -> Result<()> {
+        let connection = self.connection.lock().unwrap();
+        connection.execute(
+            "UPDATE downloads SET rate_limit = ?, updated_at = ? WHERE id = ?",
+            params![rate, now(), id],
+        )?;
+
+        Ok(())
+    }
+
+    /// Loads all the recorded downloads, ordered by ID.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the downloads cannot be read from the database.
+    pub fn load(&self) -> Result<Vec<QueuedDownload>> {
+        let connection = self.connection.lock().unwrap();
+        let mut stmt = connection.prepare(
+            "SELECT id, url, destination, priority, status, reason, downloaded_bytes, total_bytes, rate_limit
+                FROM downloads ORDER BY id",
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            let status: String = row.get(4)?;
+            let reason: Option<String> = row.get(5)?;
+
+            Ok(QueuedDownload {
+                id: row.get(0)?,
+                url: row.get(1)?,
+                destination: PathBuf::from(row.get::<_, String>(2)?),
+                priority: DownloadPriority::from_i32(row.get(3)?),
+                rate_limit: row.get(8)?,
+                status: parse_status(&status, reason, row.get(6)?, row.get(7)?),
+            })
+        })?;
+
+        let downloads = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(downloads)
+    }
+}
+
+#[cfg(not(feature = "cache"))]
+impl QueueStore {
+    /// Opens the queue stored in the given JSON file, creating it on the first change.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the JSON file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the parent directory cannot be created or the file cannot be read.
+    pub fn open(path: impl AsRef<Path> + std::fmt::Debug) -> Result<Self> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Opening download queue in {:?}", path);
+
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let downloads = match std::fs::read_to_string(path.as_ref()) {
+            Ok(content) => serde_json::from_str::<Vec<QueuedDownload>>(&content)?,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(error) => return Err(error.into()),
+        };
+
+        Ok(Self {
+            path: path.as_ref().to_path_buf(),
+            downloads: Mutex::new(
+                downloads
+                    .into_iter()
+                    .map(|download| (download.id, download))
+                    .collect(),
+            ),
+        })
+    }
+
+    /// Records a download, replacing the previous record with the same ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `download` - The download to record.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the queue cannot be written to the file.
+    pub fn save(&self, download: &QueuedDownload) -> Result<()> {
+        let mut downloads = self.downloads.lock().unwrap();
+        downloads.insert(download.id, download.clone());
+
+        self.write(&downloads)
+    }
+
+    /// Records the new status of a download.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the download.
+    /// * `status` - The new status.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the queue cannot be written to the file.
+    pub fn update_status(&self, id: u64, status: &DownloadStatus) -> Result<()> {
+        let mut downloads = self.downloads.lock().unwrap();
+        match downloads.get_mut(&id) {
+            Some(download) => download.status = status.clone(),
+            None => return Ok(()),
+        }
+
+        self.write(&downloads)
+    }
+
+    /// Records the new priority of a download.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the download.
+    /// * `priority` - The new priority.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the queue cannot be written to the file.
+    pub fn update_priority(&self, id: u64, priority: DownloadPriority) -> Result<()> {
+        let mut downloads = self.downloads.lock().unwrap();
+        match downloads.get_mut(&id) {
+            Some(download) => download.priority = priority,
+            None => return Ok(()),
+        }
+
+        self.write(&downloads)
+    }
+
+    /// Records the new bandwidth limit of a download.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the download.
+    /// * `rate` - The new limit in bytes per second, or None for unlimited.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the queue cannot be written to the file.
+    pub fn update_rate_limit(&self, id: u64, rate: Option<u64>) -> Result<()> {
+        let mut downloads = self.downloads.lock().unwrap();
+        match downloads.get_mut(&id) {
+            Some(download) => download.rate_limit = rate,
+            None => return Ok(()),
+        }
+
+        self.write(&downloads)
+    }
+
+    /// Loads all the recorded downloads, ordered by ID.
+    ///
+    /// # Errors
+    ///
+    /// This function never fails, the file being read when the queue is opened.
+    pub fn load(&self) -> Result<Vec<QueuedDownload>> {
+        let downloads = self.downloads.lock().unwrap();
+        Ok(downloads.values().cloned().collect())
+    }
+
+    /// Writes the queue to a temporary file, then replaces the JSON file with it,
+    /// so that the file is never left half written.
+    fn write(&self, downloads: &BTreeMap<u64, QueuedDownload>) -> Result<()> {
+        let json = serde_json::to_vec(&downloads.values().collect::<Vec<_>>())?;
+        let temp_path = self.path.with_extension("tmp");
+
+        let mut file = std::fs::File::create(&temp_path)?;
+        file.write_all(&json)?;
+        file.sync_all()?;
+        std::fs::rename(&temp_path, &self.path)?;
+
+        Ok(())
+    }
+}
+
+/// Splits a status into the name, reason, downloaded bytes and total bytes columns.
+#[cfg(feature = "cache")]
+fn status_columns(status: &DownloadStatus) -> (&'static str, Option<&str>, u64, u64) {
+    match status {
+        DownloadStatus::Queued => ("queued", None, 0, 0),
+        DownloadStatus::Downloading {
+            downloaded_bytes,
+            total_bytes,
+        } => ("downloading", None, *downloaded_bytes, *total_bytes),
+        DownloadStatus::Completed => ("completed", None, 0, 0),
+        DownloadStatus::Failed { reason } => ("failed", Some(reason.as_str()), 0, 0),
+        DownloadStatus::Canceled => ("canceled", None, 0, 0),
+        DownloadStatus::Paused => ("paused", None, 0, 0),
+    }
+}
+
+/// Rebuilds a status from its columns. An unknown status is considered queued.
+#[cfg(feature = "cache")]
+fn parse_status(
+    status: &str,
+    reason: Option<String>,
+    downloaded_bytes: u64,
+    total_bytes: u64,
+) -> DownloadStatus {
+    match status {
+        "downloading" => DownloadStatus::Downloading {
+            downloaded_bytes,
+            total_bytes,
+        },
+        "completed" => DownloadStatus::Completed,
+        "failed" => DownloadStatus::Failed {
+            reason: reason.unwrap_or_default(),
+        },
+        "canceled" => DownloadStatus::Canceled,
+        "paused" => DownloadStatus::Paused,
+        _ => DownloadStatus::Queued,
+    }
+}
+
+/// Returns the current Unix timestamp.
+#[cfg(feature = "cache")]
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
@@ -0,0 +1,274 @@
+//! Sources of SponsorBlock segments, from the SponsorBlock API or from a local file.
+
+use crate::error::{Error, Result};
+use crate::fetcher::Fetcher;
+use crate::model::segment::{SegmentCategory, SponsorSegment};
+use crate::network::{HttpClient, NetworkConfig};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// The URL of the public SponsorBlock API.
+pub const DEFAULT_API_URL: &str = "https://sponsor.ajay.app";
+
+/// A source of SponsorBlock segments.
+pub trait SegmentSource {
+    /// Fetches the segments of a video in the given categories, sorted by start time.
+    /// All the skippable categories are returned when `categories` is empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `video_id` - The ID of the video.
+    /// * `categories` - The categories of the segments to fetch.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the segments could not be fetched or parsed.
+    fn fetch_segments(
+        &self,
+        video_id: &str,
+        categories: &[SegmentCategory],
+    ) -> impl Future<Output = Result<Vec<SponsorSegment>>> + Send;
+}
+
+/// A client of the SponsorBlock API.
+///
+/// The video ID is not sent to the API: only the first characters of its SHA-256 hash are,
+/// and the segments of the video are picked from the matching videos.
+#[derive(Debug, Clone)]
+pub struct SponsorBlockClient {
+    /// The URL of the API, without trailing slash.
+    api_url: String,
+    /// The network configuration of the requests.
+    network: Arc<NetworkConfig>,
+    /// The HTTP client shared by the requests, if any.
+    client: Option<HttpClient>,
+}
+
+impl SponsorBlockClient {
+    /// Creates a new client of the public SponsorBlock API.
+    pub fn new() -> Self {
+        Self {
+            api_url: DEFAULT_API_URL.to_string(),
+            network: Arc::default(),
+            client: None,
+        }
+    }
+
+    /// Sets the URL of the API, e.g. for a mirror.
+    ///
+    /// # Arguments
+    ///
+    /// * `api_url` - The URL of the API, e.g. 'https://sponsor.ajay.app'.
+    pub fn with_api_url(mut self, api_url: impl AsRef<str>) -> Self {
+        self.api_url = api_url.as_ref().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Configures the proxy, certificates, timeouts and user agent of the requests.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The network configuration, e.g. the one of `Youtube`.
+    pub fn with_network(mut self, network: Arc<NetworkConfig>) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Sends the requests through a shared HTTP client, reusing its connections.
+    /// Its network configuration replaces the one given to `with_network`.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The shared client, e.g. the one of `Youtube`, or None to build a client for each request.
+    pub fn with_client(mut self, client: Option<HttpClient>) -> Self {
+        if let Some(client) = &client {
+            self.network = client.network().clone();
+        }
+
+        self.client = client;
+        self
+    }
+}
+
+impl Default for SponsorBlockClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Implementation of PartialEq for SponsorBlockClient, the shared client having the same network
+impl PartialEq for SponsorBlockClient {
+    fn eq(&self, other: &Self) -> bool {
+        self.api_url == other.api_url && self.network == other.network
+    }
+}
+
+// Implementation of Eq for SponsorBlockClient
+impl Eq for SponsorBlockClient {}
+
+// Implementation of Hash for SponsorBlockClient
+impl Hash for SponsorBlockClient {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.api_url.hash(state);
+        self.network.hash(state);
+    }
+}
+
+impl SegmentSource for SponsorBlockClient {
+    async fn fetch_segments(
+        &self,
+        video_id: &str,
+        categories: &[SegmentCategory],
+    ) -> Result<Vec<SponsorSegment>> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Fetching SponsorBlock segments of video {}", video_id);
+
+        let hash = format!("{:x}", Sha256::digest(video_id.as_bytes()));
+        let query: Vec<String> = categories_or_default(categories)
+            .iter()
+            .map(|category| format!("category={}", category))
+            .collect();
+        let url = format!(
+            "{}/api/skipSegments/{}?{}",
+            self.api_url,
+            &hash[..4],
+            query.join("&")
+        );
+
+        let fetcher = Fetcher::new(&url)
+            .with_network(self.network.clone())
+            .with_client(self.client.clone());
+        let json = match fetcher.fetch_json(None).await {
+            Ok(json) => json,
+            // The API answers 404 when no video matches
+            Err(Error::Reqwest(e)) if e.status() == Some(reqwest::StatusCode::NOT_FOUND) => {
+                return Ok(Vec::new());
+            }
+            Err(e) => return Err(e),
+        };
+
+        let videos: Vec<VideoSegments> = serde_json::from_value(json)?;
+        let segments = videos
+            .into_iter()
+            .filter(|video| video.video_id == video_id)
+            .flat_map(|video| video.segments)
+            .collect();
+
+        Ok(filter_segments(segments, categories))
+    }
+}
+
+/// A local JSON file of SponsorBlock segments, e.g. to work offline or to use custom segments.
+///
+/// The file holds either the segments of a single video, as returned by
+/// '/api/skipSegments?videoID=...', or the segments of several videos,
+/// as returned by '/api/skipSegments/{hash}', each with its 'videoID'.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SegmentFile {
+    /// The path of the JSON file.
+    path: PathBuf,
+}
+
+impl SegmentFile {
+    /// Creates a new source reading the given file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the JSON file.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl SegmentSource for SegmentFile {
+    async fn fetch_segments(
+        &self,
+        video_id: &str,
+        categories: &[SegmentCategory],
+    ) -> Result<Vec<SponsorSegment>> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            "Reading SponsorBlock segments of video {} from {:?}",
+            video_id,
+            self.path
+        );
+
+        let content = tokio::fs::read_to_string(&self.path).await?;
+        let segments = match serde_json::from_str(&content)? {
+            SegmentDocument::Videos(videos) => videos
+                .into_iter()
+                .filter(|video| video.video_id == video_id)
+                .flat_map(|video| video.segments)
+                .collect(),
+            SegmentDocument::Segments(segments) => segments,
+        };
+
+        Ok(filter_segments(segments, categories))
+    }
+}
+
+/// The segments of a video, as returned by the hashed endpoint of the API.
+#[derive(Debug, Deserialize)]
+struct VideoSegments {
+    /// The ID of the video.
+    #[serde(rename = "videoID")]
+    video_id: String,
+    /// The segments of the video.
+    segments: Vec<SponsorSegment>,
+}
+
+/// The content of a segment file.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum SegmentDocument {
+    /// The segments of several videos.
+    Videos(Vec<VideoSegments>),
+    /// The segments of a single video.
+    Segments(Vec<SponsorSegment>),
+}
+
+/// Returns the given categories, or all the skippable categories if none is given.
+fn categories_or_default(categories: &[SegmentCategory]) -> &[SegmentCategory] {
+    if categories.is_empty() {
+        &SegmentCategory::SKIPPABLE
+    } else {
+        categories
+    }
+}
+
+/// Keeps the segments of the given categories, sorted by start time.
+fn filter_segments(
+    segments: Vec<SponsorSegment>,
+    categories: &[SegmentCategory],
+) -> Vec<SponsorSegment> {
+    let categories = categories_or_default(categories);
+
+    let mut segments: Vec<SponsorSegment> = segments
+        .into_iter()
+        .filter(|segment| categories.contains(&segment.category))
+        .collect();
+    segments.sort_by(|a, b| a.start_time().total_cmp(&b.start_time()));
+
+    segments
+}
+
+// Implementation of the Display trait for SponsorBlockClient
+impl fmt::Display for SponsorBlockClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SponsorBlockClient(api_url={})", self.api_url)
+    }
+}
+
+// Implementation of the Display trait for SegmentFile
+impl fmt::Display for SegmentFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SegmentFile(path={})", self.path.display())
+    }
+}
@@ -1,10 +1,12 @@
 //! Tools for fetching video streams from YouTube.
 
 use crate::error::Error;
+use crate::events::{DownloadEvent, EventEmitter};
 use crate::executor::Executor;
-use crate::fetcher::Fetcher;
+use crate::fetcher::manifest::ManifestFetcher;
+use crate::fetcher::{Fetcher, ProgressCallback};
 use crate::model::Video;
-use crate::model::format::Format;
+use crate::model::format::{Extension, Format};
 #[cfg(feature = "cache")]
 use crate::model::format_selector::{
     AudioCodecPreference, AudioQuality, VideoCodecPreference, VideoQuality,
@@ -49,23 +51,60 @@ impl Youtube {
         // Check if the video is in the cache
         #[cfg(feature = "cache")]
         if let Some(cache) = &self.cache {
-            if let Some(video) = cache.get(&url) {
+            if let Some(mut video) = cache.get(&url) {
                 #[cfg(feature = "tracing")]
                 tracing::debug!("Using cached video information for {}", url);
+
+                video.format_sort = self.format_sort.clone();
                 return Ok(video);
             }
         }
 
         // If the video is not in the cache, retrieve it from YouTube
-        let download_args = vec!["--no-progress", "--dump-json", &url];
+        let video = self.dump_video_infos(&url, &[]).await?;
+
+        // Put the video in the cache if caching is enabled, except live streams whose formats expire quickly
+        #[cfg(feature = "cache")]
+        if let Some(cache) = self
+            .cache
+            .as_ref()
+            .filter(|_| !video.is_live() && !video.is_upcoming())
+        {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("Caching video information for {}", url);
 
-        let mut final_args = self.args.clone();
+            if let Err(_e) = cache.put(url.clone(), video.clone()) {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("Failed to cache video information: {}", _e);
+            }
+        }
+
+        Ok(video)
+    }
+
+    /// Runs 'yt-dlp' to fetch the video information from the given URL, without using the cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the video to fetch.
+    /// * `extra_args` - The arguments to pass to 'yt-dlp', in addition to the configured ones.
+    pub(crate) async fn dump_video_infos(
+        &self,
+        url: &str,
+        extra_args: &[&str],
+    ) -> crate::error::Result<Video> {
+        let mut download_args = vec!["--no-progress"];
+        download_args.extend_from_slice(extra_args);
+        download_args.extend(["--dump-json", url]);
+
+        let mut final_args = self.yt_dlp_args();
         final_args.append(&mut utils::to_owned(download_args));
 
         let executor = Executor {
             executable_path: self.libraries.youtube.clone(),
             timeout: self.timeout,
             args: final_args,
+            env: self.network.yt_dlp_env(),
         };
 
         let output = executor.execute().await?;
@@ -75,18 +114,7 @@ impl Youtube {
         for format in &mut video.formats {
             format.video_id = Some(video.id.clone());
         }
-
-        // Put the video in the cache if caching is enabled
-        #[cfg(feature = "cache")]
-        if let Some(cache) = &self.cache {
-            #[cfg(feature = "tracing")]
-            tracing::debug!("Caching video information for {}", url);
-
-            if let Err(_e) = cache.put(url.clone(), video.clone()) {
-                #[cfg(feature = "tracing")]
-                tracing::warn!("Failed to cache video information: {}", _e);
-            }
-        }
+        video.format_sort = self.format_sort.clone();
 
         Ok(video)
     }
@@ -484,6 +512,7 @@ impl Youtube {
             executable_path: self.libraries.ffmpeg.clone(),
             timeout: self.timeout,
             args: utils::to_owned(args),
+            env: Vec::new(),
         };
 
         executor.execute().await?;
@@ -565,9 +594,9 @@ impl Youtube {
         // Use the internal function to download the format without preferences
         cfg_if::cfg_if! {
             if #[cfg(feature = "cache")] {
-                self.download_format_internal(format, &output_path, None, None, None, None).await
+                self.download_format_internal(format, &output_path, None, None, None, None, None, None).await
             } else {
-                self.download_format_internal(format, &output_path).await
+                self.download_format_internal(format, &output_path, None, None).await
             }
         }
     }
@@ -614,10 +643,12 @@ impl Youtube {
                     audio_quality,
                     video_codec,
                     audio_codec,
+                    None,
+                    None,
                 )
                 .await
             } else {
-                self.download_format_internal(format, &output_path).await
+                self.download_format_internal(format, &output_path, None, None).await
             }
         }
     }
@@ -625,7 +656,10 @@ impl Youtube {
     /// Internal function that handles downloading a format with or without preferences
     ///
     /// This function avoids code duplication between download_format and download_format_with_preferences
-    async fn download_format_internal(
+    /// The optional progress callback is called with the downloaded size and total size,
+    /// and the stages of the download are published with the optional event emitter.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn download_format_internal(
         &self,
         format: &Format,
         path: &PathBuf,
@@ -633,7 +667,18 @@ impl Youtube {
         #[cfg(feature = "cache")] audio_quality: Option<AudioQuality>,
         #[cfg(feature = "cache")] video_codec: Option<VideoCodecPreference>,
         #[cfg(feature = "cache")] audio_codec: Option<AudioCodecPreference>,
+        events: Option<&EventEmitter>,
+        progress: Option<ProgressCallback>,
     ) -> crate::error::Result<PathBuf> {
+        let emit = |event| {
+            if let Some(events) = events {
+                events.emit(event);
+            }
+        };
+        emit(DownloadEvent::StreamStarted {
+            stream: format.format_id.clone(),
+        });
+
         // Check if we have specific preferences
         #[cfg(feature = "cache")]
         let has_preferences = video_quality.is_some()
@@ -653,7 +698,14 @@ impl Youtube {
                     tracing::debug!("Using cached format by ID: {}", format.format_id);
 
                     // Copy the file from the cache to the output directory
-                    tokio::fs::copy(&cached_path, path).await?;
+                    let size = tokio::fs::copy(&cached_path, path).await?;
+                    if let Some(progress) = &progress {
+                        progress(size, size);
+                    }
+                    emit(DownloadEvent::StreamCompleted {
+                        stream: format.format_id.clone(),
+                        path: path.clone(),
+                    });
                     return Ok(path.clone());
                 }
 
@@ -670,32 +722,72 @@ impl Youtube {
                         tracing::debug!("Using cached format by preferences");
 
                         // Copy the file from the cache to the output directory
-                        tokio::fs::copy(&cached_path, path).await?;
+                        let size = tokio::fs::copy(&cached_path, path).await?;
+                        if let Some(progress) = &progress {
+                            progress(size, size);
+                        }
+                        emit(DownloadEvent::StreamCompleted {
+                            stream: format.format_id.clone(),
+                            path: path.clone(),
+                        });
                         return Ok(path.clone());
                     }
                 }
             }
         }
 
-        // Check if URL is available
-        let url = format
-            .download_info
-            .url
-            .clone()
-            .ok_or(Error::MissingUrl(format.format_id.clone()))?;
+        if let Some(manifest_fetcher) = ManifestFetcher::for_format(format) {
+            // Manifest formats are made of fragments, listed by an HLS playlist or a DASH manifest
+            let mut manifest_fetcher = manifest_fetcher
+                .with_parallel_fragments(8)
+                .with_retry_attempts(3)
+                .with_client(Some(self.http_client.clone()))
+                .with_rate_limiter(self.download_manager.rate_limiter().clone());
+
+            if let Some(progress) = progress {
+                manifest_fetcher = manifest_fetcher
+                    .with_progress_callback(move |downloaded, total| progress(downloaded, total));
+            }
+            if let Some(events) = events {
+                manifest_fetcher = manifest_fetcher.with_events(events.clone(), &format.format_id);
+            }
 
-        // Create an optimized fetcher with parallel downloading
-        let fetcher = Fetcher::new(&url)
-            .with_parallel_segments(8) // Use 8 parallel segments
-            .with_segment_size(1024 * 1024 * 5) // 5 MB per segment
-            .with_retry_attempts(3); // 3 attempts in case of failure
+            self.fetch_manifest_format(&manifest_fetcher, path).await?;
+        } else {
+            // Check if URL is available
+            let url = format
+                .download_info
+                .url
+                .clone()
+                .ok_or(Error::MissingUrl(format.format_id.clone()))?;
+
+            // Create an optimized fetcher with parallel downloading
+            let mut fetcher = Fetcher::new(&url)
+                .with_parallel_segments(8) // Use 8 parallel segments
+                .with_segment_size(1024 * 1024 * 5) // 5 MB per segment
+                .with_retry_attempts(3) // 3 attempts in case of failure
+                .with_client(Some(self.http_client.clone()))
+                .with_rate_limiter(self.download_manager.rate_limiter().clone());
+
+            if let Some(progress) = progress {
+                fetcher = fetcher
+                    .with_progress_callback(move |downloaded, total| progress(downloaded, total));
+            }
+            if let Some(events) = events {
+                fetcher = fetcher.with_events(events.clone(), &format.format_id);
+            }
 
-        fetcher.fetch_asset(path.clone()).await?;
+            fetcher.fetch_asset(path.clone()).await?;
+        }
+        emit(DownloadEvent::StreamCompleted {
+            stream: format.format_id.clone(),
+            path: path.clone(),
+        });
 
         // Don't add metadata for video or audio streams that will be combined later
         // Only add metadata for standalone formats that contain both
         // audio and video, or for audio-only formats intended for direct use
-        self.add_metadata_if_needed(path, format).await?;
+        self.add_metadata_if_needed(path, format, events).await?;
 
         // Cache the downloaded file if caching is enabled
         #[cfg(feature = "cache")]
@@ -708,6 +800,7 @@ impl Youtube {
 
             #[cfg(feature = "tracing")]
             tracing::debug!("Caching format with ID: {}", format.format_id);
+            emit(DownloadEvent::Caching { path: path.clone() });
 
             // Use the appropriate function depending on whether we have preferences or not
             if has_preferences {
@@ -741,22 +834,123 @@ impl Youtube {
         Ok(path.clone())
     }
 
+    /// Downloads the fragments of a manifest format into the given path.
+    /// MPEG-TS streams are remuxed with FFmpeg, unless they are written to a '.ts' file.
+    async fn fetch_manifest_format(
+        &self,
+        fetcher: &ManifestFetcher,
+        path: &Path,
+    ) -> crate::error::Result<()> {
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or(Error::Path("Invalid output path".to_string()))?;
+        let part_path = path.with_file_name(format!("{}.part", file_name));
+
+        let download = fetcher.fetch_manifest(&part_path).await?;
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            "Downloaded {} fragments ({} bytes) from {:?} manifest",
+            download.fragments,
+            download.size,
+            download.kind
+        );
+
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase())
+            .unwrap_or_default();
+        if !download.is_transport_stream || extension == "ts" {
+            tokio::fs::rename(&part_path, path).await?;
+            return Ok(());
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Remuxing MPEG-TS stream into {:?}", path);
+
+        let mut args = utils::to_owned(vec![
+            "-i",
+            &crate::postprocessor::path_arg(&part_path)?,
+            "-map",
+            "0",
+            "-c",
+            "copy",
+        ]);
+        // MPEG-TS streams carry AAC with ADTS headers, which MP4 files do not accept
+        if matches!(extension.as_str(), "mp4" | "m4a" | "mov") {
+            args.extend(utils::to_owned(vec!["-bsf:a", "aac_adtstoasc"]));
+        }
+        args.push(crate::postprocessor::path_arg(path)?);
+
+        let executor = Executor {
+            executable_path: self.libraries.ffmpeg.clone(),
+            timeout: crate::postprocessor::remux_timeout(self.timeout, download.size),
+            args,
+            env: Vec::new(),
+        };
+        // The stream is kept when it could not be remuxed, so that it can still be remuxed by hand
+        if let Err(e) = executor.execute().await {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("Failed to remux MPEG-TS stream, keeping {:?}", part_path);
+
+            return Err(e);
+        }
+
+        if let Err(_e) = tokio::fs::remove_file(&part_path).await {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("Failed to remove downloaded MPEG-TS stream: {}", _e);
+        }
+
+        Ok(())
+    }
+
     /// Adds format metadata based on the format type (audio-only, video-only, or both)
     /// This function is extracted to avoid code duplication
     async fn add_metadata_if_needed(
         &self,
         path: impl AsRef<Path>,
         format: &Format,
+        events: Option<&EventEmitter>,
     ) -> crate::error::Result<()> {
         let format_type = format.format_type();
         let is_standalone_format = format_type.is_audio_and_video() || format_type.is_audio();
 
+        // Tags are written according to the file extension, so a stream saved under the
+        // extension of another container, e.g. a WebM audio stream saved as '.mp3', is left
+        // untouched until it is converted
+        let extensions: &[&str] = match format.download_info.ext {
+            Extension::M4A | Extension::Mp4 => &["m4a", "m4b", "m4p", "m4v", "mp4"],
+            Extension::Webm => &["webm", "mkv", "mka"],
+            _ => &[],
+        };
+        let extension = path
+            .as_ref()
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase())
+            .unwrap_or_default();
+        if !extensions.is_empty() && !extensions.contains(&extension.as_str()) {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                "Skipping metadata: the stream is not a '.{}' file, it must be converted first",
+                extension
+            );
+            return Ok(());
+        }
+
         if is_standalone_format {
             if let Some(video_id) = format.video_id.as_ref() {
                 // Get the video metadata from the cache
                 if let Some(video) = self.get_video_by_id(video_id).await {
                     #[cfg(feature = "tracing")]
                     tracing::debug!("Adding metadata to standalone file with format preferences");
+                    if let Some(events) = events {
+                        events.emit(DownloadEvent::Tagging {
+                            path: path.as_ref().to_path_buf(),
+                        });
+                    }
 
                     // Use the method with format information for richer metadata
                     // Add metadata, log error on failure, then propagate
@@ -802,7 +996,10 @@ impl Youtube {
             if let Ok(cached_video) = cache.get_by_id(video_id) {
                 #[cfg(feature = "tracing")]
                 tracing::debug!("Using cached video data for ID: {}", video_id);
-                return Some(cached_video.video);
+
+                let mut video = cached_video.video;
+                video.format_sort = self.format_sort.clone();
+                return Some(video);
             }
         }
 
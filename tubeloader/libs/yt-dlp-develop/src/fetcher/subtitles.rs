@@ -0,0 +1,347 @@
+//! Tools for downloading subtitles and automatic captions from YouTube, and embedding them.
+
+use crate::caption::Captions;
+use crate::error::{Error, Result};
+use crate::executor::Executor;
+use crate::fetcher::Fetcher;
+use crate::model::Video;
+use crate::model::caption::{AutomaticCaption, Extension, SubtitleTrack};
+use crate::postprocessor::path_arg;
+use crate::{Youtube, utils};
+use std::fmt::Display;
+use std::path::PathBuf;
+
+/// The formats that SRT and WebVTT subtitles are converted from, in order of preference.
+const CONVERSION_SOURCES: [Extension; 7] = [
+    Extension::Json3,
+    Extension::Srv3,
+    Extension::Vtt,
+    Extension::Srv2,
+    Extension::Srv1,
+    Extension::Ttml,
+    Extension::Srt,
+];
+
+impl Youtube {
+    /// Downloads the subtitles of the video in the given languages.
+    /// Manual subtitles are preferred over automatic captions, and a language such as 'en'
+    /// also matches regional variants such as 'en-US' when there is no exact match.
+    /// Languages without any subtitle are skipped.
+    ///
+    /// YouTube does not serve SRT files, so SRT subtitles are converted from another format,
+    /// and the rolling cues of automatic captions are merged.
+    /// The files are written to the output directory as '{id}.{language}.{extension}'.
+    ///
+    /// # Arguments
+    ///
+    /// * `video` - The video to download the subtitles of.
+    /// * `languages` - The language codes of the subtitles, e.g. 'ko' or 'en', in order of preference.
+    /// * `format` - The extension of the subtitle files, e.g. `Extension::Srt`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a subtitle could not be downloaded or converted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::model::caption::Extension;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url).await?;
+    ///
+    /// let tracks = fetcher.download_subtitles(&video, &["ko", "en"], Extension::Srt).await?;
+    /// for track in &tracks {
+    ///     println!("{}", track);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_subtitles(
+        &self,
+        video: &Video,
+        languages: &[impl AsRef<str>],
+        format: Extension,
+    ) -> Result<Vec<SubtitleTrack>> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Downloading {} subtitles for {}", format, video.id);
+
+        let mut tracks = Vec::new();
+
+        for language in languages {
+            let Some((language, captions, automatic)) = find_subtitles(video, language.as_ref())
+            else {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("No subtitle found for language {}", language.as_ref());
+                continue;
+            };
+
+            // The same subtitle may be requested twice, e.g. with 'en' and 'en-US'
+            if tracks
+                .iter()
+                .any(|track: &SubtitleTrack| track.language == language)
+            {
+                continue;
+            }
+
+            // Formats not served by YouTube, such as SRT, are converted from another format
+            let requested = captions.iter().find(|c| c.extension == format);
+            let writable = matches!(format, Extension::Srt | Extension::Vtt);
+            let (caption, convert) = match requested {
+                Some(caption) => (caption, false),
+                None if writable => {
+                    let source = CONVERSION_SOURCES
+                        .iter()
+                        .find_map(|extension| captions.iter().find(|c| c.extension == *extension));
+                    match source {
+                        Some(caption) => (caption, true),
+                        None => continue,
+                    }
+                }
+                None => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!("No {} subtitle found for language {}", format, language);
+                    continue;
+                }
+            };
+
+            let path = self
+                .output_dir
+                .join(format!("{}.{}.{}", video.id, language, caption.extension));
+            Fetcher::new(&caption.url)
+                .with_client(Some(self.http_client.clone()))
+                .fetch_asset(path.clone())
+                .await?;
+
+            let path = if convert {
+                let mut parsed = Captions::from_file(&path)?;
+                if automatic {
+                    parsed.merge_overlapping();
+                }
+
+                let converted = path.with_extension(format.to_string());
+                tokio::fs::write(&converted, parsed.serialize(&format)?).await?;
+                tokio::fs::remove_file(&path).await?;
+                converted
+            } else {
+                path
+            };
+
+            tracks.push(SubtitleTrack {
+                language: language.to_string(),
+                name: caption.name.clone(),
+                extension: format.clone(),
+                automatic,
+                path,
+            });
+        }
+
+        Ok(tracks)
+    }
+
+    /// Embeds subtitle tracks into a video file, with their language tags.
+    /// The subtitles are converted to the text format of the container:
+    /// 'mov_text' for MP4, 'srt' for MKV, and 'webvtt' for WebM.
+    ///
+    /// # Arguments
+    ///
+    /// * `video_file` - The name of the video file in the output directory, replaced in place.
+    /// * `tracks` - The subtitle tracks to embed, in SRT or VTT format.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the container or a subtitle format is not supported,
+    /// or if ffmpeg failed to mux the subtitles.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::model::caption::Extension;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url.clone()).await?;
+    ///
+    /// fetcher.download_video_from_url(url, "my-video.mp4").await?;
+    /// let tracks = fetcher.download_subtitles(&video, &["ko", "en"], Extension::Vtt).await?;
+    /// let video_path = fetcher.embed_subtitles("my-video.mp4", &tracks).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn embed_subtitles(
+        &self,
+        video_file: impl AsRef<str> + std::fmt::Debug + Display,
+        tracks: &[SubtitleTrack],
+    ) -> Result<PathBuf> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Embedding {} subtitles into {}", tracks.len(), video_file);
+
+        let video_path = self.output_dir.join(video_file.as_ref());
+        if tracks.is_empty() {
+            return Ok(video_path);
+        }
+
+        let container = video_path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase())
+            .unwrap_or_default();
+        let codec = match container.as_str() {
+            "mp4" | "m4v" | "mov" => "mov_text",
+            "mkv" => "srt",
+            "webm" => "webvtt",
+            _ => {
+                return Err(Error::IncompatibleFormat(
+                    container,
+                    "subtitles can only be embedded in MP4, MKV or WebM files".to_string(),
+                ));
+            }
+        };
+
+        if let Some(track) = tracks.iter().find(|t| !t.extension.is_ffmpeg_readable()) {
+            return Err(Error::IncompatibleFormat(
+                track.extension.to_string(),
+                "only SRT and VTT subtitles can be embedded".to_string(),
+            ));
+        }
+
+        let temp_path = video_path.with_extension(format!("subs.{}", container));
+
+        let mut args = vec!["-y".to_string(), "-i".to_string(), path_arg(&video_path)?];
+        for track in tracks {
+            args.push("-i".to_string());
+            args.push(path_arg(&track.path)?);
+        }
+
+        args.extend(utils::to_owned(vec!["-map", "0:v?", "-map", "0:a?"]));
+        for index in 1..=tracks.len() {
+            args.push("-map".to_string());
+            args.push(index.to_string());
+        }
+
+        args.extend(utils::to_owned(vec!["-c", "copy", "-c:s", codec]));
+        for (index, track) in tracks.iter().enumerate() {
+            args.push(format!("-metadata:s:s:{}", index));
+            args.push(format!("language={}", iso639_2(&track.language)));
+            if let Some(name) = &track.name {
+                args.push(format!("-metadata:s:s:{}", index));
+                args.push(format!("title={}", name));
+            }
+        }
+        args.push(path_arg(&temp_path)?);
+
+        let executor = Executor {
+            executable_path: self.libraries.ffmpeg.clone(),
+            timeout: self.timeout,
+            args,
+            env: Vec::new(),
+        };
+
+        if let Err(e) = executor.execute().await {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            return Err(e);
+        }
+
+        tokio::fs::rename(&temp_path, &video_path).await?;
+        Ok(video_path)
+    }
+}
+
+/// Finds the subtitles of a language, preferring manual subtitles over automatic captions.
+/// Returns the matched language code, the available files, and whether they are automatic captions.
+fn find_subtitles<'a>(
+    video: &'a Video,
+    language: &str,
+) -> Option<(&'a str, &'a [AutomaticCaption], bool)> {
+    let sources = [(&video.subtitles, false), (&video.automatic_captions, true)];
+    let prefix = format!("{}-", language);
+
+    // Exact matches first, then regional variants, e.g. 'en' matching 'en-US'
+    let exact = sources.iter().find_map(|(subtitles, automatic)| {
+        subtitles
+            .get_key_value(language)
+            .map(|(key, captions)| (key.as_str(), captions.as_slice(), *automatic))
+    });
+
+    exact.or_else(|| {
+        sources.iter().find_map(|(subtitles, automatic)| {
+            let mut variants: Vec<_> = subtitles
+                .iter()
+                .filter(|(key, _)| key.starts_with(&prefix))
+                .collect();
+            variants.sort_by_key(|(key, _)| key.as_str());
+
+            variants
+                .first()
+                .map(|(key, captions)| (key.as_str(), captions.as_slice(), *automatic))
+        })
+    })
+}
+
+/// Converts a language code such as 'en' or 'en-US' to the ISO 639-2 code used by containers.
+pub(crate) fn iso639_2(language: &str) -> String {
+    let base = language
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    let code = match base.as_str() {
+        "ar" => "ara",
+        "cs" => "cze",
+        "da" => "dan",
+        "de" => "ger",
+        "el" => "gre",
+        "en" => "eng",
+        "es" => "spa",
+        "fa" => "per",
+        "fi" => "fin",
+        "fr" => "fre",
+        "he" | "iw" => "heb",
+        "hi" => "hin",
+        "hu" => "hun",
+        "id" => "ind",
+        "it" => "ita",
+        "ja" => "jpn",
+        "ko" => "kor",
+        "ms" => "may",
+        "nl" => "dut",
+        "no" | "nb" => "nor",
+        "pl" => "pol",
+        "pt" => "por",
+        "ro" => "rum",
+        "ru" => "rus",
+        "sv" => "swe",
+        "th" => "tha",
+        "tl" => "fil",
+        "tr" => "tur",
+        "uk" => "ukr",
+        "vi" => "vie",
+        "zh" => "chi",
+        code if code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic()) => code,
+        _ => "und",
+    };
+
+    code.to_string()
+}
@@ -124,7 +124,8 @@ impl Youtube {
         let fetcher = Fetcher::new(&best_thumbnail.url)
             .with_parallel_segments(4) // Use 4 parallel segments for thumbnails
             .with_segment_size(1024 * 1024) // 1 MB per segment
-            .with_retry_attempts(3); // 3 attempts in case of failure
+            .with_retry_attempts(3) // 3 attempts in case of failure
+            .with_client(Some(self.http_client.clone()));
 
         fetcher.fetch_asset(path.clone()).await?;
 
@@ -171,7 +172,7 @@ impl Youtube {
             }
         }
 
-        let fetcher = Fetcher::new(&video.thumbnail);
+        let fetcher = Fetcher::new(&video.thumbnail).with_client(Some(self.http_client.clone()));
         fetcher.fetch_asset(path.clone()).await?;
 
         // Cache the downloaded thumbnail if caching is enabled
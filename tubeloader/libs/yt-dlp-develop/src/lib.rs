@@ -1,24 +1,39 @@
 #![doc = include_str!("../README.md")]
 
+use crate::cookies::Cookies;
 use crate::error::{Error, Result};
+use crate::events::{DownloadEvent, DownloadUpdate, EventEmitter};
 use crate::executor::Executor;
+use crate::fetcher::ProgressCallback;
 use crate::fetcher::deps::{Libraries, LibraryInstaller};
 use crate::fetcher::download_manager::{DownloadManager, ManagerConfig};
+use crate::model::sort::FormatSort;
+use crate::network::{HttpClient, NetworkConfig};
+use crate::postprocessor::merge::{MergeContainer, MergePlan};
 use crate::utils::file_system;
 #[cfg(feature = "cache")]
 use cache::{DownloadCache, VideoCache};
+use std::borrow::Cow;
 use std::fmt::{self, Display};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::broadcast;
 
+pub mod archive;
 #[cfg(feature = "cache")]
 pub mod cache;
+pub mod caption;
+pub mod cookies;
 pub mod error;
+pub mod events;
 pub mod executor;
 pub mod fetcher;
 pub mod metadata;
 pub mod model;
+pub mod network;
+pub mod postprocessor;
+pub mod rate_limit;
 pub mod utils;
 
 // Re-export of common traits to facilitate their use
@@ -65,10 +80,12 @@ pub struct Youtube {
 
     /// The directory where the video (or formats) will be downloaded.
     pub output_dir: PathBuf,
-    /// The arguments to pass to 'yt-dlp'.
+    /// The arguments to pass to 'yt-dlp', after the ones of the network configuration and cookies.
     pub args: Vec<String>,
     /// The timeout for command execution.
     pub timeout: Duration,
+    /// The sort order used to rank the formats of the fetched videos.
+    pub format_sort: FormatSort,
     /// The cache for video metadata.
     #[cfg(feature = "cache")]
     pub cache: Option<Arc<cache::VideoCache>>,
@@ -77,6 +94,12 @@ pub struct Youtube {
     pub download_cache: Option<Arc<cache::DownloadCache>>,
     /// The download manager for managing parallel downloads.
     pub download_manager: Arc<DownloadManager>,
+    /// The cookies given to 'yt-dlp' and sent with the downloads.
+    pub cookies: Option<Arc<Cookies>>,
+    /// The network configuration of 'yt-dlp' and the downloads.
+    pub network: Arc<NetworkConfig>,
+    /// The HTTP client shared by the downloads, the thumbnails, the subtitles and the download manager.
+    pub http_client: HttpClient,
 }
 
 impl fmt::Display for Youtube {
@@ -138,19 +161,25 @@ impl Youtube {
         #[cfg(feature = "cache")]
         let download_cache = DownloadCache::new(cache_dir, None)?;
 
-        // Initialize download manager with default configuration
+        // Initialize download manager with default configuration, sharing the HTTP client
+        let http_client = HttpClient::new(Arc::default(), None)?;
         let download_manager = DownloadManager::new();
+        download_manager.set_client(http_client.clone());
 
         Ok(Self {
             libraries,
             output_dir: output_dir.as_ref().to_path_buf(),
             args: Vec::new(),
             timeout: Duration::from_secs(30),
+            format_sort: FormatSort::default(),
             #[cfg(feature = "cache")]
             cache: Some(Arc::new(cache)),
             #[cfg(feature = "cache")]
             download_cache: Some(Arc::new(download_cache)),
             download_manager: Arc::new(download_manager),
+            cookies: None,
+            network: Arc::default(),
+            http_client,
         })
     }
 
@@ -183,19 +212,25 @@ impl Youtube {
         #[cfg(feature = "cache")]
         let download_cache = DownloadCache::new(cache_dir, None)?;
 
-        // Initialize download manager with custom configuration
+        // Initialize download manager with custom configuration, sharing the HTTP client
+        let http_client = HttpClient::new(Arc::default(), None)?;
         let download_manager = DownloadManager::with_config(download_manager_config);
+        download_manager.set_client(http_client.clone());
 
         Ok(Self {
             libraries,
             output_dir: output_dir.as_ref().to_path_buf(),
             args: Vec::new(),
             timeout: Duration::from_secs(30),
+            format_sort: FormatSort::default(),
             #[cfg(feature = "cache")]
             cache: Some(Arc::new(cache)),
             #[cfg(feature = "cache")]
             download_cache: Some(Arc::new(download_cache)),
             download_manager: Arc::new(download_manager),
+            cookies: None,
+            network: Arc::default(),
+            http_client,
         })
     }
 
@@ -229,11 +264,54 @@ impl Youtube {
     pub async fn with_new_binaries(
         executables_dir: impl AsRef<Path> + std::fmt::Debug,
         output_dir: impl AsRef<Path> + std::fmt::Debug,
+    ) -> Result<Self> {
+        Self::with_new_binaries_and_network(executables_dir, output_dir, NetworkConfig::default())
+            .await
+    }
+
+    /// Creates a new YouTube fetcher with the given network configuration, and installs the yt-dlp
+    /// and ffmpeg binaries through it, e.g. behind a proxy.
+    /// Be careful, this function may take a while to execute.
+    ///
+    /// # Arguments
+    ///
+    /// * `executables_dir` - The directory where the binaries will be installed.
+    /// * `output_dir` - The directory where the video will be downloaded.
+    /// * `network` - The network configuration of the installation, 'yt-dlp' and the downloads.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the network configuration is invalid,
+    /// or if the executables could not be installed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::network::NetworkConfig;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let executables_dir = PathBuf::from("libs");
+    /// let output_dir = PathBuf::from("output");
+    /// let network = NetworkConfig::new().with_proxy("http://proxy.local:3128");
+    ///
+    /// let fetcher = Youtube::with_new_binaries_and_network(executables_dir, output_dir, network).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn with_new_binaries_and_network(
+        executables_dir: impl AsRef<Path> + std::fmt::Debug,
+        output_dir: impl AsRef<Path> + std::fmt::Debug,
+        network: NetworkConfig,
     ) -> Result<Self> {
         #[cfg(feature = "tracing")]
         tracing::debug!("Creating a new video fetcher with binaries installation");
 
-        let installer = LibraryInstaller::new(executables_dir.as_ref().to_path_buf());
+        // Fail before installing anything if the configuration is invalid
+        let http_client = HttpClient::new(Arc::new(network), None)?;
+        let installer = LibraryInstaller::new(executables_dir.as_ref().to_path_buf())
+            .with_client(Some(http_client.clone()));
 
         // Check if binaries already exist
         let youtube_path = executables_dir
@@ -256,7 +334,10 @@ impl Youtube {
         };
 
         let libraries = Libraries::new(youtube, ffmpeg);
-        Self::new(libraries, output_dir)
+        let mut fetcher = Self::new(libraries, output_dir)?;
+        fetcher.apply_client(http_client);
+
+        Ok(fetcher)
     }
 
     /// Sets the arguments to pass to yt-dlp.
@@ -322,6 +403,42 @@ impl Youtube {
         self
     }
 
+    /// Sets the sort order used to rank the formats of the fetched videos.
+    /// It drives every format selection, before the default ranking and the quality preferences.
+    ///
+    /// # Arguments
+    ///
+    /// * `format_sort` - The format sort order, e.g. `res:1080,vcodec:avc1,+size`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::model::sort::FormatSort;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let mut fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// // Prefer H.264 up to 1080p, for older devices
+    /// fetcher.with_format_sort(FormatSort::new("res:1080,vcodec:avc1")?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_format_sort(&mut self, format_sort: FormatSort) -> &mut Self {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Setting format sort: {}", format_sort);
+
+        self.format_sort = format_sort;
+        self
+    }
+
     /// Adds an argument to pass to yt-dlp.
     ///
     /// # Arguments
@@ -352,6 +469,163 @@ impl Youtube {
         self
     }
 
+    /// Sets the cookies of an authenticated session, to download age-restricted or members-only videos.
+    /// The cookies file is given to 'yt-dlp' with '--cookies', and the cookies are sent with the
+    /// downloads of the formats, thumbnails and subtitles, by a new shared HTTP client.
+    /// They replace the cookies set before.
+    ///
+    /// # Arguments
+    ///
+    /// * `cookies` - The cookies, read from a Netscape 'cookies.txt' file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the path of the cookies file is not valid UTF-8,
+    /// or if the HTTP client could not be built.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::cookies::Cookies;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let mut fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// fetcher.with_cookies(Cookies::from_file("cookies.txt")?)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_cookies(&mut self, cookies: Cookies) -> Result<&mut Self> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Using {}", cookies);
+
+        cookies
+            .path()
+            .to_str()
+            .ok_or(Error::Path(format!("{:?}", cookies.path())))?;
+        let cookies = Arc::new(cookies);
+        let http_client = HttpClient::new(self.network.clone(), Some(cookies.clone()))?;
+
+        self.download_manager.set_client(http_client.clone());
+        self.http_client = http_client;
+        self.cookies = Some(cookies);
+
+        Ok(self)
+    }
+
+    /// Sets the network configuration: the proxy, CA bundle, timeouts and user agent are given to 'yt-dlp',
+    /// and used by the downloads of the formats, thumbnails and subtitles, through a new shared HTTP client.
+    /// It replaces the configuration set before.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The network configuration.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the proxy URL is invalid, or if the CA bundle could not be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use std::time::Duration;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::network::NetworkConfig;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let mut fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let network = NetworkConfig::new()
+    ///     .with_proxy("socks5h://proxy.local:1080")
+    ///     .with_ca_bundle("corporate-ca.pem")
+    ///     .with_connect_timeout(Duration::from_secs(10));
+    /// fetcher.with_network(network)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_network(&mut self, network: NetworkConfig) -> Result<&mut Self> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Using {}", network);
+
+        let http_client = HttpClient::new(Arc::new(network), self.cookies.clone())?;
+        self.apply_client(http_client);
+
+        Ok(self)
+    }
+
+    /// Gives the network configuration of a client to 'yt-dlp', and shares the client with the downloads.
+    fn apply_client(&mut self, http_client: HttpClient) {
+        self.network = http_client.network().clone();
+        self.download_manager.set_client(http_client.clone());
+        self.http_client = http_client;
+    }
+
+    /// Returns the arguments to give to 'yt-dlp', e.g. when it downloads a format itself with an `Executor`:
+    /// the ones of the current network configuration, cookies and bandwidth limit, rebuilt on each call
+    /// so that replacing them does not leave the previous ones, then the configured arguments.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use std::time::Duration;
+    /// # use yt_dlp::executor::Executor;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::rate_limit::parse_rate;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    /// fetcher.set_rate_limit(Some(parse_rate("5M")?));
+    ///
+    /// // The download is limited with '--limit-rate 5242880'
+    /// let mut args = fetcher.yt_dlp_args();
+    /// args.extend(["-o", "video.mp4", "https://www.youtube.com/watch?v=dQw4w9WgXcQ"].map(String::from));
+    ///
+    /// let executor = Executor {
+    ///     executable_path: fetcher.libraries.youtube.clone(),
+    ///     timeout: Duration::from_secs(3600),
+    ///     args,
+    ///     env: fetcher.network.yt_dlp_env(),
+    /// };
+    /// executor.execute().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn yt_dlp_args(&self) -> Vec<String> {
+        let mut args = self.network.yt_dlp_args();
+        if let Some(cookies) = &self.cookies {
+            args.extend([
+                "--cookies".to_string(),
+                cookies.path().to_string_lossy().to_string(),
+            ]);
+        }
+        args.extend(self.download_manager.rate_limiter().yt_dlp_args());
+
+        args.extend(self.args.iter().cloned());
+        args
+    }
+
     /// Updates the yt-dlp executable.
     /// Be careful, this function may take a while to execute.
     ///
@@ -382,12 +656,14 @@ impl Youtube {
         #[cfg(feature = "tracing")]
         tracing::debug!("Updating the downloader");
 
-        let args = vec!["--update"];
+        let mut args = utils::to_owned(vec!["--update"]);
+        args.extend(self.network.yt_dlp_args());
 
         let executor = Executor {
             executable_path: self.libraries.youtube.clone(),
             timeout: self.timeout,
-            args: utils::to_owned(args),
+            args,
+            env: self.network.yt_dlp_env(),
         };
 
         executor.execute().await?;
@@ -395,6 +671,9 @@ impl Youtube {
     }
 
     /// Combines the audio and video files into a single file.
+    /// The container is found from the extension of the output file, and the streams it does not accept
+    /// are transcoded. The codecs of the streams are found from the cache, and without it the video is copied
+    /// while the audio is transcoded, except in MKV files.
     /// Be careful, this function may take a while to execute.
     ///
     /// # Arguments
@@ -440,13 +719,113 @@ impl Youtube {
         audio_file: impl AsRef<str> + std::fmt::Debug + Display,
         video_file: impl AsRef<str> + std::fmt::Debug + Display,
         output_file: impl AsRef<str> + std::fmt::Debug + Display,
+    ) -> Result<PathBuf> {
+        let container = MergeContainer::from_path(output_file.as_ref());
+
+        // The codecs are only known if the streams were downloaded through the cache
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "cache")] {
+                let video_path = self.output_dir.join(video_file.as_ref());
+                let audio_path = self.output_dir.join(audio_file.as_ref());
+                let video_format = self.find_cached_format(&video_path).await;
+                let audio_format = self.find_cached_format(&audio_path).await;
+            } else {
+                let video_format: Option<model::format::Format> = None;
+                let audio_format: Option<model::format::Format> = None;
+            }
+        }
+
+        let video_codec = video_format
+            .as_ref()
+            .and_then(|format| format.codec_info.video_codec.as_deref());
+        let audio_codec = audio_format
+            .as_ref()
+            .and_then(|format| format.codec_info.audio_codec.as_deref());
+        let plan = MergePlan::new(container, video_codec, &[audio_codec]);
+
+        self.combine_audio_and_video_with_plan(audio_file, video_file, output_file, &plan)
+            .await
+    }
+
+    /// Combines the audio and video files into a single file, following a merge plan.
+    /// The streams accepted by the container of the plan are copied, and the others are transcoded.
+    /// Be careful, this function may take a while to execute.
+    ///
+    /// # Arguments
+    ///
+    /// * `audio_file` - The name of the audio file to combine.
+    /// * `video_file` - The name of the video file to combine.
+    /// * `output_file` - The name of the output file, with the extension of the container.
+    /// * `plan` - How the streams are merged, e.g. from `MergePlan::for_formats`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the audio and video files could not be combined.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::postprocessor::merge::{MergeContainer, MergePlan};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url).await?;
+    ///
+    /// let audio_format = video.best_audio_format().unwrap();
+    /// fetcher.download_format(audio_format, "audio-stream.webm").await?;
+    ///
+    /// let video_format = video.best_video_format().unwrap();
+    /// fetcher.download_format(video_format, "video-stream.webm").await?;
+    ///
+    /// let plan = MergePlan::for_formats(MergeContainer::Mkv, video_format, &[audio_format]);
+    /// println!("{}", plan);
+    ///
+    /// let output_path = fetcher
+    ///     .combine_audio_and_video_with_plan("audio-stream.webm", "video-stream.webm", "my-output.mkv", &plan)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn combine_audio_and_video_with_plan(
+        &self,
+        audio_file: impl AsRef<str> + std::fmt::Debug + Display,
+        video_file: impl AsRef<str> + std::fmt::Debug + Display,
+        output_file: impl AsRef<str> + std::fmt::Debug + Display,
+        plan: &MergePlan,
+    ) -> Result<PathBuf> {
+        self.combine_audio_and_video_internal(audio_file, video_file, output_file, plan, None, None)
+            .await
+    }
+
+    /// Internal function that handles combining the audio and video files following a merge plan,
+    /// tagging the output with the given video, or the one found from the file names,
+    /// and publishing the merge and tagging stages with the optional event emitter.
+    async fn combine_audio_and_video_internal(
+        &self,
+        audio_file: impl AsRef<str> + std::fmt::Debug + Display,
+        video_file: impl AsRef<str> + std::fmt::Debug + Display,
+        output_file: impl AsRef<str> + std::fmt::Debug + Display,
+        plan: &MergePlan,
+        video: Option<&model::Video>,
+        events: Option<&EventEmitter>,
     ) -> Result<PathBuf> {
         #[cfg(feature = "tracing")]
         tracing::debug!(
-            "Combining audio and video files {} and {}, into {}",
+            "Combining audio and video files {} and {}, into {} with {}",
             audio_file,
             video_file,
-            output_file
+            output_file,
+            plan
         );
 
         let audio_path = self.output_dir.join(audio_file.as_ref());
@@ -454,89 +833,275 @@ impl Youtube {
         let output_path = self.output_dir.join(output_file.as_ref());
 
         // Perform the combination with FFmpeg
-        self.execute_ffmpeg_combine(&audio_path, &video_path, &output_path)
+        if let Some(events) = events {
+            events.emit(DownloadEvent::Merging {
+                output: output_path.clone(),
+                plan: plan.clone(),
+            });
+        }
+        self.execute_ffmpeg_combine(&audio_path, &video_path, &output_path, plan)
             .await?;
 
         // Add metadata to the combined file, propagating potential errors
-        self.add_metadata_to_combined_file(&audio_path, &video_path, &output_path)
+        self.add_metadata_to_combined_file(&audio_path, &video_path, &output_path, video, events)
             .await?;
 
         Ok(output_path)
     }
 
-    /// Executes the FFmpeg command to combine audio and video files
-    async fn execute_ffmpeg_combine(
+    /// Combines several audio tracks and a video file into a single file, e.g. the dubbed tracks of a video.
+    /// Each track is tagged with the language of its format, and the first one is the default track.
+    /// The container is found from the extension of the output file, and the streams it does not accept
+    /// are transcoded, as with `combine_audio_and_video_with_plan`.
+    /// Be careful, this function may take a while to execute.
+    ///
+    /// # Arguments
+    ///
+    /// * `audio_tracks` - The names of the audio files to combine, with the formats they were downloaded from.
+    /// * `video_track` - The name of the video file to combine, with the format it was downloaded from.
+    /// * `output_file` - The name of the output file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is no audio track, or if the files could not be combined.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::model::{AudioCodecPreference, AudioLanguage, AudioQuality};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url).await?;
+    ///
+    /// let languages = AudioLanguage::parse_list("ko,en");
+    /// let audio_formats = video.select_audio_formats_by_language(&languages, AudioQuality::Best, AudioCodecPreference::Any);
+    /// fetcher.download_format(audio_formats[0], "audio-ko.m4a").await?;
+    /// fetcher.download_format(audio_formats[1], "audio-en.m4a").await?;
+    ///
+    /// let video_format = video.best_video_format().unwrap();
+    /// fetcher.download_format(video_format, "video-stream.mp4").await?;
+    ///
+    /// let tracks = [("audio-ko.m4a", audio_formats[0]), ("audio-en.m4a", audio_formats[1])];
+    /// let output_path = fetcher
+    ///     .combine_audio_tracks_and_video(&tracks, ("video-stream.mp4", video_format), "my-output.mkv")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn combine_audio_tracks_and_video(
         &self,
-        audio_path: impl AsRef<Path>,
-        video_path: impl AsRef<Path>,
-        output_path: impl AsRef<Path>,
-    ) -> Result<()> {
-        let audio = audio_path
-            .as_ref()
-            .to_str()
-            .ok_or(Error::Path("Invalid audio path".to_string()))?;
-        let video = video_path
-            .as_ref()
-            .to_str()
-            .ok_or(Error::Path("Invalid video path".to_string()))?;
-        let output = output_path
-            .as_ref()
-            .to_str()
+        audio_tracks: &[(impl AsRef<str> + std::fmt::Debug, &model::format::Format)],
+        (video_file, video_format): (
+            impl AsRef<str> + std::fmt::Debug + Display,
+            &model::format::Format,
+        ),
+        output_file: impl AsRef<str> + std::fmt::Debug + Display,
+    ) -> Result<PathBuf> {
+        self.combine_audio_tracks_and_video_internal(
+            audio_tracks,
+            (video_file, video_format),
+            output_file,
+            None,
+            None,
+        )
+        .await
+    }
+
+    /// Internal function that handles combining several audio tracks and a video file,
+    /// tagging the output with the given video, or the one found from the file names,
+    /// and publishing the merge and tagging stages with the optional event emitter.
+    async fn combine_audio_tracks_and_video_internal(
+        &self,
+        audio_tracks: &[(impl AsRef<str> + std::fmt::Debug, &model::format::Format)],
+        (video_file, video_format): (
+            impl AsRef<str> + std::fmt::Debug + Display,
+            &model::format::Format,
+        ),
+        output_file: impl AsRef<str> + std::fmt::Debug + Display,
+        video: Option<&model::Video>,
+        events: Option<&EventEmitter>,
+    ) -> Result<PathBuf> {
+        let Some((first_audio_file, _)) = audio_tracks.first() else {
+            return Err(Error::MissingFormat("audio".to_string()));
+        };
+
+        let audio_formats: Vec<&model::format::Format> =
+            audio_tracks.iter().map(|(_, format)| *format).collect();
+        let plan = MergePlan::for_formats(
+            MergeContainer::from_path(output_file.as_ref()),
+            video_format,
+            &audio_formats,
+        );
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            "Combining {} audio tracks and video file {}, into {} with {}",
+            audio_tracks.len(),
+            video_file,
+            output_file,
+            plan
+        );
+
+        let video_path = self.output_dir.join(video_file.as_ref());
+        let output_path = self.output_dir.join(output_file.as_ref());
+        let output = output_path
+            .to_str()
+            .ok_or(Error::Path("Invalid output path".to_string()))?;
+        let video = video_path
+            .to_str()
+            .ok_or(Error::Path("Invalid video path".to_string()))?;
+
+        let mut args = utils::to_owned(vec!["-i", video]);
+        for (audio_file, _) in audio_tracks {
+            let audio_path = self.output_dir.join(audio_file.as_ref());
+            let audio = audio_path
+                .to_str()
+                .ok_or(Error::Path("Invalid audio path".to_string()))?;
+            args.extend(utils::to_owned(vec!["-i", audio]));
+        }
+
+        args.extend(utils::to_owned(vec!["-map", "0:v:0"]));
+        for index in 1..=audio_tracks.len() {
+            args.push("-map".to_string());
+            args.push(format!("{}:a:0", index));
+        }
+
+        args.extend(plan.codec_args());
+        args.extend(crate::metadata::MetadataManager::audio_track_args(
+            &audio_formats,
+        ));
+        args.push(output.to_string());
+
+        if let Some(events) = events {
+            events.emit(DownloadEvent::Merging {
+                output: output_path.clone(),
+                plan: plan.clone(),
+            });
+        }
+        let executor = Executor {
+            executable_path: self.libraries.ffmpeg.clone(),
+            timeout: self.timeout,
+            args,
+            env: Vec::new(),
+        };
+        executor.execute().await?;
+
+        // Add metadata to the combined file, propagating potential errors
+        let first_audio_path = self.output_dir.join(first_audio_file.as_ref());
+        self.add_metadata_to_combined_file(
+            &first_audio_path,
+            &video_path,
+            &output_path,
+            video,
+            events,
+        )
+        .await?;
+
+        Ok(output_path)
+    }
+
+    /// Executes the FFmpeg command to combine audio and video files
+    async fn execute_ffmpeg_combine(
+        &self,
+        audio_path: impl AsRef<Path>,
+        video_path: impl AsRef<Path>,
+        output_path: impl AsRef<Path>,
+        plan: &MergePlan,
+    ) -> Result<()> {
+        let audio = audio_path
+            .as_ref()
+            .to_str()
+            .ok_or(Error::Path("Invalid audio path".to_string()))?;
+        let video = video_path
+            .as_ref()
+            .to_str()
+            .ok_or(Error::Path("Invalid video path".to_string()))?;
+        let output = output_path
+            .as_ref()
+            .to_str()
             .ok_or(Error::Path("Invalid output path".to_string()))?;
 
-        let args = vec![
-            "-i", audio, "-i", video, "-c:v", "copy", "-c:a", "aac", output,
-        ];
+        // Only the first stream of each input, as the video file may also contain an audio stream
+        let mut args = utils::to_owned(vec![
+            "-i", audio, "-i", video, "-map", "1:v:0", "-map", "0:a:0",
+        ]);
+        args.extend(plan.codec_args());
+        args.push(output.to_string());
 
         let executor = Executor {
             executable_path: self.libraries.ffmpeg.clone(),
             timeout: self.timeout,
-            args: utils::to_owned(args),
+            args,
+            env: Vec::new(),
         };
 
         executor.execute().await?;
         Ok(())
     }
 
-    /// Adds metadata to the combined file by extracting the video ID and
-    /// retrieving information from the original audio and video formats
+    /// Adds metadata to the combined file from the given video, or the one whose ID is extracted
+    /// from the file names, and from the original audio and video formats
     async fn add_metadata_to_combined_file(
         &self,
         audio_path: impl AsRef<Path>,
         video_path: impl AsRef<Path>,
         output_path: impl AsRef<Path>,
+        video: Option<&model::Video>,
+        events: Option<&EventEmitter>,
     ) -> Result<()> {
-        let video_id =
-            self.extract_video_id_from_file_paths(video_path.as_ref(), audio_path.as_ref());
+        // The file names are only parsed when the caller does not hold the video
+        let video = match video {
+            Some(video) => Some(Cow::Borrowed(video)),
+            None => match self
+                .extract_video_id_from_file_paths(video_path.as_ref(), audio_path.as_ref())
+            {
+                Some(video_id) => self.get_video_by_id(&video_id).await.map(Cow::Owned),
+                None => None,
+            },
+        };
 
-        if let Some(video_id) = video_id {
-            if let Some(video) = self.get_video_by_id(&video_id).await {
-                #[cfg(feature = "tracing")]
-                tracing::debug!("Adding metadata to combined file");
-
-                cfg_if::cfg_if! {
-                    if #[cfg(feature = "cache")] {
-                        let video_format = self.find_cached_format(video_path.as_ref()).await;
-                        let audio_format = self.find_cached_format(audio_path.as_ref()).await;
-                    } else {
-                        let video_format: Option<model::format::Format> = None;
-                        let audio_format: Option<model::format::Format> = None;
-                    }
-                }
+        if let Some(video) = video {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("Adding metadata to combined file");
+            if let Some(events) = events {
+                events.emit(DownloadEvent::Tagging {
+                    path: output_path.as_ref().to_path_buf(),
+                });
+            }
 
-                // Add metadata, log error on failure, then propagate
-                crate::metadata::MetadataManager::add_metadata_with_format(
-                    output_path.as_ref(),
-                    &video,
-                    video_format.as_ref(),
-                    audio_format.as_ref(),
-                )
-                .await
-                .inspect_err(|_e| {
-                    #[cfg(feature = "tracing")]
-                    tracing::warn!("Failed to add metadata to combined file: {}", _e);
-                })?;
+            cfg_if::cfg_if! {
+                if #[cfg(feature = "cache")] {
+                    let video_format = self.find_cached_format(video_path.as_ref()).await;
+                    let audio_format = self.find_cached_format(audio_path.as_ref()).await;
+                } else {
+                    let video_format: Option<model::format::Format> = None;
+                    let audio_format: Option<model::format::Format> = None;
+                }
             }
+
+            // Add metadata, log error on failure, then propagate
+            crate::metadata::MetadataManager::add_metadata_with_format(
+                output_path.as_ref(),
+                &video,
+                video_format.as_ref(),
+                audio_format.as_ref(),
+            )
+            .await
+            .inspect_err(|_e| {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("Failed to add metadata to combined file: {}", _e);
+            })?;
         }
 
         Ok(())
@@ -672,6 +1237,56 @@ impl Youtube {
         Ok(self)
     }
 
+    /// Records the queue of the download manager in a persistent store, so that it survives a crash or a reboot.
+    /// The queue recorded by a previous run is restored, and its unfinished downloads are resumed.
+    /// The download manager is replaced, so the events must be subscribed to afterwards.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file where the queue is recorded, a SQLite database with the `cache` feature.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the queue could not be opened or read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let mut fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// // Resume the downloads of the previous run, and record the new ones
+    /// fetcher.with_persistent_queue(PathBuf::from("output/queue.db")).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn with_persistent_queue(
+        &mut self,
+        path: impl AsRef<Path> + std::fmt::Debug,
+    ) -> Result<&mut Self> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Enabling persistent download queue");
+
+        let config = self.download_manager.config().clone();
+        let mut download_manager = DownloadManager::restore(config, path)?;
+        download_manager.set_client(self.http_client.clone());
+        download_manager.set_rate_limiter(self.download_manager.rate_limiter().clone());
+
+        // The restored downloads are resumed once they have the shared client
+        download_manager.process_queue();
+        self.download_manager = Arc::new(download_manager);
+        Ok(self)
+    }
+
     /// Download a video using the download manager with priority.
     ///
     /// This method adds the video download to the download queue with the specified priority.
@@ -814,6 +1429,84 @@ impl Youtube {
         self.download_manager.cancel(download_id).await
     }
 
+    /// Pause a download, keeping its partial file to resume it later.
+    ///
+    /// # Arguments
+    ///
+    /// * `download_id` - The ID of the download to pause.
+    ///
+    /// # Returns
+    ///
+    /// true if the download was paused, false if it was not found, already finished or already paused.
+    pub async fn pause_download(&self, download_id: u64) -> bool {
+        self.download_manager.pause(download_id).await
+    }
+
+    /// Resume a paused download.
+    ///
+    /// # Arguments
+    ///
+    /// * `download_id` - The ID of the download to resume.
+    ///
+    /// # Returns
+    ///
+    /// true if the download was resumed, false if it was not found or not paused.
+    pub async fn resume_download(&self, download_id: u64) -> bool {
+        self.download_manager.resume(download_id).await
+    }
+
+    /// Limits the bandwidth shared by all the downloads, applied to the downloads in progress too.
+    /// The limit is given to 'yt-dlp' by `yt_dlp_args`, when it downloads a format itself.
+    ///
+    /// # Arguments
+    ///
+    /// * `rate` - The limit in bytes per second, or None for unlimited.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::rate_limit::parse_rate;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// // Keep some bandwidth for the rest of the network
+    /// fetcher.set_rate_limit(Some(parse_rate("5M")?));
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url).await?;
+    /// fetcher.download_video(&video, "video.mp4").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_rate_limit(&self, rate: Option<u64>) {
+        self.download_manager.set_rate_limit(rate);
+    }
+
+    /// Limits the bandwidth of a download of the download manager, applied while it is in progress too.
+    ///
+    /// # Arguments
+    ///
+    /// * `download_id` - The ID of the download.
+    /// * `rate` - The limit in bytes per second, or None for unlimited.
+    ///
+    /// # Returns
+    ///
+    /// true if the limit was changed, false if the download was not found or already finished.
+    pub async fn set_download_rate_limit(&self, download_id: u64, rate: Option<u64>) -> bool {
+        self.download_manager
+            .set_download_rate_limit(download_id, rate)
+            .await
+    }
+
     /// Wait for a download to complete.
     ///
     /// # Arguments
@@ -830,6 +1523,86 @@ impl Youtube {
         self.download_manager.wait_for_completion(download_id).await
     }
 
+    /// Subscribes to the lifecycle events of the downloads, published from now on.
+    /// The events of the downloads queued in the download manager, and of the downloads run by
+    /// the quality, audio language and format selector methods, are keyed by download ID.
+    ///
+    /// # Returns
+    ///
+    /// A receiver of the events. A receiver which does not keep up lags behind, and then receives the most recent events.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::events::DownloadEvent;
+    /// # use yt_dlp::model::{VideoQuality, VideoCodecPreference, AudioQuality, AudioCodecPreference};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let mut events = fetcher.subscribe_events();
+    /// tokio::spawn(async move {
+    ///     while let Ok(update) = events.recv().await {
+    ///         if let DownloadEvent::StreamProgress { downloaded_bytes, total_bytes, .. } = update.event {
+    ///             println!("Download {}: {}/{} bytes", update.download_id, downloaded_bytes, total_bytes);
+    ///         }
+    ///     }
+    /// });
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// fetcher.download_video_with_quality(
+    ///     url,
+    ///     "my-video.mp4",
+    ///     VideoQuality::High,
+    ///     VideoCodecPreference::Any,
+    ///     AudioQuality::High,
+    ///     AudioCodecPreference::Any,
+    /// ).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn subscribe_events(&self) -> broadcast::Receiver<DownloadUpdate> {
+        self.download_manager.subscribe()
+    }
+
+    /// Starts publishing the events of a new download, with an ID shared with the download manager.
+    async fn start_download_events(&self, url: &str, output: &str) -> EventEmitter {
+        let download_id = self.download_manager.next_id().await;
+        let events = self.download_manager.events().emitter(download_id);
+        events.emit(DownloadEvent::Started {
+            url: url.to_string(),
+            output: output.to_string(),
+        });
+
+        events
+    }
+
+    /// Fetches the video information of a download, publishing the fetching stages.
+    async fn fetch_video_infos_with_events(
+        &self,
+        url: &str,
+        events: &EventEmitter,
+    ) -> Result<model::Video> {
+        events.emit(DownloadEvent::FetchingInfo {
+            url: url.to_string(),
+        });
+        let video = self.fetch_video_infos(url.to_string()).await?;
+        events.emit(DownloadEvent::InfoFetched {
+            video_id: video.id.clone(),
+            title: video.title.clone(),
+        });
+
+        Ok(video)
+    }
+
     /// Downloads a video with the specified video and audio quality preferences.
     ///
     /// # Arguments
@@ -883,17 +1656,301 @@ impl Youtube {
         audio_quality: model::format_selector::AudioQuality,
         audio_codec: model::format_selector::AudioCodecPreference,
     ) -> Result<PathBuf> {
-        let video = self.fetch_video_infos(url.to_string()).await?;
+        self.download_video_with_quality_internal(
+            url,
+            output,
+            (video_quality, video_codec),
+            (audio_quality, audio_codec),
+            &[],
+            None,
+            None,
+        )
+        .await
+    }
 
-        // Select video format based on quality and codec preferences
-        let video_format = video
-            .select_video_format(video_quality, video_codec.clone())
-            .ok_or_else(|| Error::MissingFormat("video".to_string()))?;
+    /// Downloads a video with the specified video and audio quality preferences,
+    /// reporting the progress of the video and audio streams separately.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the video to download
+    /// * `output` - The name of the output file
+    /// * `video_quality` - The desired video quality
+    /// * `video_codec` - The preferred video codec
+    /// * `audio_quality` - The desired audio quality
+    /// * `audio_codec` - The preferred audio codec
+    /// * `video_progress` - A function called with the downloaded size and total size of the video stream
+    /// * `audio_progress` - A function called with the downloaded size and total size of the audio stream
+    ///
+    /// # Returns
+    ///
+    /// The path to the downloaded video file
+    ///
+    /// # Example
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::model::{VideoQuality, VideoCodecPreference, AudioQuality, AudioCodecPreference};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// # let fetcher = Youtube::new(libraries, output_dir)?;
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    ///
+    /// let video_path = fetcher.download_video_with_quality_and_progress(
+    ///     url,
+    ///     "my-video.mp4",
+    ///     VideoQuality::High,
+    ///     VideoCodecPreference::VP9,
+    ///     AudioQuality::High,
+    ///     AudioCodecPreference::Opus,
+    ///     |downloaded, total| println!("Video: {}/{} bytes", downloaded, total),
+    ///     |downloaded, total| println!("Audio: {}/{} bytes", downloaded, total),
+    /// ).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub async fn download_video_with_quality_and_progress<V, A>(
+        &self,
+        url: impl AsRef<str> + std::fmt::Debug + Display,
+        output: impl AsRef<str> + std::fmt::Debug + Display,
+        video_quality: model::format_selector::VideoQuality,
+        video_codec: model::format_selector::VideoCodecPreference,
+        audio_quality: model::format_selector::AudioQuality,
+        audio_codec: model::format_selector::AudioCodecPreference,
+        video_progress: V,
+        audio_progress: A,
+    ) -> Result<PathBuf>
+    where
+        V: Fn(u64, u64) + Send + Sync + 'static,
+        A: Fn(u64, u64) + Send + Sync + 'static,
+    {
+        self.download_video_with_quality_internal(
+            url,
+            output,
+            (video_quality, video_codec),
+            (audio_quality, audio_codec),
+            &[],
+            Some(Arc::new(video_progress)),
+            Some(Arc::new(audio_progress)),
+        )
+        .await
+    }
 
-        // Select audio format based on quality and codec preferences
-        let audio_format = video
-            .select_audio_format(audio_quality, audio_codec.clone())
-            .ok_or_else(|| Error::MissingFormat("audio".to_string()))?;
+    /// Downloads a video with one audio track per preferred language, e.g. the original and dubbed tracks.
+    /// The tracks are merged in the order of the languages, tagged with their language,
+    /// and the first one is the default track. Languages without an audio track are skipped,
+    /// and the best audio format is used when none of them is available.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the video to download
+    /// * `output` - The name of the output file, e.g. 'my-video.mkv' to keep the audio codecs
+    /// * `video_quality` - The desired video quality
+    /// * `video_codec` - The preferred video codec
+    /// * `audio_quality` - The desired audio quality of each track
+    /// * `audio_codec` - The preferred audio codec of each track
+    /// * `audio_languages` - The preferred languages, from the most to the least preferred
+    ///
+    /// # Returns
+    ///
+    /// The path to the downloaded video file
+    ///
+    /// # Example
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::model::{VideoQuality, VideoCodecPreference, AudioQuality, AudioCodecPreference, AudioLanguage};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// # let fetcher = Youtube::new(libraries, output_dir)?;
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    ///
+    /// // Korean and English tracks, then the original one if it is in another language
+    /// let languages = AudioLanguage::parse_list("ko,en,original");
+    /// let video_path = fetcher.download_video_with_audio_languages(
+    ///     url,
+    ///     "my-video.mkv",
+    ///     VideoQuality::High,
+    ///     VideoCodecPreference::Any,
+    ///     AudioQuality::High,
+    ///     AudioCodecPreference::Any,
+    ///     &languages,
+    /// ).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub async fn download_video_with_audio_languages(
+        &self,
+        url: impl AsRef<str> + std::fmt::Debug + Display,
+        output: impl AsRef<str> + std::fmt::Debug + Display,
+        video_quality: model::format_selector::VideoQuality,
+        video_codec: model::format_selector::VideoCodecPreference,
+        audio_quality: model::format_selector::AudioQuality,
+        audio_codec: model::format_selector::AudioCodecPreference,
+        audio_languages: &[model::format_selector::AudioLanguage],
+    ) -> Result<PathBuf> {
+        self.download_video_with_quality_internal(
+            url,
+            output,
+            (video_quality, video_codec),
+            (audio_quality, audio_codec),
+            audio_languages,
+            None,
+            None,
+        )
+        .await
+    }
+
+    /// Downloads a video with one audio track per preferred language,
+    /// reporting the progress of the video and audio streams separately.
+    /// The audio progress is reported for each track in turn.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the video to download
+    /// * `output` - The name of the output file
+    /// * `video_quality` - The desired video quality
+    /// * `video_codec` - The preferred video codec
+    /// * `audio_quality` - The desired audio quality of each track
+    /// * `audio_codec` - The preferred audio codec of each track
+    /// * `audio_languages` - The preferred languages, from the most to the least preferred
+    /// * `video_progress` - A function called with the downloaded size and total size of the video stream
+    /// * `audio_progress` - A function called with the downloaded size and total size of the current audio track
+    ///
+    /// # Returns
+    ///
+    /// The path to the downloaded video file
+    #[allow(clippy::too_many_arguments)]
+    pub async fn download_video_with_audio_languages_and_progress<V, A>(
+        &self,
+        url: impl AsRef<str> + std::fmt::Debug + Display,
+        output: impl AsRef<str> + std::fmt::Debug + Display,
+        video_quality: model::format_selector::VideoQuality,
+        video_codec: model::format_selector::VideoCodecPreference,
+        audio_quality: model::format_selector::AudioQuality,
+        audio_codec: model::format_selector::AudioCodecPreference,
+        audio_languages: &[model::format_selector::AudioLanguage],
+        video_progress: V,
+        audio_progress: A,
+    ) -> Result<PathBuf>
+    where
+        V: Fn(u64, u64) + Send + Sync + 'static,
+        A: Fn(u64, u64) + Send + Sync + 'static,
+    {
+        self.download_video_with_quality_internal(
+            url,
+            output,
+            (video_quality, video_codec),
+            (audio_quality, audio_codec),
+            audio_languages,
+            Some(Arc::new(video_progress)),
+            Some(Arc::new(audio_progress)),
+        )
+        .await
+    }
+
+    /// Internal function that handles downloading a video with quality preferences,
+    /// with or without progress callbacks, and publishes its events.
+    /// With audio languages, one audio track per available language is merged with the video.
+    #[allow(clippy::too_many_arguments)]
+    async fn download_video_with_quality_internal(
+        &self,
+        url: impl AsRef<str> + std::fmt::Debug + Display,
+        output: impl AsRef<str> + std::fmt::Debug + Display,
+        video_preferences: (
+            model::format_selector::VideoQuality,
+            model::format_selector::VideoCodecPreference,
+        ),
+        audio_preferences: (
+            model::format_selector::AudioQuality,
+            model::format_selector::AudioCodecPreference,
+        ),
+        audio_languages: &[model::format_selector::AudioLanguage],
+        video_progress: Option<ProgressCallback>,
+        audio_progress: Option<ProgressCallback>,
+    ) -> Result<PathBuf> {
+        let events = self
+            .start_download_events(url.as_ref(), output.as_ref())
+            .await;
+        let result = self
+            .download_video_with_quality_steps(
+                url,
+                output,
+                video_preferences,
+                audio_preferences,
+                audio_languages,
+                (video_progress, audio_progress),
+                &events,
+            )
+            .await;
+        events.finish(result.as_deref());
+
+        result
+    }
+
+    /// Runs the stages of a download with quality preferences, publishing them with the event emitter.
+    #[allow(clippy::too_many_arguments)]
+    async fn download_video_with_quality_steps(
+        &self,
+        url: impl AsRef<str> + std::fmt::Debug + Display,
+        output: impl AsRef<str> + std::fmt::Debug + Display,
+        (video_quality, video_codec): (
+            model::format_selector::VideoQuality,
+            model::format_selector::VideoCodecPreference,
+        ),
+        (audio_quality, audio_codec): (
+            model::format_selector::AudioQuality,
+            model::format_selector::AudioCodecPreference,
+        ),
+        audio_languages: &[model::format_selector::AudioLanguage],
+        (video_progress, audio_progress): (Option<ProgressCallback>, Option<ProgressCallback>),
+        events: &EventEmitter,
+    ) -> Result<PathBuf> {
+        let video = self
+            .fetch_video_infos_with_events(url.as_ref(), events)
+            .await?;
+
+        // Select video format based on quality and codec preferences
+        let video_format = video
+            .select_video_format(video_quality, video_codec.clone())
+            .ok_or_else(|| Error::MissingFormat("video".to_string()))?;
+
+        // Select audio formats based on language, quality and codec preferences
+        let mut audio_formats = video.select_audio_formats_by_language(
+            audio_languages,
+            audio_quality,
+            audio_codec.clone(),
+        );
+        if audio_formats.is_empty() {
+            #[cfg(feature = "tracing")]
+            if !audio_languages.is_empty() {
+                tracing::warn!(
+                    "No audio track in languages {:?}, using the best audio format",
+                    audio_languages
+                );
+            }
+
+            let audio_format = video
+                .select_audio_format(audio_quality, audio_codec.clone())
+                .ok_or_else(|| Error::MissingFormat("audio".to_string()))?;
+            audio_formats.push(audio_format);
+        }
 
         // Download video format with preferences
         let video_ext = format!("{:?}", video_format.download_info.ext);
@@ -903,65 +1960,115 @@ impl Youtube {
             video_ext
         );
 
+        let video_path = self.output_dir.join(&video_filename);
         cfg_if::cfg_if! {
             if #[cfg(feature = "cache")] {
                 let video_path = self
-                    .download_format_with_preferences(
+                    .download_format_internal(
                         video_format,
-                        &video_filename,
+                        &video_path,
                         Some(video_quality),
                         None,
                         Some(video_codec),
                         None,
+                        Some(events),
+                        video_progress,
                     )
                     .await?;
             } else {
                 let video_path = self
-                    .download_format(video_format, &video_filename)
+                    .download_format_internal(
+                        video_format,
+                        &video_path,
+                        Some(events),
+                        video_progress,
+                    )
                     .await?;
             }
         }
 
-        // Download audio format with preferences
-        let audio_ext = format!("{:?}", audio_format.download_info.ext);
-        let audio_filename = format!(
-            "temp_audio_{}.{}",
-            utils::file_system::random_filename(8),
-            audio_ext
-        );
-        cfg_if::cfg_if! {
-            if #[cfg(feature = "cache")] {
-                let audio_path = self
-                    .download_format_with_preferences(
-                        audio_format,
-                        &audio_filename,
-                        None,
-                        Some(audio_quality),
-                        None,
-                        Some(audio_codec),
-                    )
-                    .await?;
-            } else {
-                let audio_path = self
-                    .download_format(audio_format, &audio_filename)
-                    .await?;
+        // Download audio formats with preferences
+        let mut audio_files = Vec::with_capacity(audio_formats.len());
+        for audio_format in &audio_formats {
+            let audio_ext = format!("{:?}", audio_format.download_info.ext);
+            let audio_filename = format!(
+                "temp_audio_{}.{}",
+                utils::file_system::random_filename(8),
+                audio_ext
+            );
+            let audio_path = self.output_dir.join(&audio_filename);
+            cfg_if::cfg_if! {
+                if #[cfg(feature = "cache")] {
+                    let audio_path = self
+                        .download_format_internal(
+                            audio_format,
+                            &audio_path,
+                            None,
+                            Some(audio_quality),
+                            None,
+                            Some(audio_codec.clone()),
+                            Some(events),
+                            audio_progress.clone(),
+                        )
+                        .await?;
+                } else {
+                    let audio_path = self
+                        .download_format_internal(
+                            audio_format,
+                            &audio_path,
+                            Some(events),
+                            audio_progress.clone(),
+                        )
+                        .await?;
+                }
             }
+            audio_files.push((audio_filename, audio_path));
         }
 
-        // Combine audio and video
-        let output_path = self
-            .combine_audio_and_video(&audio_filename, &video_filename, output)
-            .await?;
+        // Combine audio and video, with a language-tagged track per audio language
+        let output_path = if audio_languages.is_empty() {
+            let plan = MergePlan::for_formats(
+                MergeContainer::from_path(output.as_ref()),
+                video_format,
+                &audio_formats[..1],
+            );
+            self.combine_audio_and_video_internal(
+                &audio_files[0].0,
+                &video_filename,
+                output,
+                &plan,
+                Some(&video),
+                Some(events),
+            )
+            .await?
+        } else {
+            let audio_tracks: Vec<(&str, &model::format::Format)> = audio_files
+                .iter()
+                .zip(&audio_formats)
+                .map(|((audio_filename, _), audio_format)| (audio_filename.as_str(), *audio_format))
+                .collect();
+            self.combine_audio_tracks_and_video_internal(
+                &audio_tracks,
+                (video_filename.as_str(), video_format),
+                output,
+                Some(&video),
+                Some(events),
+            )
+            .await?
+        };
 
         // Clean up temporary files
+        events.emit(DownloadEvent::CleaningUp);
         if let Err(_e) = tokio::fs::remove_file(&video_path).await {
             #[cfg(feature = "tracing")]
             tracing::warn!("Failed to remove temporary video file: {}", _e);
         }
 
-        if let Err(_e) = tokio::fs::remove_file(&audio_path).await {
-            #[cfg(feature = "tracing")]
-            tracing::warn!("Failed to remove temporary audio file: {}", _e);
+        for (_, audio_path) in &audio_files {
+            if let Err(_e) = tokio::fs::remove_file(audio_path).await {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("Failed to remove temporary audio file: {}", _e);
+            }
         }
 
         Ok(output_path)
@@ -1087,7 +2194,108 @@ impl Youtube {
         quality: model::format_selector::AudioQuality,
         codec: model::format_selector::AudioCodecPreference,
     ) -> Result<PathBuf> {
-        let video = self.fetch_video_infos(url.to_string()).await?;
+        self.download_audio_stream_with_quality_internal(url, output, quality, codec, None)
+            .await
+    }
+
+    /// Downloads an audio stream with the specified quality preferences, reporting its progress.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the video to download
+    /// * `output` - The name of the output file
+    /// * `quality` - The desired audio quality
+    /// * `codec` - The preferred audio codec
+    /// * `progress` - A function called with the downloaded size and total size
+    ///
+    /// # Returns
+    ///
+    /// The path to the downloaded audio file
+    ///
+    /// # Example
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::model::{AudioQuality, AudioCodecPreference};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// # let fetcher = Youtube::new(libraries, output_dir)?;
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    ///
+    /// let audio_path = fetcher.download_audio_stream_with_quality_and_progress(
+    ///     url,
+    ///     "audio-only.mp3",
+    ///     AudioQuality::High,
+    ///     AudioCodecPreference::Opus,
+    ///     |downloaded, total| println!("{}/{} bytes", downloaded, total),
+    /// ).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_audio_stream_with_quality_and_progress<F>(
+        &self,
+        url: impl AsRef<str> + std::fmt::Debug + Display,
+        output: impl AsRef<str> + std::fmt::Debug + Display,
+        quality: model::format_selector::AudioQuality,
+        codec: model::format_selector::AudioCodecPreference,
+        progress: F,
+    ) -> Result<PathBuf>
+    where
+        F: Fn(u64, u64) + Send + Sync + 'static,
+    {
+        self.download_audio_stream_with_quality_internal(
+            url,
+            output,
+            quality,
+            codec,
+            Some(Arc::new(progress)),
+        )
+        .await
+    }
+
+    /// Internal function that handles downloading an audio stream with quality preferences,
+    /// with or without a progress callback, and publishes its events.
+    async fn download_audio_stream_with_quality_internal(
+        &self,
+        url: impl AsRef<str> + std::fmt::Debug + Display,
+        output: impl AsRef<str> + std::fmt::Debug + Display,
+        quality: model::format_selector::AudioQuality,
+        codec: model::format_selector::AudioCodecPreference,
+        progress: Option<ProgressCallback>,
+    ) -> Result<PathBuf> {
+        let events = self
+            .start_download_events(url.as_ref(), output.as_ref())
+            .await;
+        let result = self
+            .download_audio_stream_with_quality_steps(
+                url, output, quality, codec, progress, &events,
+            )
+            .await;
+        events.finish(result.as_deref());
+
+        result
+    }
+
+    /// Runs the stages of an audio stream download, publishing them with the event emitter.
+    async fn download_audio_stream_with_quality_steps(
+        &self,
+        url: impl AsRef<str> + std::fmt::Debug + Display,
+        output: impl AsRef<str> + std::fmt::Debug + Display,
+        quality: model::format_selector::AudioQuality,
+        codec: model::format_selector::AudioCodecPreference,
+        progress: Option<ProgressCallback>,
+        events: &EventEmitter,
+    ) -> Result<PathBuf> {
+        let video = self
+            .fetch_video_infos_with_events(url.as_ref(), events)
+            .await?;
 
         // Select audio format based on quality and codec preferences
         let audio_format = video
@@ -1095,19 +2303,295 @@ impl Youtube {
             .ok_or_else(|| Error::MissingFormat("audio".to_string()))?;
 
         // Download audio format with preferences
+        let output_path = self.output_dir.join(output.as_ref());
         cfg_if::cfg_if! {
             if #[cfg(feature = "cache")] {
-                self.download_format_with_preferences(
+                self.download_format_internal(
                     audio_format,
-                    output,
+                    &output_path,
                     None,
                     Some(quality),
                     None,
                     Some(codec),
+                    Some(events),
+                    progress,
                 )
                 .await
             } else {
-                self.download_format(audio_format, output)
+                self.download_format_internal(audio_format, &output_path, Some(events), progress)
+                    .await
+            }
+        }
+    }
+
+    /// Downloads the formats picked by a 'yt-dlp' format selector, e.g. `bv*[height<=1080]+ba/b`.
+    /// A single format is downloaded as is, while a video format merged with an audio format
+    /// is downloaded in two streams, which are then combined.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the video to download
+    /// * `output` - The name of the output file
+    /// * `selector` - The parsed format selector
+    ///
+    /// # Returns
+    ///
+    /// The path to the downloaded file
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if no format matches the selector,
+    /// or if the selected formats cannot be combined.
+    ///
+    /// # Example
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::model::selector::FormatSelector;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// # let fetcher = Youtube::new(libraries, output_dir)?;
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    ///
+    /// // Download an H.264 video up to 1080p with Korean audio, or the best MP4 file
+    /// let selector = FormatSelector::new("bv*[height<=1080][vcodec^=avc1]+ba[language=ko]/b[ext=mp4]")?;
+    /// let video_path = fetcher.download_with_selector(url, "my-video.mp4", &selector).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_with_selector(
+        &self,
+        url: impl AsRef<str> + std::fmt::Debug + Display,
+        output: impl AsRef<str> + std::fmt::Debug + Display,
+        selector: &model::selector::FormatSelector,
+    ) -> Result<PathBuf> {
+        self.download_with_selector_internal(url, output, selector, None, None)
+            .await
+    }
+
+    /// Downloads the formats picked by a 'yt-dlp' format selector, reporting the progress
+    /// of the video and audio streams.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the video to download
+    /// * `output` - The name of the output file
+    /// * `selector` - The parsed format selector
+    /// * `video_progress` - A function called with the downloaded size and total size of the video stream,
+    ///   or of the single selected format if it has video
+    /// * `audio_progress` - A function called with the downloaded size and total size of the audio stream,
+    ///   or of the single selected format if it is audio-only
+    ///
+    /// # Returns
+    ///
+    /// The path to the downloaded file
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if no format matches the selector,
+    /// or if the selected formats cannot be combined.
+    ///
+    /// # Example
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # use yt_dlp::model::selector::FormatSelector;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// # let fetcher = Youtube::new(libraries, output_dir)?;
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    ///
+    /// let selector = FormatSelector::new("bv*[height<=?720]+ba/b")?;
+    /// let video_path = fetcher.download_with_selector_and_progress(
+    ///     url,
+    ///     "my-video.mp4",
+    ///     &selector,
+    ///     |downloaded, total| println!("Video: {}/{} bytes", downloaded, total),
+    ///     |downloaded, total| println!("Audio: {}/{} bytes", downloaded, total),
+    /// ).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_with_selector_and_progress<V, A>(
+        &self,
+        url: impl AsRef<str> + std::fmt::Debug + Display,
+        output: impl AsRef<str> + std::fmt::Debug + Display,
+        selector: &model::selector::FormatSelector,
+        video_progress: V,
+        audio_progress: A,
+    ) -> Result<PathBuf>
+    where
+        V: Fn(u64, u64) + Send + Sync + 'static,
+        A: Fn(u64, u64) + Send + Sync + 'static,
+    {
+        self.download_with_selector_internal(
+            url,
+            output,
+            selector,
+            Some(Arc::new(video_progress)),
+            Some(Arc::new(audio_progress)),
+        )
+        .await
+    }
+
+    /// Internal function that handles downloading the formats picked by a format selector,
+    /// with or without progress callbacks, and publishes its events.
+    async fn download_with_selector_internal(
+        &self,
+        url: impl AsRef<str> + std::fmt::Debug + Display,
+        output: impl AsRef<str> + std::fmt::Debug + Display,
+        selector: &model::selector::FormatSelector,
+        video_progress: Option<ProgressCallback>,
+        audio_progress: Option<ProgressCallback>,
+    ) -> Result<PathBuf> {
+        let events = self
+            .start_download_events(url.as_ref(), output.as_ref())
+            .await;
+        let result = self
+            .download_with_selector_steps(
+                url,
+                output,
+                selector,
+                (video_progress, audio_progress),
+                &events,
+            )
+            .await;
+        events.finish(result.as_deref());
+
+        result
+    }
+
+    /// Runs the stages of a download with a format selector, publishing them with the event emitter.
+    async fn download_with_selector_steps(
+        &self,
+        url: impl AsRef<str> + std::fmt::Debug + Display,
+        output: impl AsRef<str> + std::fmt::Debug + Display,
+        selector: &model::selector::FormatSelector,
+        (video_progress, audio_progress): (Option<ProgressCallback>, Option<ProgressCallback>),
+        events: &EventEmitter,
+    ) -> Result<PathBuf> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            "Downloading {} into {} with selector {}",
+            url,
+            output,
+            selector.selector()
+        );
+
+        let video = self
+            .fetch_video_infos_with_events(url.as_ref(), events)
+            .await?;
+        let formats = video.select_formats(selector).ok_or_else(|| {
+            Error::FormatNotFound(format!("format matching '{}'", selector.selector()))
+        })?;
+
+        let (video_format, audio_format) = match formats.as_slice() {
+            [format] => {
+                let output_path = self.output_dir.join(output.as_ref());
+                let progress = if format.is_audio() {
+                    audio_progress
+                } else {
+                    video_progress
+                };
+                return self
+                    .download_selected_format(format, &output_path, events, progress)
+                    .await;
+            }
+            [first, second] if first.codec_info.video_codec.is_some() && !second.is_video() => {
+                (*first, *second)
+            }
+            [first, second] if second.codec_info.video_codec.is_some() && !first.is_video() => {
+                (*second, *first)
+            }
+            _ => {
+                return Err(Error::IncompatibleFormat(
+                    selector.selector().to_string(),
+                    "only a single format, or a video format merged with an audio format, can be downloaded"
+                        .to_string(),
+                ));
+            }
+        };
+
+        let video_ext = format!("{:?}", video_format.download_info.ext);
+        let video_filename = format!(
+            "temp_video_{}.{}",
+            utils::file_system::random_filename(8),
+            video_ext
+        );
+        let video_path = self.output_dir.join(&video_filename);
+        self.download_selected_format(video_format, &video_path, events, video_progress)
+            .await?;
+
+        let audio_ext = format!("{:?}", audio_format.download_info.ext);
+        let audio_filename = format!(
+            "temp_audio_{}.{}",
+            utils::file_system::random_filename(8),
+            audio_ext
+        );
+        let audio_path = self.output_dir.join(&audio_filename);
+        self.download_selected_format(audio_format, &audio_path, events, audio_progress)
+            .await?;
+
+        // Combine audio and video, copying the streams accepted by the container
+        let plan = MergePlan::for_formats(
+            MergeContainer::from_path(output.as_ref()),
+            video_format,
+            &[audio_format],
+        );
+        let output_path = self
+            .combine_audio_and_video_internal(
+                &audio_filename,
+                &video_filename,
+                output,
+                &plan,
+                Some(&video),
+                Some(events),
+            )
+            .await?;
+
+        // Clean up temporary files
+        events.emit(DownloadEvent::CleaningUp);
+        if let Err(_e) = tokio::fs::remove_file(&video_path).await {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("Failed to remove temporary video file: {}", _e);
+        }
+
+        if let Err(_e) = tokio::fs::remove_file(&audio_path).await {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("Failed to remove temporary audio file: {}", _e);
+        }
+
+        Ok(output_path)
+    }
+
+    /// Downloads a format picked by a format selector, without quality or codec preferences.
+    async fn download_selected_format(
+        &self,
+        format: &model::format::Format,
+        path: &PathBuf,
+        events: &EventEmitter,
+        progress: Option<ProgressCallback>,
+    ) -> Result<PathBuf> {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "cache")] {
+                self.download_format_internal(format, path, None, None, None, None, Some(events), progress)
+                    .await
+            } else {
+                self.download_format_internal(format, path, Some(events), progress)
                     .await
             }
         }
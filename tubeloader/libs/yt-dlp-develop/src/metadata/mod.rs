@@ -1,16 +1,18 @@
 //! Metadata management module for downloaded files.
 //!
 //! This module provides functionality to add metadata to downloaded files,
-//! such as title, artist, album, etc.
+//! such as title, artist, album, chapters, etc.
 
 use crate::error::{Error, Result};
 use crate::executor::Executor;
+use crate::fetcher::subtitles::iso639_2;
 use crate::model::Video;
+use crate::model::chapter::Chapter;
 use crate::model::format::Format;
 use chrono::DateTime;
 use id3::{Frame as ID3Frame, Tag as ID3Tag, TagLike, Version as ID3Version};
 use mp4ameta;
-use mp4ameta::Tag as MP4Tag;
+use mp4ameta::{Chapter as MP4Chapter, Tag as MP4Tag};
 use std::fmt::Debug;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -134,6 +136,9 @@ pub trait BaseMetadata {
         // Sample rate
         Self::add_metadata_if_some(&mut metadata, "audio_sample_rate", format.codec_info.asr);
 
+        // Audio language
+        Self::add_metadata_if_some(&mut metadata, "audio_language", format.language.clone());
+
         metadata
     }
 }
@@ -208,6 +213,93 @@ impl MetadataManager {
         }
     }
 
+    /// Builds the FFmpeg output arguments recording the language of each audio track of a merged file.
+    /// The first track is marked as the default one, and the others are not, so that players
+    /// start with the most preferred language. Tracks without a known language are tagged 'und'.
+    ///
+    /// # Arguments
+    ///
+    /// * `audio_formats` - The formats of the audio tracks, in the order of the output audio streams.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::metadata::MetadataManager;
+    /// # use yt_dlp::model::Video;
+    /// # fn example(video: &Video) {
+    /// let audio_formats: Vec<_> = video.formats.iter().filter(|f| f.is_audio()).collect();
+    /// let args = MetadataManager::audio_track_args(&audio_formats);
+    /// // ["-metadata:s:a:0", "language=kor", "-disposition:a:0", "default", ...]
+    /// # }
+    /// ```
+    pub fn audio_track_args(audio_formats: &[&Format]) -> Vec<String> {
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            "Building arguments for {} audio tracks",
+            audio_formats.len()
+        );
+
+        audio_formats
+            .iter()
+            .enumerate()
+            .flat_map(|(index, format)| {
+                let language = format
+                    .language
+                    .as_deref()
+                    .map_or_else(|| "und".to_string(), iso639_2);
+                let disposition = if index == 0 { "default" } else { "0" };
+
+                [
+                    format!("-metadata:s:a:{}", index),
+                    format!("language={}", language),
+                    format!("-disposition:a:{}", index),
+                    disposition.to_string(),
+                ]
+            })
+            .collect()
+    }
+
+    /// Add chapters to a file based on its format.
+    ///
+    /// MP4/M4A files get both a chapter list and a chapter track, readable by most players,
+    /// and Matroska files (MKV/WebM) get their chapters through FFmpeg.
+    /// Other formats are left untouched, as well as any file when there are no chapters.
+    /// The chapters are not added by `add_metadata`, since writing them to MKV/WebM files remuxes them.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - The path of the file to add the chapters to.
+    /// * `chapters` - The chapters to add, e.g. `video.chapters`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the chapters could not be written.
+    pub async fn add_chapters(file_path: impl AsRef<Path>, chapters: &[Chapter]) -> Result<()> {
+        #[cfg(feature = "tracing")]
+        tracing::trace!("Adding chapters to file: {:?}", file_path.as_ref());
+
+        if chapters.is_empty() {
+            return Ok(());
+        }
+
+        // Determine file format
+        let file_format = Self::get_file_extension(file_path.as_ref())?;
+
+        match file_format.as_str() {
+            "m4a" | "m4b" | "m4p" | "m4v" | "mp4" => {
+                Self::add_chapters_to_m4a(file_path.as_ref(), chapters)
+            }
+            "webm" | "mkv" => {
+                Self::add_chapters_to_mkv(file_path.as_ref(), chapters, &file_format).await
+            }
+            _ => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("Chapters not supported for file format: {}", file_format);
+                Ok(())
+            }
+        }
+    }
+
     /// Add metadata and thumbnail to a file based on its format.
     pub async fn add_metadata_with_thumbnail(
         file_path: impl AsRef<Path> + Debug + Copy,
@@ -429,6 +521,7 @@ impl MetadataManager {
             executable_path: PathBuf::from("ffmpeg"),
             timeout: Duration::from_secs(120),
             args,
+            env: Vec::new(),
         };
 
         let _ = executor.execute().await?;
@@ -442,6 +535,135 @@ impl MetadataManager {
         Ok(())
     }
 
+    /// Add chapters to an M4A/MP4 file using MP4AMETA
+    fn add_chapters_to_m4a(file_path: &Path, chapters: &[Chapter]) -> Result<()> {
+        #[cfg(feature = "tracing")]
+        tracing::trace!("Adding chapters to M4A/MP4 file: {:?}", file_path);
+
+        let mut tag = MP4Tag::read_from_path(file_path)
+            .map_err(|e| Error::Unknown(format!("Failed to read MP4 tags: {}", e)))?;
+
+        // MP4 chapters only have a start, each one ends where the next one starts
+        let mp4_chapters: Vec<MP4Chapter> = chapters
+            .iter()
+            .map(|chapter| {
+                MP4Chapter::new(
+                    Duration::from_secs_f64(chapter.start_time.0.max(0.0)),
+                    chapter.title.clone(),
+                )
+            })
+            .collect();
+
+        *tag.chapter_list_mut() = mp4_chapters.clone();
+        *tag.chapter_track_mut() = mp4_chapters;
+
+        tag.write_to_path(file_path)
+            .map_err(|e| Error::Unknown(format!("Failed to write MP4 chapters: {}", e)))?;
+
+        Self::log_metadata_debug(format!(
+            "Added {} chapters to M4A/MP4 file: {:?}",
+            chapters.len(),
+            file_path
+        ));
+
+        Ok(())
+    }
+
+    /// Add chapters to a WebM/MKV file using an FFmpeg metadata file
+    async fn add_chapters_to_mkv(
+        file_path: &Path,
+        chapters: &[Chapter],
+        file_format: &str,
+    ) -> Result<()> {
+        #[cfg(feature = "tracing")]
+        tracing::trace!("Adding chapters to WebM/MKV file: {:?}", file_path);
+
+        let temp_output_path = Self::create_temp_output_path(file_path, file_format)?;
+        let metadata_path = Self::create_temp_output_path(file_path, "txt")?;
+        tokio::fs::write(&metadata_path, Self::ffmetadata_chapters(chapters)).await?;
+
+        // Convert paths to strings
+        let input_str = file_path
+            .to_str()
+            .ok_or_else(|| Error::Unknown("Failed to convert input path to string".to_string()))?;
+        let metadata_str = metadata_path.to_str().ok_or_else(|| {
+            Error::Unknown("Failed to convert metadata path to string".to_string())
+        })?;
+        let output_str = temp_output_path
+            .to_str()
+            .ok_or_else(|| Error::Unknown("Failed to convert output path to string".to_string()))?;
+
+        // The streams and the tags come from the file, the chapters from the metadata file
+        let ffmpeg_args = vec![
+            "-i".to_string(),
+            input_str.to_string(),
+            "-i".to_string(),
+            metadata_str.to_string(),
+            "-map".to_string(),
+            "0".to_string(),
+            "-map_metadata".to_string(),
+            "0".to_string(),
+            "-map_chapters".to_string(),
+            "1".to_string(),
+            "-c".to_string(),
+            "copy".to_string(),
+            "-y".to_string(),
+            output_str.to_string(),
+        ];
+
+        let executor = Executor {
+            executable_path: PathBuf::from("ffmpeg"),
+            timeout: Duration::from_secs(120),
+            args: ffmpeg_args,
+            env: Vec::new(),
+        };
+
+        let output = executor.execute().await;
+        let _ = tokio::fs::remove_file(&metadata_path).await;
+
+        // Clean up temporary file if failure
+        if !output.is_ok_and(|output| output.code == 0) {
+            if temp_output_path.exists() {
+                let _ = tokio::fs::remove_file(&temp_output_path).await;
+            }
+
+            return Err(Error::Command("FFmpeg command failed".to_string()));
+        }
+
+        // Replace original file with the file containing chapters
+        tokio::fs::rename(&temp_output_path, file_path)
+            .await
+            .map_err(|e| Error::Unknown(format!("Failed to replace original file: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Build an FFmpeg metadata file defining the given chapters, in milliseconds.
+    fn ffmetadata_chapters(chapters: &[Chapter]) -> String {
+        // '=', ';', '#', '\' and newlines must be escaped in FFmpeg metadata files
+        let escape = |value: &str| {
+            value.chars().fold(String::new(), |mut escaped, c| {
+                if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+                    escaped.push('\\');
+                }
+                escaped.push(c);
+                escaped
+            })
+        };
+
+        let mut metadata = String::from(";FFMETADATA1\n");
+        for chapter in chapters {
+            let start = (chapter.start_time.0.max(0.0) * 1000.0).round() as u64;
+            let end = (chapter.end_time.0.max(0.0) * 1000.0).round() as u64;
+
+            metadata.push_str("[CHAPTER]\nTIMEBASE=1/1000\n");
+            metadata.push_str(&format!("START={}\nEND={}\n", start, end.max(start)));
+            metadata.push_str(&format!("title={}\n", escape(&chapter.title)));
+        }
+
+        metadata
+    }
+
     /// Log metadata debug messages if tracing is enabled
     fn log_metadata_debug<S: AsRef<str>>(_message: S) {
         #[cfg(feature = "tracing")]
@@ -649,7 +871,7 @@ impl MetadataManager {
         // WebM is based on Matroska format and uses specific metadata tags
         let metadata_args: Vec<String> = all_metadata
             .iter()
-            .map(|(key, value)| {
+            .flat_map(|(key, value)| {
                 // Map standard metadata keys to Matroska format keys
                 let matroska_key = match key.as_str() {
                     "title" => "title",
@@ -669,7 +891,11 @@ impl MetadataManager {
                     "audio_sample_rate" => "AUDIOSAMPLERATE",
                     _ => key.as_str(),
                 };
-                format!("-metadata:g {}={}", matroska_key, value)
+                // The option and its value are separate arguments
+                [
+                    "-metadata:g".to_string(),
+                    format!("{}={}", matroska_key, value),
+                ]
             })
             .collect();
 
@@ -700,6 +926,7 @@ impl MetadataManager {
             executable_path: PathBuf::from("ffmpeg"),
             timeout: Duration::from_secs(120),
             args: ffmpeg_args,
+            env: Vec::new(),
         };
 
         let output = executor.execute().await?;
@@ -765,7 +992,7 @@ impl MetadataManager {
         // Build FFmpeg metadata arguments
         let metadata_args: Vec<String> = all_metadata
             .iter()
-            .map(|(key, value)| format!("-metadata {}={}", key, value))
+            .flat_map(|(key, value)| ["-metadata".to_string(), format!("{}={}", key, value)])
             .collect();
 
         // Build the FFmpeg command
@@ -795,6 +1022,7 @@ impl MetadataManager {
             executable_path: PathBuf::from("ffmpeg"),
             timeout: Duration::from_secs(120),
             args: ffmpeg_args,
+            env: Vec::new(),
         };
 
         let output = executor.execute().await?;
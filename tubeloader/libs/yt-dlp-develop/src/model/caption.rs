@@ -3,6 +3,7 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
 
 /// Represents an automatic caption of a YouTube video.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -16,7 +17,11 @@ pub struct AutomaticCaption {
     pub name: Option<String>,
 }
 
-/// The available extensions for automatic caption files.
+/// Represents a subtitle written by the uploader of a YouTube video.
+/// Manual subtitles are listed by 'yt-dlp' in the same shape as automatic captions.
+pub type Subtitle = AutomaticCaption;
+
+/// The available extensions for subtitle and automatic caption files.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Extension {
@@ -33,7 +38,27 @@ pub enum Extension {
     /// The Vtt extension.
     Vtt,
     /// The Srt extension.
-    Srt
+    Srt,
+    /// The JSON extension, used by the live chat replay.
+    Json,
+    /// Any other extension not known by this crate.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Represents a subtitle file downloaded for a video.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubtitleTrack {
+    /// The language code of the subtitle, e.g. 'en' or 'ko'.
+    pub language: String,
+    /// The language name of the subtitle, e.g. 'English'.
+    pub name: Option<String>,
+    /// The extension of the subtitle file.
+    pub extension: Extension,
+    /// If the subtitle is an automatic caption, rather than a manual subtitle.
+    pub automatic: bool,
+    /// The path of the downloaded file.
+    pub path: PathBuf,
 }
 
 // Implementation of the Display trait for AutomaticCaption
@@ -60,6 +85,60 @@ impl Hash for AutomaticCaption {
     }
 }
 
+// Implementation of the Display trait for SubtitleTrack
+impl fmt::Display for SubtitleTrack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "SubtitleTrack(lang={}, ext={}, automatic={}, path={})",
+            self.language,
+            self.extension,
+            self.automatic,
+            self.path.display()
+        )
+    }
+}
+
+// Implementation of Eq for SubtitleTrack
+impl Eq for SubtitleTrack {}
+
+// Implementation of Hash for SubtitleTrack
+impl Hash for SubtitleTrack {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.language.hash(state);
+        self.automatic.hash(state);
+        self.path.hash(state);
+        std::mem::discriminant(&self.extension).hash(state);
+    }
+}
+
+impl Extension {
+    /// Parses an extension from its file extension, e.g. 'srt' or 'vtt'.
+    /// Returns None if the extension is not a known subtitle extension.
+    ///
+    /// # Arguments
+    ///
+    /// * `extension` - The file extension, case insensitive.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.trim_start_matches('.').to_lowercase().as_str() {
+            "json3" => Some(Extension::Json3),
+            "srv1" => Some(Extension::Srv1),
+            "srv2" => Some(Extension::Srv2),
+            "srv3" => Some(Extension::Srv3),
+            "ttml" => Some(Extension::Ttml),
+            "vtt" => Some(Extension::Vtt),
+            "srt" => Some(Extension::Srt),
+            "json" => Some(Extension::Json),
+            _ => None,
+        }
+    }
+
+    /// Returns true if ffmpeg can read files with this extension as a subtitle stream.
+    pub fn is_ffmpeg_readable(&self) -> bool {
+        matches!(self, Extension::Srt | Extension::Vtt)
+    }
+}
+
 // Implementation of the Display trait for Extension
 impl fmt::Display for Extension {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -71,6 +150,8 @@ impl fmt::Display for Extension {
             Extension::Ttml => write!(f, "ttml"),
             Extension::Vtt => write!(f, "vtt"),
             Extension::Srt => write!(f, "srt"),
+            Extension::Json => write!(f, "json"),
+            Extension::Unknown => write!(f, "unknown"),
         }
     }
 }
@@ -0,0 +1,64 @@
+//! Chapters-related models.
+
+use ordered_float::OrderedFloat;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// Represents a chapter of a YouTube video, as defined in its description or by the uploader.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chapter {
+    /// The start time of the chapter, in seconds.
+    pub start_time: OrderedFloat<f64>,
+    /// The end time of the chapter, in seconds.
+    pub end_time: OrderedFloat<f64>,
+    /// The title of the chapter, can be empty.
+    #[serde(default)]
+    pub title: String,
+}
+
+impl Chapter {
+    /// Creates a new chapter.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_time` - The start time of the chapter, in seconds.
+    /// * `end_time` - The end time of the chapter, in seconds.
+    /// * `title` - The title of the chapter.
+    pub fn new(start_time: f64, end_time: f64, title: impl AsRef<str>) -> Self {
+        Self {
+            start_time: OrderedFloat(start_time),
+            end_time: OrderedFloat(end_time),
+            title: title.as_ref().to_string(),
+        }
+    }
+
+    /// Returns the duration of the chapter, in seconds.
+    /// A chapter ending before its start has a duration of 0.
+    pub fn duration(&self) -> f64 {
+        (self.end_time.0 - self.start_time.0).max(0.0)
+    }
+}
+
+// Implementation of the Display trait for Chapter
+impl fmt::Display for Chapter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Chapter(title=\"{}\", start={}, end={})",
+            self.title, self.start_time, self.end_time
+        )
+    }
+}
+
+// Implementation of Eq for Chapter
+impl Eq for Chapter {}
+
+// Implementation of Hash for Chapter
+impl Hash for Chapter {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.start_time.hash(state);
+        self.end_time.hash(state);
+        self.title.hash(state);
+    }
+}
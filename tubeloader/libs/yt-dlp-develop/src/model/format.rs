@@ -27,6 +27,9 @@ pub struct Format {
     pub protocol: Protocol,
     /// The language of the format.
     pub language: Option<String>,
+    /// The preference of the language, 10 for the original audio track of a dubbed video.
+    #[serde(default)]
+    pub language_preference: Option<i64>,
 
     /// If the format has DRM.
     pub has_drm: Option<bool>,
@@ -78,6 +81,17 @@ impl Format {
         format_type.is_audio()
     }
 
+    /// Checks if the format is the original audio track of the video, and not a dubbed one.
+    /// 'yt-dlp' gives the original track the highest language preference, and marks it in the format note.
+    pub fn is_original_audio(&self) -> bool {
+        self.language_preference
+            .is_some_and(|preference| preference >= 10)
+            || self
+                .format_note
+                .as_deref()
+                .is_some_and(|note| note.to_lowercase().contains("original"))
+    }
+
     /// Gets the type of the format.
     /// It can be audio, video, both of them, a manifest, or a storyboard.
     pub fn format_type(&self) -> FormatType {
@@ -356,6 +370,7 @@ impl fmt::Display for HttpHeaders {
 #[serde(rename_all = "snake_case")]
 pub enum Extension {
     /// The M4A extension.
+    #[serde(rename = "m4a")]
     M4A,
     /// The MP3 extension.
     Mp4,
@@ -434,6 +449,11 @@ pub enum Protocol {
     /// The M3U8 protocol, used for manifest formats.
     #[serde(rename = "m3u8_native")]
     M3U8Native,
+    /// The M3U8 protocol, when the fragments were meant to be downloaded by FFmpeg.
+    #[serde(rename = "m3u8")]
+    M3U8,
+    /// The DASH protocol, used for formats made of the segments of a MPD manifest.
+    HttpDashSegments,
     /// The MHTML protocol, used for storyboard formats.
     Mhtml,
 
@@ -443,6 +463,16 @@ pub enum Protocol {
     Unknown,
 }
 
+impl Protocol {
+    /// Checks if the format is made of fragments listed in an HLS playlist or a DASH manifest.
+    pub fn is_manifest(&self) -> bool {
+        matches!(
+            self,
+            Protocol::M3U8Native | Protocol::M3U8 | Protocol::HttpDashSegments
+        )
+    }
+}
+
 impl fmt::Display for Protocol {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -451,6 +481,8 @@ impl fmt::Display for Protocol {
             match self {
                 Protocol::Https => "https",
                 Protocol::M3U8Native => "hls",
+                Protocol::M3U8 => "m3u8",
+                Protocol::HttpDashSegments => "dash",
                 Protocol::Mhtml => "mhtml",
                 Protocol::Unknown => "unknown",
             }
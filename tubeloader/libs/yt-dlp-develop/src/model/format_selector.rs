@@ -1,5 +1,6 @@
 //! Format selector enumerations for audio and video formats.
 
+use crate::model::format::Format;
 use serde::{Deserialize, Serialize};
 
 /// Represents video quality preferences for format selection.
@@ -68,6 +69,50 @@ pub enum AudioCodecPreference {
     Any,
 }
 
+/// Represents language preferences for audio track selection, for dubbed and multi-language videos.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AudioLanguage {
+    /// The original audio track, whatever its language
+    Original,
+    /// An audio track in a language, e.g. 'ko', also matching its regional variants, e.g. 'en' for 'en-US'
+    Code(String),
+}
+
+impl AudioLanguage {
+    /// Parses a language preference, 'original' or a language code.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The language preference, e.g. 'original', 'ko' or 'en-US'.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use yt_dlp::model::AudioLanguage;
+    /// assert_eq!(AudioLanguage::from_name("original"), AudioLanguage::Original);
+    /// assert_eq!(AudioLanguage::from_name("KO"), AudioLanguage::Code("ko".to_string()));
+    /// ```
+    pub fn from_name(name: &str) -> Self {
+        let name = name.trim().to_lowercase();
+        match name.as_str() {
+            "original" | "orig" => AudioLanguage::Original,
+            _ => AudioLanguage::Code(name),
+        }
+    }
+
+    /// Parses a comma-separated list of language preferences, e.g. 'ko,en,original'.
+    ///
+    /// # Arguments
+    ///
+    /// * `list` - The language preferences, from the most to the least preferred.
+    pub fn parse_list(list: &str) -> Vec<Self> {
+        list.split(',')
+            .filter(|name| !name.trim().is_empty())
+            .map(Self::from_name)
+            .collect()
+    }
+}
+
 /// Helper function to check if a video codec matches the preference
 pub fn matches_video_codec(codec: &str, preference: &VideoCodecPreference) -> bool {
     let codec_lower = codec.to_lowercase();
@@ -95,3 +140,14 @@ pub fn matches_audio_codec(codec: &str, preference: &AudioCodecPreference) -> bo
         AudioCodecPreference::Any => true,
     }
 }
+
+/// Helper function to check if the audio track of a format matches the language preference
+pub fn matches_audio_language(format: &Format, preference: &AudioLanguage) -> bool {
+    match preference {
+        AudioLanguage::Original => format.is_original_audio(),
+        AudioLanguage::Code(code) => format.language.as_deref().is_some_and(|language| {
+            let language = language.to_lowercase();
+            language == *code || language.starts_with(&format!("{}-", code))
+        }),
+    }
+}
@@ -1,27 +1,38 @@
 //! The models used to represent the data fetched by 'yt-dlp'.
 //!
-//! The represented data is the video information, thumbnails, automatic captions, and formats.
-
-use crate::model::caption::AutomaticCaption;
-use crate::model::format::Format;
-use crate::model::format_selector::{matches_audio_codec, matches_video_codec};
+//! The represented data is the video information, playlists, thumbnails, subtitles, automatic captions, chapters, SponsorBlock segments, and formats.
+
+use crate::model::caption::{AutomaticCaption, Subtitle};
+use crate::model::chapter::Chapter;
+use crate::model::format::{Format, Protocol};
+use crate::model::format_selector::{
+    matches_audio_codec, matches_audio_language, matches_video_codec,
+};
+use crate::model::selector::FormatSelector;
+use crate::model::sort::FormatSort;
 use crate::model::thumbnail::Thumbnail;
+use crate::model::utils::serde::json_null_default;
 use ordered_float::OrderedFloat;
 use serde::{Deserialize, Serialize};
-use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fmt;
 
 pub mod caption;
+pub mod chapter;
 pub mod format;
 pub mod format_selector;
+pub mod segment;
+pub mod selector;
+pub mod sort;
 pub mod thumbnail;
 pub mod utils;
 
 // Re-export traits for easier access
 pub use utils::{AllTraits, CommonTraits};
 // Re-export format selectors for easier access
-pub use format_selector::{AudioCodecPreference, AudioQuality, VideoCodecPreference, VideoQuality};
+pub use format_selector::{
+    AudioCodecPreference, AudioLanguage, AudioQuality, VideoCodecPreference, VideoQuality,
+};
 
 /// Represents a YouTube video, the output of 'yt-dlp'.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -36,11 +47,18 @@ pub struct Video {
     pub description: String,
     /// If the video is public, unlisted, or private.
     pub availability: String,
-    /// The upload date of the video.
-    #[serde(rename = "timestamp")]
+    /// The upload date of the video, 0 for live streams which have not started yet.
+    #[serde(rename = "timestamp", default, deserialize_with = "json_null_default")]
     pub upload_date: i64,
+    /// The scheduled or actual start of a live stream or a premiere, as a Unix timestamp.
+    #[serde(default)]
+    pub release_timestamp: Option<i64>,
+    /// The duration of the video, in seconds. None for live streams.
+    #[serde(default)]
+    pub duration: Option<OrderedFloat<f64>>,
 
     /// The number of views the video has.
+    #[serde(default, deserialize_with = "json_null_default")]
     pub view_count: i64,
     /// The number of likes the video has. None, when the author has hidden it.
     pub like_count: Option<i64>,
@@ -56,12 +74,19 @@ pub struct Video {
     /// The number of subscribers the channel has.
     pub channel_follower_count: Option<i64>,
 
-    /// The available formats of the video.
+    /// The available formats of the video. Empty for live streams which have not started yet.
+    #[serde(default, deserialize_with = "json_null_default")]
     pub formats: Vec<Format>,
     /// The thumbnails of the video.
     pub thumbnails: Vec<Thumbnail>,
     /// The automatic captions of the video.
     pub automatic_captions: HashMap<String, Vec<AutomaticCaption>>,
+    /// The subtitles written by the uploader, by language code.
+    #[serde(default)]
+    pub subtitles: HashMap<String, Vec<Subtitle>>,
+    /// The chapters of the video, in order. Empty when the video has no chapters.
+    #[serde(default, deserialize_with = "json_null_default")]
+    pub chapters: Vec<Chapter>,
 
     /// The tags of the video.
     pub tags: Vec<String>,
@@ -73,7 +98,7 @@ pub struct Video {
     /// If the video is available in the country.
     #[serde(rename = "_has_drm")]
     pub has_drm: Option<bool>,
-    /// If the video was a live stream.
+    /// If the video is, was, or will be a live stream, e.g. 'is_live', 'is_upcoming', 'was_live' or 'not_live'.
     pub live_status: String,
     /// If the video is playable in an embed.
     pub playable_in_embed: bool,
@@ -84,6 +109,105 @@ pub struct Video {
     /// The version of 'yt-dlp' used to fetch the video.
     #[serde(rename = "_version")]
     pub version: Version,
+
+    /// The sort order used to rank the formats, set by `Youtube` when fetching the video.
+    /// An empty order keeps the default ranking.
+    #[serde(skip)]
+    pub format_sort: FormatSort,
+}
+
+/// Represents a playlist or a channel page, the output of 'yt-dlp' with '--flat-playlist'.
+///
+/// The entries are not fully resolved: they only carry the basic information of each video,
+/// and must be fetched with `Youtube::fetch_video_infos` to get their formats.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Playlist {
+    /// The ID of the playlist, or the channel ID for channel pages.
+    pub id: String,
+    /// The title of the playlist.
+    pub title: String,
+    /// The description of the playlist.
+    pub description: Option<String>,
+    /// The display name of the playlist owner.
+    pub uploader: Option<String>,
+    /// The number of entries announced by the playlist.
+    pub playlist_count: Option<i64>,
+    /// The URL of the playlist page.
+    pub webpage_url: Option<String>,
+
+    /// The channel display name, for channel pages and channel-owned playlists.
+    pub channel: Option<String>,
+    /// The channel ID, not the @username.
+    pub channel_id: Option<String>,
+    /// The URL of the channel.
+    pub channel_url: Option<String>,
+    /// The number of subscribers the channel has.
+    pub channel_follower_count: Option<i64>,
+
+    /// The flat entries of the playlist, in the playlist order.
+    #[serde(default)]
+    pub entries: Vec<PlaylistEntry>,
+}
+
+/// Represents the channel owning a playlist or a channel page.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Channel {
+    /// The channel ID, not the @username.
+    pub id: String,
+    /// The channel display name.
+    pub name: String,
+    /// The URL of the channel.
+    pub url: Option<String>,
+    /// The number of subscribers the channel has.
+    pub follower_count: Option<i64>,
+}
+
+/// Represents a flat entry of a playlist, without its formats.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaylistEntry {
+    /// The ID of the video.
+    pub id: String,
+    /// The URL of the video, if provided by the extractor.
+    pub url: Option<String>,
+    /// The title of the video.
+    pub title: Option<String>,
+    /// The duration of the video, in seconds.
+    pub duration: Option<OrderedFloat<f64>>,
+    /// The upload date of the video, rarely available for flat entries.
+    #[serde(rename = "timestamp")]
+    pub upload_date: Option<i64>,
+
+    /// The channel display name.
+    pub channel: Option<String>,
+    /// The channel ID, not the @username.
+    pub channel_id: Option<String>,
+    /// If the video is, was, or will be a live stream.
+    pub live_status: Option<String>,
+}
+
+impl Playlist {
+    /// Returns the channel owning this playlist, if 'yt-dlp' reported one.
+    pub fn channel(&self) -> Option<Channel> {
+        let id = self.channel_id.clone()?;
+
+        Some(Channel {
+            name: self.channel.clone().unwrap_or_else(|| id.clone()),
+            id,
+            url: self.channel_url.clone(),
+            follower_count: self.channel_follower_count,
+        })
+    }
+}
+
+impl PlaylistEntry {
+    /// Returns the URL to use to fetch the complete video information.
+    /// If the extractor did not provide one, it is built from the video ID.
+    pub fn video_url(&self) -> String {
+        match &self.url {
+            Some(url) if url.starts_with("http") => url.clone(),
+            _ => format!("https://www.youtube.com/watch?v={}", self.id),
+        }
+    }
 }
 
 /// Represents the extractor information.
@@ -109,8 +233,39 @@ pub struct Version {
 }
 
 impl Video {
+    /// Checks if the video is a live stream currently broadcasting.
+    pub fn is_live(&self) -> bool {
+        self.live_status == "is_live"
+    }
+
+    /// Checks if the video is a live stream or a premiere which has not started yet.
+    pub fn is_upcoming(&self) -> bool {
+        self.live_status == "is_upcoming"
+    }
+
+    /// Returns the best format to record a live stream: an HLS format, preferably with video and audio.
+    /// Formats sorting : formats with audio first, then the same order as `best_video_format`.
+    /// If the video has no HLS video formats, it returns None.
+    pub fn best_live_format(&self) -> Option<&Format> {
+        #[cfg(feature = "tracing")]
+        tracing::trace!("Selecting best live format for video: {}", self.id);
+
+        self.formats
+            .iter()
+            .filter(|f| matches!(f.protocol, Protocol::M3U8Native | Protocol::M3U8))
+            .filter(|f| f.codec_info.video_codec.is_some())
+            .max_by(|a, b| {
+                let a_audio = a.codec_info.audio_codec.is_some();
+                let b_audio = b.codec_info.audio_codec.is_some();
+
+                a_audio
+                    .cmp(&b_audio)
+                    .then_with(|| self.compare_video_formats(a, b))
+            })
+    }
+
     /// Returns the best format available.
-    /// Formats sorting : "quality", "video resolution", "fps", "video bitrate"
+    /// Formats sorting : the format sort of the video, then "quality", "video resolution", "fps", "video bitrate"
     /// If the video has no formats video formats, it returns None.
     pub fn best_video_format(&self) -> Option<&Format> {
         #[cfg(feature = "tracing")]
@@ -123,7 +278,7 @@ impl Video {
     }
 
     /// Returns the best audio format available.
-    /// Formats sorting : "quality", "audio bitrate", "sample rate", "audio channels"
+    /// Formats sorting : the format sort of the video, then "quality", "audio bitrate", "sample rate", "audio channels"
     /// If the video has no formats audio formats, it returns None.
     pub fn best_audio_format(&self) -> Option<&Format> {
         #[cfg(feature = "tracing")]
@@ -136,7 +291,7 @@ impl Video {
     }
 
     /// Returns the worst video format available.
-    /// Formats sorting : "quality", "video resolution", "fps", "video bitrate"
+    /// Formats sorting : the format sort of the video, then "quality", "video resolution", "fps", "video bitrate"
     /// If the video has no formats video formats, it returns None.
     pub fn worst_video_format(&self) -> Option<&Format> {
         #[cfg(feature = "tracing")]
@@ -149,7 +304,7 @@ impl Video {
     }
 
     /// Returns the worst audio format available.
-    /// Formats sorting : "quality", "audio bitrate", "sample rate", "audio channels"
+    /// Formats sorting : the format sort of the video, then "quality", "audio bitrate", "sample rate", "audio channels"
     /// If the video has no formats audio formats, it returns None.
     pub fn worst_audio_format(&self) -> Option<&Format> {
         #[cfg(feature = "tracing")]
@@ -162,7 +317,7 @@ impl Video {
     }
 
     /// Compares two video formats.
-    /// Formats sorting : "quality", "video resolution", "fps", "video bitrate"
+    /// Formats sorting : the format sort of the video, then "quality", "video resolution", "fps", "video bitrate"
     pub fn compare_video_formats(&self, a: &Format, b: &Format) -> std::cmp::Ordering {
         #[cfg(feature = "tracing")]
         tracing::trace!(
@@ -171,6 +326,11 @@ impl Video {
             b.format_id
         );
 
+        let cmp_sort = self.format_sort.compare(a, b);
+        if cmp_sort != std::cmp::Ordering::Equal {
+            return cmp_sort;
+        }
+
         let a_quality = a.quality_info.quality.unwrap_or(OrderedFloat(0.0));
         let b_quality = b.quality_info.quality.unwrap_or(OrderedFloat(0.0));
 
@@ -202,7 +362,7 @@ impl Video {
     }
 
     /// Compares two audio formats.
-    /// Formats sorting : "quality", "audio bitrate", "sample rate", "audio channels"
+    /// Formats sorting : the format sort of the video, then "quality", "audio bitrate", "sample rate", "audio channels"
     pub fn compare_audio_formats(&self, a: &Format, b: &Format) -> std::cmp::Ordering {
         #[cfg(feature = "tracing")]
         tracing::trace!(
@@ -211,6 +371,11 @@ impl Video {
             b.format_id
         );
 
+        let cmp_sort = self.format_sort.compare(a, b);
+        if cmp_sort != std::cmp::Ordering::Equal {
+            return cmp_sort;
+        }
+
         let a_quality = a.quality_info.quality.unwrap_or(OrderedFloat(0.0));
         let b_quality = b.quality_info.quality.unwrap_or(OrderedFloat(0.0));
 
@@ -250,7 +415,8 @@ impl Video {
     ///
     /// # Returns
     ///
-    /// The selected format, or None if no suitable format is found
+    /// The selected format, or None if no suitable format is found.
+    /// When the video has a format sort, it ranks the candidates before their closeness to the quality preference.
     pub fn select_video_format(
         &self,
         quality: VideoQuality,
@@ -332,7 +498,8 @@ impl Video {
     ///
     /// # Returns
     ///
-    /// The selected format, or None if no suitable format is found
+    /// The selected format, or None if no suitable format is found.
+    /// When the video has a format sort, it ranks the candidates before their closeness to the quality preference.
     pub fn select_audio_format(
         &self,
         quality: AudioQuality,
@@ -351,6 +518,68 @@ impl Video {
             .filter(|format| format.is_audio())
             .collect();
 
+        self.select_audio_format_from(audio_formats, quality, codec)
+    }
+
+    /// Selects one audio format per language, for dubbed and multi-language videos.
+    /// Languages without an audio track are skipped, as well as languages matching an already selected track,
+    /// e.g. 'original' when the original track is also the first requested language.
+    ///
+    /// # Arguments
+    ///
+    /// * `languages` - The preferred languages, from the most to the least preferred
+    /// * `quality` - The desired audio quality
+    /// * `codec` - The preferred audio codec
+    ///
+    /// # Returns
+    ///
+    /// The selected formats, in the order of the languages, or an empty list if no language is available
+    pub fn select_audio_formats_by_language(
+        &self,
+        languages: &[AudioLanguage],
+        quality: AudioQuality,
+        codec: AudioCodecPreference,
+    ) -> Vec<&Format> {
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            "Selecting audio formats with languages: {:?}, quality: {:?}, codec: {:?}",
+            languages,
+            quality,
+            codec
+        );
+
+        let mut selected: Vec<&Format> = Vec::new();
+        for language in languages {
+            let audio_formats: Vec<&Format> = self
+                .formats
+                .iter()
+                .filter(|format| format.is_audio() && matches_audio_language(format, language))
+                .collect();
+
+            let Some(format) = self.select_audio_format_from(audio_formats, quality, codec.clone())
+            else {
+                continue;
+            };
+
+            let duplicate = selected.iter().any(|other| {
+                other.format_id == format.format_id
+                    || (other.language.is_some() && other.language == format.language)
+            });
+            if !duplicate {
+                selected.push(format);
+            }
+        }
+
+        selected
+    }
+
+    /// Selects an audio format among the given ones, based on quality preference and codec preference.
+    fn select_audio_format_from<'a>(
+        &self,
+        audio_formats: Vec<&'a Format>,
+        quality: AudioQuality,
+        codec: AudioCodecPreference,
+    ) -> Option<&'a Format> {
         if audio_formats.is_empty() {
             return None;
         }
@@ -400,6 +629,23 @@ impl Video {
             }
         }
     }
+
+    /// Selects formats with a 'yt-dlp' format selector, e.g. `bv*[height<=1080]+ba/b`.
+    /// Merged formats are returned in the order of the selector.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - The parsed format selector
+    ///
+    /// # Returns
+    ///
+    /// The selected formats, or None if no alternative of the selector matches
+    pub fn select_formats(&self, selector: &FormatSelector) -> Option<Vec<&Format>> {
+        #[cfg(feature = "tracing")]
+        tracing::trace!("Selecting formats with selector: {}", selector.selector());
+
+        selector.select(self)
+    }
 }
 
 /// Selects the video format with the closest height to the target
@@ -444,10 +690,12 @@ fn select_closest_video_height<'a>(
                 .unwrap_or(0)
                 .saturating_sub(target_height);
 
-            // Compare difference then quality
-            a_diff
-                .cmp(&b_diff)
-                .then_with(|| video.compare_video_formats(a, b))
+            // Compare sort order, difference then quality, the preferred formats being the smallest
+            video
+                .format_sort
+                .compare(b, a)
+                .then(a_diff.cmp(&b_diff))
+                .then_with(|| video.compare_video_formats(b, a))
         });
     }
 
@@ -456,9 +704,11 @@ fn select_closest_video_height<'a>(
         let a_height = a.video_resolution.height.unwrap_or(0);
         let b_height = b.video_resolution.height.unwrap_or(0);
 
-        // Compare height then quality
-        a_height
-            .cmp(&b_height)
+        // Compare sort order, height then quality
+        video
+            .format_sort
+            .compare(a, b)
+            .then(a_height.cmp(&b_height))
             .then_with(|| video.compare_video_formats(a, b))
     })
 }
@@ -502,10 +752,12 @@ fn select_closest_video_width<'a>(
                 .unwrap_or(0)
                 .saturating_sub(target_width);
 
-            // Compare difference then quality
-            a_diff
-                .cmp(&b_diff)
-                .then_with(|| video.compare_video_formats(a, b))
+            // Compare sort order, difference then quality, the preferred formats being the smallest
+            video
+                .format_sort
+                .compare(b, a)
+                .then(a_diff.cmp(&b_diff))
+                .then_with(|| video.compare_video_formats(b, a))
         });
     }
 
@@ -514,9 +766,11 @@ fn select_closest_video_width<'a>(
         let a_width = a.video_resolution.width.unwrap_or(0);
         let b_width = b.video_resolution.width.unwrap_or(0);
 
-        // Compare width then quality
-        a_width
-            .cmp(&b_width)
+        // Compare sort order, width then quality
+        video
+            .format_sort
+            .compare(a, b)
+            .then(a_width.cmp(&b_width))
             .then_with(|| video.compare_video_formats(a, b))
     })
 }
@@ -560,11 +814,12 @@ fn select_closest_audio_bitrate<'a>(
             let a_diff = (a_rate.0 - target_bitrate as f64).abs();
             let b_diff = (b_rate.0 - target_bitrate as f64).abs();
 
-            // Compare bitrate difference then quality
-            OrderedFloat(a_diff)
-                .partial_cmp(&OrderedFloat(b_diff))
-                .unwrap_or(Ordering::Equal)
-                .then_with(|| video.compare_audio_formats(a, b))
+            // Compare sort order, bitrate difference then quality, the preferred formats being the smallest
+            video
+                .format_sort
+                .compare(b, a)
+                .then(OrderedFloat(a_diff).cmp(&OrderedFloat(b_diff)))
+                .then_with(|| video.compare_audio_formats(b, a))
         });
     }
 
@@ -573,10 +828,11 @@ fn select_closest_audio_bitrate<'a>(
         let a_rate = a.rates_info.audio_rate.unwrap_or(OrderedFloat(0.0));
         let b_rate = b.rates_info.audio_rate.unwrap_or(OrderedFloat(0.0));
 
-        // Compare bitrate then quality
-        a_rate
-            .partial_cmp(&b_rate)
-            .unwrap_or(Ordering::Equal)
+        // Compare sort order, bitrate then quality
+        video
+            .format_sort
+            .compare(a, b)
+            .then(a_rate.cmp(&b_rate))
             .then_with(|| video.compare_audio_formats(a, b))
     })
 }
@@ -595,6 +851,38 @@ impl fmt::Display for Video {
     }
 }
 
+// Implementation of the Display trait for Playlist
+impl fmt::Display for Playlist {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Playlist(id = {}, title = \"{}\", entries = {})",
+            self.id,
+            self.title,
+            self.entries.len()
+        )
+    }
+}
+
+// Implementation of the Display trait for PlaylistEntry
+impl fmt::Display for PlaylistEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "PlaylistEntry(id = {}, title = \"{}\")",
+            self.id,
+            self.title.as_deref().unwrap_or("unknown")
+        )
+    }
+}
+
+// Implementation of the Display trait for Channel
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Channel(id = {}, name = \"{}\")", self.id, self.name)
+    }
+}
+
 // Implementation of the Display trait for ExtractorInfo
 impl fmt::Display for ExtractorInfo {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -619,6 +907,9 @@ impl fmt::Display for Version {
 
 // Implementation of Eq for structures that support it
 impl Eq for Video {}
+impl Eq for Playlist {}
+impl Eq for PlaylistEntry {}
+impl Eq for Channel {}
 impl Eq for Version {}
 impl Eq for ExtractorInfo {}
 
@@ -632,6 +923,29 @@ impl std::hash::Hash for Video {
     }
 }
 
+impl std::hash::Hash for Playlist {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.title.hash(state);
+        self.entries.hash(state);
+    }
+}
+
+impl std::hash::Hash for PlaylistEntry {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.url.hash(state);
+        self.title.hash(state);
+    }
+}
+
+impl std::hash::Hash for Channel {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.name.hash(state);
+    }
+}
+
 impl std::hash::Hash for Version {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.version.hash(state);
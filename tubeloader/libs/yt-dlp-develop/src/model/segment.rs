@@ -0,0 +1,240 @@
+//! SponsorBlock segments-related models.
+
+use ordered_float::OrderedFloat;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// Represents a segment of a video submitted to SponsorBlock, e.g. a sponsor or an intro.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SponsorSegment {
+    /// The category of the segment.
+    pub category: SegmentCategory,
+    /// What players should do with the segment.
+    #[serde(rename = "actionType", default)]
+    pub action_type: ActionType,
+    /// The start and end times of the segment, in seconds.
+    pub segment: (OrderedFloat<f64>, OrderedFloat<f64>),
+    /// The ID of the segment on SponsorBlock.
+    #[serde(rename = "UUID", default)]
+    pub uuid: String,
+    /// The duration of the video when the segment was submitted, 0 when unknown.
+    #[serde(rename = "videoDuration", default)]
+    pub video_duration: OrderedFloat<f64>,
+}
+
+impl SponsorSegment {
+    /// Creates a new segment to skip.
+    ///
+    /// # Arguments
+    ///
+    /// * `category` - The category of the segment.
+    /// * `start_time` - The start time of the segment, in seconds.
+    /// * `end_time` - The end time of the segment, in seconds.
+    pub fn new(category: SegmentCategory, start_time: f64, end_time: f64) -> Self {
+        Self {
+            category,
+            action_type: ActionType::Skip,
+            segment: (OrderedFloat(start_time), OrderedFloat(end_time)),
+            uuid: String::new(),
+            video_duration: OrderedFloat(0.0),
+        }
+    }
+
+    /// Returns the start time of the segment, in seconds.
+    pub fn start_time(&self) -> f64 {
+        self.segment.0.0
+    }
+
+    /// Returns the end time of the segment, in seconds.
+    pub fn end_time(&self) -> f64 {
+        self.segment.1.0
+    }
+}
+
+/// The categories of SponsorBlock segments.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentCategory {
+    /// A paid promotion, or a referral.
+    Sponsor,
+    /// An unpaid promotion, e.g. of merchandise or of another channel.
+    Selfpromo,
+    /// A reminder to like, subscribe or follow.
+    Interaction,
+    /// An intermission or an intro animation.
+    Intro,
+    /// Endcards or credits.
+    Outro,
+    /// A preview or a recap of the content.
+    Preview,
+    /// A non-music section of a music video.
+    MusicOfftopic,
+    /// A tangent that is not needed to understand the content.
+    Filler,
+    /// The most important part of the video.
+    PoiHighlight,
+    /// A chapter submitted to SponsorBlock.
+    Chapter,
+    /// A category added after this version.
+    #[serde(other)]
+    Unknown,
+}
+
+impl SegmentCategory {
+    /// The categories of segments that can be skipped.
+    pub const SKIPPABLE: [SegmentCategory; 8] = [
+        SegmentCategory::Sponsor,
+        SegmentCategory::Selfpromo,
+        SegmentCategory::Interaction,
+        SegmentCategory::Intro,
+        SegmentCategory::Outro,
+        SegmentCategory::Preview,
+        SegmentCategory::MusicOfftopic,
+        SegmentCategory::Filler,
+    ];
+
+    /// Parses a category from its SponsorBlock name, e.g. 'sponsor' or 'music_offtopic'.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the category.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use yt_dlp::model::segment::SegmentCategory;
+    /// assert_eq!(SegmentCategory::from_name("selfpromo"), Some(SegmentCategory::Selfpromo));
+    /// assert_eq!(SegmentCategory::from_name("unknown"), None);
+    /// ```
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "sponsor" => Some(SegmentCategory::Sponsor),
+            "selfpromo" => Some(SegmentCategory::Selfpromo),
+            "interaction" => Some(SegmentCategory::Interaction),
+            "intro" => Some(SegmentCategory::Intro),
+            "outro" => Some(SegmentCategory::Outro),
+            "preview" => Some(SegmentCategory::Preview),
+            "music_offtopic" => Some(SegmentCategory::MusicOfftopic),
+            "filler" => Some(SegmentCategory::Filler),
+            "poi_highlight" => Some(SegmentCategory::PoiHighlight),
+            "chapter" => Some(SegmentCategory::Chapter),
+            _ => None,
+        }
+    }
+
+    /// Returns the human-readable title of the category, used to name chapters.
+    pub fn title(&self) -> &'static str {
+        match self {
+            SegmentCategory::Sponsor => "Sponsor",
+            SegmentCategory::Selfpromo => "Unpaid/Self Promotion",
+            SegmentCategory::Interaction => "Interaction Reminder",
+            SegmentCategory::Intro => "Intermission/Intro Animation",
+            SegmentCategory::Outro => "Endcards/Credits",
+            SegmentCategory::Preview => "Preview/Recap",
+            SegmentCategory::MusicOfftopic => "Non-Music Section",
+            SegmentCategory::Filler => "Filler Tangent",
+            SegmentCategory::PoiHighlight => "Highlight",
+            SegmentCategory::Chapter => "Chapter",
+            SegmentCategory::Unknown => "Unknown",
+        }
+    }
+}
+
+/// The actions that players should take on SponsorBlock segments.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionType {
+    /// The segment should be skipped.
+    #[default]
+    Skip,
+    /// The audio of the segment should be muted.
+    Mute,
+    /// The whole video is about the category, e.g. a sponsored video.
+    Full,
+    /// The segment is a point of interest, without duration.
+    Poi,
+    /// The segment is a chapter.
+    Chapter,
+    /// An action added after this version.
+    #[serde(other)]
+    Unknown,
+}
+
+// Implementation of the Display trait for SponsorSegment
+impl fmt::Display for SponsorSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "SponsorSegment(category={}, start={}, end={})",
+            self.category,
+            self.start_time(),
+            self.end_time()
+        )
+    }
+}
+
+// Implementation of the Display trait for SegmentCategory
+impl fmt::Display for SegmentCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SegmentCategory::Sponsor => write!(f, "sponsor"),
+            SegmentCategory::Selfpromo => write!(f, "selfpromo"),
+            SegmentCategory::Interaction => write!(f, "interaction"),
+            SegmentCategory::Intro => write!(f, "intro"),
+            SegmentCategory::Outro => write!(f, "outro"),
+            SegmentCategory::Preview => write!(f, "preview"),
+            SegmentCategory::MusicOfftopic => write!(f, "music_offtopic"),
+            SegmentCategory::Filler => write!(f, "filler"),
+            SegmentCategory::PoiHighlight => write!(f, "poi_highlight"),
+            SegmentCategory::Chapter => write!(f, "chapter"),
+            SegmentCategory::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+// Implementation of the Display trait for ActionType
+impl fmt::Display for ActionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionType::Skip => write!(f, "skip"),
+            ActionType::Mute => write!(f, "mute"),
+            ActionType::Full => write!(f, "full"),
+            ActionType::Poi => write!(f, "poi"),
+            ActionType::Chapter => write!(f, "chapter"),
+            ActionType::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+// Implementation of Eq for SponsorSegment
+impl Eq for SponsorSegment {}
+
+// Implementation of Hash for SponsorSegment
+impl Hash for SponsorSegment {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.category.hash(state);
+        self.segment.hash(state);
+        self.uuid.hash(state);
+    }
+}
+
+// Implementation of Eq for SegmentCategory
+impl Eq for SegmentCategory {}
+
+// Implementation of Hash for SegmentCategory
+impl Hash for SegmentCategory {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+    }
+}
+
+// Implementation of Eq for ActionType
+impl Eq for ActionType {}
+
+// Implementation of Hash for ActionType
+impl Hash for ActionType {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+    }
+}
@@ -0,0 +1,844 @@
+//! Format selectors, to pick the formats of a video with the expressions of 'yt-dlp'.
+//!
+//! A selector is a list of alternatives separated by `/`, tried in order until one matches.
+//! Each alternative merges formats with `+`, and each format is picked by a keyword or by its ID,
+//! followed by filters between brackets, e.g. `bv*[height<=1080][vcodec^=avc1]+ba[language=ko]/b[ext=mp4]`.
+//!
+//! The keywords are `b`/`best` and `w`/`worst` for formats with both audio and video,
+//! `bv`/`bestvideo` and `wv`/`worstvideo` for video-only formats,
+//! and `ba`/`bestaudio` and `wa`/`worstaudio` for audio-only formats.
+//! A trailing `*` also accepts the formats with both audio and video, e.g. `bv*`,
+//! while `b*` and `w*` accept any format with audio or video.
+//!
+//! Filters compare a field with `<`, `<=`, `>`, `>=`, `=` or `!=` for numbers, and with `=`,
+//! `^=` (starts with), `$=` (ends with), `*=` (contains) or `~=` (matches a regex) for strings,
+//! which are negated with a leading `!`, e.g. `[vcodec!^=av01]`.
+//! A `?` after the operator also accepts the formats where the field is unknown, e.g. `[height<=?720]`.
+//! Numbers accept the suffixes `k`, `M`, `G` and `T`, and their binary forms `Ki`, `Mi`, `Gi` and `Ti`,
+//! e.g. `[filesize<50M]`.
+//!
+//! Parentheses group selectors, and the filters of a group apply to all its formats,
+//! e.g. `(bv+ba/b)[height<=720]`.
+
+use crate::error::{Error, Result};
+use crate::model::Video;
+use crate::model::format::{Format, FormatType};
+use regex::Regex;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// The fields that can be used in a filter.
+pub const FIELDS: &[&str] = &[
+    "format_id",
+    "format",
+    "format_note",
+    "ext",
+    "container",
+    "protocol",
+    "language",
+    "vcodec",
+    "acodec",
+    "video_ext",
+    "audio_ext",
+    "resolution",
+    "dynamic_range",
+    "width",
+    "height",
+    "fps",
+    "aspect_ratio",
+    "asr",
+    "audio_channels",
+    "vbr",
+    "abr",
+    "tbr",
+    "quality",
+    "filesize",
+    "filesize_approx",
+];
+
+/// A parsed format selector.
+#[derive(Debug, Clone)]
+pub struct FormatSelector {
+    /// The selector, as given by the user.
+    selector: String,
+    /// The parsed selector.
+    root: Selector,
+}
+
+impl FormatSelector {
+    /// Parses a new format selector.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - The selector, e.g. `bv*[height<=1080]+ba/b`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the selector is malformed, if a field is unknown,
+    /// or if an operator does not apply to its field.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use yt_dlp::model::selector::FormatSelector;
+    /// let selector = FormatSelector::new("bv*[height<=1080][vcodec^=avc1]+ba[language=ko]/b[ext=mp4]");
+    /// assert!(selector.is_ok());
+    ///
+    /// let selector = FormatSelector::new("bv[height^=10]");
+    /// assert!(selector.is_err());
+    /// ```
+    pub fn new(selector: impl Into<String>) -> Result<Self> {
+        let selector = selector.into();
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Parsing format selector: {}", selector);
+
+        let mut parser = Parser::new(&selector);
+        let root = parser.parse_alternatives()?;
+        parser.skip_whitespace();
+        if let Some(c) = parser.peek() {
+            return Err(parser.error(format!("unexpected '{}'", c)));
+        }
+
+        Ok(Self { selector, root })
+    }
+
+    /// Returns the selector, as given by the user.
+    pub fn selector(&self) -> &str {
+        &self.selector
+    }
+
+    /// Selects the formats of a video, in the order of the selector.
+    /// Returns `None` if no alternative of the selector matches.
+    ///
+    /// # Arguments
+    ///
+    /// * `video` - The video to select the formats from.
+    pub(crate) fn select<'a>(&self, video: &'a Video) -> Option<Vec<&'a Format>> {
+        let formats: Vec<&Format> = video.formats.iter().collect();
+
+        self.root.select(video, &formats, &[])
+    }
+}
+
+/// A node of a parsed selector.
+#[derive(Debug, Clone)]
+enum Selector {
+    /// Selectors tried in order until one matches, separated by `/`.
+    Alternatives(Vec<Selector>),
+    /// Selectors whose formats are all selected, separated by `+`.
+    Merge(Vec<Selector>),
+    /// A single format.
+    Single {
+        /// How the format is picked.
+        base: Base,
+        /// The filters of the format.
+        filters: Vec<Filter>,
+    },
+    /// A selector between parentheses.
+    Group {
+        /// The selector of the group.
+        selector: Box<Selector>,
+        /// The filters applied to all the formats of the group.
+        filters: Vec<Filter>,
+    },
+}
+
+impl Selector {
+    /// Selects the formats matching this selector and the filters of its enclosing groups.
+    fn select<'a>(
+        &self,
+        video: &Video,
+        formats: &[&'a Format],
+        group_filters: &[&Filter],
+    ) -> Option<Vec<&'a Format>> {
+        match self {
+            Selector::Alternatives(selectors) => selectors
+                .iter()
+                .find_map(|selector| selector.select(video, formats, group_filters)),
+            Selector::Merge(selectors) => {
+                let mut selected = Vec::new();
+                for selector in selectors {
+                    selected.extend(selector.select(video, formats, group_filters)?);
+                }
+                Some(selected)
+            }
+            Selector::Single { base, filters } => {
+                let candidates = formats.iter().copied().filter(|format| {
+                    base.accepts(format)
+                        && filters.iter().all(|filter| filter.matches(format))
+                        && group_filters.iter().all(|filter| filter.matches(format))
+                });
+                base.pick(video, candidates).map(|format| vec![format])
+            }
+            Selector::Group { selector, filters } => {
+                let group_filters: Vec<&Filter> =
+                    group_filters.iter().copied().chain(filters).collect();
+                selector.select(video, formats, &group_filters)
+            }
+        }
+    }
+}
+
+/// How a single format is picked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Base {
+    /// The best or worst format of a kind, e.g. `bv*`.
+    Keyword {
+        /// Whether the best or the worst format is picked.
+        best: bool,
+        /// The streams the format must have.
+        kind: Kind,
+        /// Whether formats with both audio and video are also accepted, with a trailing `*`.
+        any: bool,
+    },
+    /// The format with the given ID, e.g. `137`.
+    FormatId(String),
+}
+
+impl Base {
+    /// Checks if a format can be picked, before filtering.
+    fn accepts(&self, format: &Format) -> bool {
+        match self {
+            Base::Keyword { kind, any, .. } => {
+                let format_type = format.format_type();
+                match (kind, any) {
+                    (Kind::Both, false) => format_type == FormatType::AudioVideo,
+                    (Kind::Both, true) => matches!(
+                        format_type,
+                        FormatType::Audio | FormatType::Video | FormatType::AudioVideo
+                    ),
+                    (Kind::Video, false) => format_type == FormatType::Video,
+                    (Kind::Video, true) => {
+                        matches!(format_type, FormatType::Video | FormatType::AudioVideo)
+                    }
+                    (Kind::Audio, false) => format_type == FormatType::Audio,
+                    (Kind::Audio, true) => {
+                        matches!(format_type, FormatType::Audio | FormatType::AudioVideo)
+                    }
+                }
+            }
+            Base::FormatId(format_id) => format.format_id == *format_id,
+        }
+    }
+
+    /// Picks a format among the accepted and filtered ones.
+    fn pick<'a>(
+        &self,
+        video: &Video,
+        candidates: impl Iterator<Item = &'a Format>,
+    ) -> Option<&'a Format> {
+        match self {
+            Base::Keyword { best, kind, .. } => {
+                let compare = |a: &&Format, b: &&Format| kind.compare(video, a, b);
+                if *best {
+                    candidates.max_by(compare)
+                } else {
+                    candidates.min_by(compare)
+                }
+            }
+            // 'yt-dlp' lists the formats from the worst to the best
+            Base::FormatId(_) => candidates.last(),
+        }
+    }
+}
+
+/// The streams a format picked by a keyword must have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// Both audio and video, e.g. `b`.
+    Both,
+    /// Video, e.g. `bv`.
+    Video,
+    /// Audio, e.g. `ba`.
+    Audio,
+}
+
+impl Kind {
+    /// Compares two formats of this kind.
+    fn compare(&self, video: &Video, a: &Format, b: &Format) -> Ordering {
+        match self {
+            Kind::Audio => video.compare_audio_formats(a, b),
+            Kind::Both | Kind::Video => video
+                .compare_video_formats(a, b)
+                .then_with(|| video.compare_audio_formats(a, b)),
+        }
+    }
+}
+
+/// A filter between brackets, e.g. `[height<=1080]`.
+#[derive(Debug, Clone)]
+struct Filter {
+    /// The compared field.
+    field: Field,
+    /// The comparison of the field.
+    comparison: Comparison,
+    /// Whether the formats where the field is unknown are accepted, with a `?`.
+    optional: bool,
+}
+
+impl Filter {
+    /// Checks if a format matches the filter.
+    fn matches(&self, format: &Format) -> bool {
+        match &self.comparison {
+            Comparison::Number(operator, value) => match self.field.number(format) {
+                Some(number) => operator.compare(number, *value),
+                None => self.optional,
+            },
+            Comparison::Text {
+                operator,
+                negated,
+                value,
+                pattern,
+            } => match self.field.text(format) {
+                Some(text) => {
+                    let matches = match operator {
+                        TextOperator::Equal => text == *value,
+                        TextOperator::StartsWith => text.starts_with(value.as_str()),
+                        TextOperator::EndsWith => text.ends_with(value.as_str()),
+                        TextOperator::Contains => text.contains(value.as_str()),
+                        TextOperator::Matches => pattern
+                            .as_ref()
+                            .is_some_and(|pattern| pattern.is_match(&text)),
+                    };
+                    matches != *negated
+                }
+                None => self.optional,
+            },
+        }
+    }
+}
+
+/// The comparison of a filter.
+#[derive(Debug, Clone)]
+enum Comparison {
+    /// A comparison of a numeric field.
+    Number(NumberOperator, f64),
+    /// A comparison of a string field.
+    Text {
+        /// The operator.
+        operator: TextOperator,
+        /// Whether the result is negated, with a leading `!`.
+        negated: bool,
+        /// The compared value.
+        value: String,
+        /// The compiled value of the `~=` operator.
+        pattern: Option<Regex>,
+    },
+}
+
+/// The operators of numeric fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NumberOperator {
+    /// `<`
+    Less,
+    /// `<=`
+    LessOrEqual,
+    /// `>`
+    Greater,
+    /// `>=`
+    GreaterOrEqual,
+    /// `=`
+    Equal,
+    /// `!=`
+    NotEqual,
+}
+
+impl NumberOperator {
+    /// Compares the value of a field with the value of the filter.
+    fn compare(&self, number: f64, value: f64) -> bool {
+        match self {
+            NumberOperator::Less => number < value,
+            NumberOperator::LessOrEqual => number <= value,
+            NumberOperator::Greater => number > value,
+            NumberOperator::GreaterOrEqual => number >= value,
+            NumberOperator::Equal => number == value,
+            NumberOperator::NotEqual => number != value,
+        }
+    }
+}
+
+/// The operators of string fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TextOperator {
+    /// `=`
+    Equal,
+    /// `^=`
+    StartsWith,
+    /// `$=`
+    EndsWith,
+    /// `*=`
+    Contains,
+    /// `~=`
+    Matches,
+}
+
+/// The fields of a format that can be filtered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    /// 'format_id'.
+    FormatId,
+    /// 'format', the display name.
+    Format,
+    /// 'format_note'.
+    FormatNote,
+    /// 'ext'.
+    Extension,
+    /// 'container'.
+    Container,
+    /// 'protocol'.
+    Protocol,
+    /// 'language'.
+    Language,
+    /// 'vcodec'.
+    VideoCodec,
+    /// 'acodec'.
+    AudioCodec,
+    /// 'video_ext'.
+    VideoExtension,
+    /// 'audio_ext'.
+    AudioExtension,
+    /// 'resolution'.
+    Resolution,
+    /// 'dynamic_range'.
+    DynamicRange,
+    /// 'width'.
+    Width,
+    /// 'height'.
+    Height,
+    /// 'fps'.
+    Fps,
+    /// 'aspect_ratio'.
+    AspectRatio,
+    /// 'asr', the audio sample rate.
+    SampleRate,
+    /// 'audio_channels'.
+    AudioChannels,
+    /// 'vbr', the video bitrate.
+    VideoRate,
+    /// 'abr', the audio bitrate.
+    AudioRate,
+    /// 'tbr', the total bitrate.
+    TotalRate,
+    /// 'quality'.
+    Quality,
+    /// 'filesize'.
+    Filesize,
+    /// 'filesize_approx'.
+    FilesizeApprox,
+}
+
+impl Field {
+    /// Parses a field from its 'yt-dlp' name, e.g. 'vcodec'.
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "format_id" => Some(Field::FormatId),
+            "format" => Some(Field::Format),
+            "format_note" => Some(Field::FormatNote),
+            "ext" => Some(Field::Extension),
+            "container" => Some(Field::Container),
+            "protocol" => Some(Field::Protocol),
+            "language" => Some(Field::Language),
+            "vcodec" => Some(Field::VideoCodec),
+            "acodec" => Some(Field::AudioCodec),
+            "video_ext" => Some(Field::VideoExtension),
+            "audio_ext" => Some(Field::AudioExtension),
+            "resolution" => Some(Field::Resolution),
+            "dynamic_range" => Some(Field::DynamicRange),
+            "width" => Some(Field::Width),
+            "height" => Some(Field::Height),
+            "fps" => Some(Field::Fps),
+            "aspect_ratio" => Some(Field::AspectRatio),
+            "asr" => Some(Field::SampleRate),
+            "audio_channels" => Some(Field::AudioChannels),
+            "vbr" => Some(Field::VideoRate),
+            "abr" => Some(Field::AudioRate),
+            "tbr" => Some(Field::TotalRate),
+            "quality" => Some(Field::Quality),
+            "filesize" => Some(Field::Filesize),
+            "filesize_approx" => Some(Field::FilesizeApprox),
+            _ => None,
+        }
+    }
+
+    /// Checks if the field is compared as a number.
+    fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            Field::Width
+                | Field::Height
+                | Field::Fps
+                | Field::AspectRatio
+                | Field::SampleRate
+                | Field::AudioChannels
+                | Field::VideoRate
+                | Field::AudioRate
+                | Field::TotalRate
+                | Field::Quality
+                | Field::Filesize
+                | Field::FilesizeApprox
+        )
+    }
+
+    /// Returns the value of a numeric field, if known.
+    fn number(&self, format: &Format) -> Option<f64> {
+        match self {
+            Field::Width => format.video_resolution.width.map(f64::from),
+            Field::Height => format.video_resolution.height.map(f64::from),
+            Field::Fps => format.video_resolution.fps.map(|fps| fps.0),
+            Field::AspectRatio => format.video_resolution.aspect_ratio.map(|ratio| ratio.0),
+            Field::SampleRate => format.codec_info.asr.map(|asr| asr as f64),
+            Field::AudioChannels => format
+                .codec_info
+                .audio_channels
+                .map(|channels| channels as f64),
+            Field::VideoRate => format.rates_info.video_rate.map(|rate| rate.0),
+            Field::AudioRate => format.rates_info.audio_rate.map(|rate| rate.0),
+            Field::TotalRate => format.rates_info.total_rate.map(|rate| rate.0),
+            Field::Quality => format.quality_info.quality.map(|quality| quality.0),
+            Field::Filesize => format.file_info.filesize.map(|size| size as f64),
+            Field::FilesizeApprox => format.file_info.filesize_approx.map(|size| size as f64),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a string field, if known.
+    /// Enumerations are named as in the 'yt-dlp' output, e.g. 'mp4' or 'm3u8_native'.
+    fn text(&self, format: &Format) -> Option<String> {
+        match self {
+            Field::FormatId => Some(format.format_id.clone()),
+            Field::Format => Some(format.format.clone()),
+            Field::FormatNote => format.format_note.clone(),
+            Field::Extension => serde_name(&format.download_info.ext),
+            Field::Container => format.container.as_ref().and_then(serde_name),
+            Field::Protocol => serde_name(&format.protocol),
+            Field::Language => format.language.clone(),
+            // 'yt-dlp' names a missing codec 'none', e.g. `[vcodec=none]` for audio-only formats
+            Field::VideoCodec => Some(
+                format
+                    .codec_info
+                    .video_codec
+                    .clone()
+                    .unwrap_or_else(|| "none".to_string()),
+            ),
+            Field::AudioCodec => Some(
+                format
+                    .codec_info
+                    .audio_codec
+                    .clone()
+                    .unwrap_or_else(|| "none".to_string()),
+            ),
+            Field::VideoExtension => serde_name(&format.codec_info.video_ext),
+            Field::AudioExtension => serde_name(&format.codec_info.audio_ext),
+            Field::Resolution => format.video_resolution.resolution.clone(),
+            Field::DynamicRange => format
+                .quality_info
+                .dynamic_range
+                .as_ref()
+                .and_then(serde_name),
+            _ => None,
+        }
+    }
+}
+
+/// A recursive descent parser of selectors.
+struct Parser {
+    /// The characters of the selector.
+    chars: Vec<char>,
+    /// The position of the next character.
+    position: usize,
+}
+
+impl Parser {
+    /// Creates a new parser of the given selector.
+    fn new(selector: &str) -> Self {
+        Self {
+            chars: selector.chars().collect(),
+            position: 0,
+        }
+    }
+
+    /// Parses alternatives separated by `/`.
+    fn parse_alternatives(&mut self) -> Result<Selector> {
+        let mut selectors = vec![self.parse_merge()?];
+        while self.consume('/') {
+            selectors.push(self.parse_merge()?);
+        }
+
+        Ok(match selectors.len() {
+            1 => selectors.remove(0),
+            _ => Selector::Alternatives(selectors),
+        })
+    }
+
+    /// Parses selectors merged with `+`.
+    fn parse_merge(&mut self) -> Result<Selector> {
+        let mut selectors = vec![self.parse_single()?];
+        while self.consume('+') {
+            selectors.push(self.parse_single()?);
+        }
+
+        Ok(match selectors.len() {
+            1 => selectors.remove(0),
+            _ => Selector::Merge(selectors),
+        })
+    }
+
+    /// Parses a keyword, a format ID or a group, followed by its filters.
+    fn parse_single(&mut self) -> Result<Selector> {
+        self.skip_whitespace();
+
+        if self.consume('(') {
+            let selector = self.parse_alternatives()?;
+            if !self.consume(')') {
+                return Err(self.error("expected ')'"));
+            }
+            let filters = self.parse_filters()?;
+
+            return Ok(Selector::Group {
+                selector: Box::new(selector),
+                filters,
+            });
+        }
+
+        let name = self.take_while(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if name.is_empty() {
+            return Err(match self.peek() {
+                Some(c) => self.error(format!("expected a format, found '{}'", c)),
+                None => self.error("expected a format"),
+            });
+        }
+        let any = self.consume('*');
+
+        let keyword = match name.as_str() {
+            "b" | "best" => Some((true, Kind::Both)),
+            "w" | "worst" => Some((false, Kind::Both)),
+            "bv" | "bestvideo" => Some((true, Kind::Video)),
+            "wv" | "worstvideo" => Some((false, Kind::Video)),
+            "ba" | "bestaudio" => Some((true, Kind::Audio)),
+            "wa" | "worstaudio" => Some((false, Kind::Audio)),
+            _ => None,
+        };
+        let base = match keyword {
+            Some((best, kind)) => Base::Keyword { best, kind, any },
+            None if any => {
+                return Err(self.error(format!("'*' cannot follow the format ID '{}'", name)));
+            }
+            None => Base::FormatId(name),
+        };
+        let filters = self.parse_filters()?;
+
+        Ok(Selector::Single { base, filters })
+    }
+
+    /// Parses the filters following a format or a group.
+    fn parse_filters(&mut self) -> Result<Vec<Filter>> {
+        let mut filters = Vec::new();
+        while self.peek() == Some('[') {
+            self.position += 1;
+            filters.push(self.parse_filter()?);
+        }
+
+        Ok(filters)
+    }
+
+    /// Parses a filter, after its opening bracket.
+    fn parse_filter(&mut self) -> Result<Filter> {
+        self.skip_whitespace();
+        let name = self.take_while(|c| c.is_alphanumeric() || c == '_');
+        let field = Field::from_name(&name)
+            .ok_or_else(|| self.error(format!("unknown field '{}'", name)))?;
+        self.skip_whitespace();
+
+        let negated = self.consume('!');
+        let operator = self.take_while(|c| matches!(c, '<' | '>' | '=' | '^' | '$' | '*' | '~'));
+        if operator.is_empty() {
+            return Err(self.error(format!("expected an operator after '{}'", name)));
+        }
+        let optional = self.consume('?');
+        self.skip_whitespace();
+        let value = self.parse_value()?;
+
+        let comparison = match (field.is_numeric(), operator.as_str()) {
+            (true, _) => {
+                let operator = match (negated, operator.as_str()) {
+                    (false, "<") => NumberOperator::Less,
+                    (false, "<=") => NumberOperator::LessOrEqual,
+                    (false, ">") => NumberOperator::Greater,
+                    (false, ">=") => NumberOperator::GreaterOrEqual,
+                    (false, "=") => NumberOperator::Equal,
+                    (true, "=") => NumberOperator::NotEqual,
+                    _ => return Err(self.operator_error(&name, negated, &operator)),
+                };
+                let value = parse_number(&value)
+                    .ok_or_else(|| self.error(format!("'{}' is not a number", value)))?;
+                Comparison::Number(operator, value)
+            }
+            (false, "=") => self.text_comparison(TextOperator::Equal, negated, value)?,
+            (false, "^=") => self.text_comparison(TextOperator::StartsWith, negated, value)?,
+            (false, "$=") => self.text_comparison(TextOperator::EndsWith, negated, value)?,
+            (false, "*=") => self.text_comparison(TextOperator::Contains, negated, value)?,
+            (false, "~=") => self.text_comparison(TextOperator::Matches, negated, value)?,
+            (false, _) => return Err(self.operator_error(&name, negated, &operator)),
+        };
+
+        Ok(Filter {
+            field,
+            comparison,
+            optional,
+        })
+    }
+
+    /// Builds the comparison of a string field, compiling the regex of the `~=` operator.
+    fn text_comparison(
+        &self,
+        operator: TextOperator,
+        negated: bool,
+        value: String,
+    ) -> Result<Comparison> {
+        let pattern = match operator {
+            TextOperator::Matches => Some(
+                Regex::new(&value)
+                    .map_err(|e| self.error(format!("invalid regex '{}': {}", value, e)))?,
+            ),
+            _ => None,
+        };
+
+        Ok(Comparison::Text {
+            operator,
+            negated,
+            value,
+            pattern,
+        })
+    }
+
+    /// Parses the value of a filter and its closing bracket.
+    /// Values can be quoted with `'` or `"`, e.g. to contain a `]`.
+    fn parse_value(&mut self) -> Result<String> {
+        let value = match self.peek() {
+            Some(quote @ ('\'' | '"')) => {
+                self.position += 1;
+                let value = self.take_while(|c| c != quote);
+                if !self.consume(quote) {
+                    return Err(self.error(format!("expected {}", quote)));
+                }
+                value
+            }
+            _ => self.take_while(|c| c != ']').trim_end().to_string(),
+        };
+
+        if !self.consume(']') {
+            return Err(self.error("expected ']'"));
+        }
+        if value.is_empty() {
+            return Err(self.error("expected a value"));
+        }
+
+        Ok(value)
+    }
+
+    /// Returns the next character, without consuming it.
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    /// Consumes the next character if it is the expected one, after whitespace.
+    fn consume(&mut self, expected: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            return true;
+        }
+
+        false
+    }
+
+    /// Consumes the characters while they match the predicate, and returns them.
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let start = self.position;
+        while self.peek().is_some_and(&predicate) {
+            self.position += 1;
+        }
+
+        self.chars[start..self.position].iter().collect()
+    }
+
+    /// Skips the whitespace.
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    /// Builds an error at the current position.
+    fn error(&self, message: impl fmt::Display) -> Error {
+        Error::FormatSelector(format!("{} at position {}", message, self.position))
+    }
+
+    /// Builds the error of an operator that does not apply to its field.
+    fn operator_error(&self, field: &str, negated: bool, operator: &str) -> Error {
+        let negation = if negated { "!" } else { "" };
+        self.error(format!(
+            "invalid operator '{}{}' for field '{}'",
+            negation, operator, field
+        ))
+    }
+}
+
+/// Parses a number, with an optional decimal or binary suffix, e.g. '50M' or '1.5Gi'.
+pub(crate) fn parse_number(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let value = value.strip_suffix(['B', 'b']).unwrap_or(value);
+    let (value, base) = match value.strip_suffix('i') {
+        Some(value) => (value, 1024_f64),
+        None => (value, 1000_f64),
+    };
+    let (value, exponent) = match value.chars().last() {
+        Some('k' | 'K') => (&value[..value.len() - 1], 1),
+        Some('m' | 'M') => (&value[..value.len() - 1], 2),
+        Some('g' | 'G') => (&value[..value.len() - 1], 3),
+        Some('t' | 'T') => (&value[..value.len() - 1], 4),
+        _ if base == 1024.0 => return None,
+        _ => (value, 0),
+    };
+
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite())
+        .map(|number| number * base.powi(exponent))
+}
+
+/// Returns the 'yt-dlp' name of an enumeration, or `None` if unknown.
+fn serde_name(value: &impl Serialize) -> Option<String> {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) if !name.eq_ignore_ascii_case("unknown") => Some(name),
+        _ => None,
+    }
+}
+
+// Implementation of the Display trait for FormatSelector
+impl fmt::Display for FormatSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FormatSelector(selector = \"{}\")", self.selector)
+    }
+}
+
+// Implementation of PartialEq for FormatSelector
+impl PartialEq for FormatSelector {
+    fn eq(&self, other: &Self) -> bool {
+        self.selector == other.selector
+    }
+}
+
+// Implementation of Eq for FormatSelector
+impl Eq for FormatSelector {}
+
+// Implementation of Hash for FormatSelector
+impl Hash for FormatSelector {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.selector.hash(state);
+    }
+}
@@ -0,0 +1,429 @@
+//! Format sort orders, to rank the formats of a video with the sort expressions of 'yt-dlp'.
+//!
+//! A sort order is a list of fields separated by commas, from the most to the least important,
+//! e.g. `res:1080,fps,hdr:sdr,vcodec:avc1,size,br`. By default, greater values are preferred,
+//! and a leading `+` prefers smaller values instead, e.g. `+size` for the smallest files.
+//!
+//! A field followed by `:value` prefers the values up to this limit, and then the closest ones above it,
+//! e.g. `res:1080` for the highest resolution up to 1080p, or `+res:720` for the lowest from 720p.
+//! A field followed by `~value` prefers the values closest to it, e.g. `size~500M`.
+//! Codecs, extensions, dynamic ranges and protocols are ranked in a fixed order, and are limited the same way,
+//! e.g. `vcodec:avc1` prefers H.264 over VP9 and AV1.
+//!
+//! Formats with an unknown value for a field are always ranked last for this field.
+//! Formats that are equal on every field are then ranked with the default order of `Video`.
+
+use crate::error::{Error, Result};
+use crate::model::format::{DynamicRange, Extension, Format, Protocol};
+use crate::model::selector::parse_number;
+use ordered_float::OrderedFloat;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// The fields that can be used in a sort order.
+pub const FIELDS: &[&str] = &[
+    "quality",
+    "res",
+    "height",
+    "width",
+    "fps",
+    "hdr",
+    "vcodec",
+    "acodec",
+    "ext",
+    "proto",
+    "size",
+    "filesize",
+    "fs_approx",
+    "br",
+    "tbr",
+    "vbr",
+    "abr",
+    "asr",
+    "channels",
+];
+
+/// A parsed format sort order.
+/// The default order is empty, and leaves the ranking to the default order of `Video`.
+#[derive(Debug, Clone, Default)]
+pub struct FormatSort {
+    /// The sort order, as given by the user.
+    spec: String,
+    /// The parsed fields, from the most to the least important.
+    fields: Vec<SortField>,
+}
+
+impl FormatSort {
+    /// Parses a new format sort order.
+    ///
+    /// # Arguments
+    ///
+    /// * `spec` - The sort order, e.g. `res:1080,fps,hdr:sdr,vcodec:avc1,size,br`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a field is unknown, or if a value is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use yt_dlp::model::sort::FormatSort;
+    /// let sort = FormatSort::new("res:1080,fps,hdr:sdr,vcodec:avc1,+size,br");
+    /// assert!(sort.is_ok());
+    ///
+    /// let sort = FormatSort::new("res:high");
+    /// assert!(sort.is_err());
+    /// ```
+    pub fn new(spec: impl Into<String>) -> Result<Self> {
+        let spec = spec.into();
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Parsing format sort: {}", spec);
+
+        let fields = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|field| !field.is_empty())
+            .map(SortField::parse)
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { spec, fields })
+    }
+
+    /// Returns the sort order, as given by the user.
+    pub fn spec(&self) -> &str {
+        &self.spec
+    }
+
+    /// Checks if the sort order has no field.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Compares two formats with the fields of the sort order.
+    /// The preferred format is the greater one, and formats are equal when the order is empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The first format.
+    /// * `b` - The second format.
+    pub fn compare(&self, a: &Format, b: &Format) -> Ordering {
+        self.fields
+            .iter()
+            .map(|field| field.score(a).cmp(&field.score(b)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+/// A field of a sort order, e.g. `+res:720`.
+#[derive(Debug, Clone, PartialEq)]
+struct SortField {
+    /// The ranked value.
+    key: SortKey,
+    /// Whether smaller values are preferred, with a leading `+`.
+    ascending: bool,
+    /// The preferred value, if any.
+    target: Option<Target>,
+}
+
+impl SortField {
+    /// Parses a field of a sort order.
+    fn parse(field: &str) -> Result<Self> {
+        let (ascending, field) = match field.strip_prefix('+') {
+            Some(field) => (true, field),
+            None => (false, field),
+        };
+
+        let (name, target) = match field.find([':', '~']) {
+            Some(index) => (
+                &field[..index],
+                Some((&field[index..index + 1], &field[index + 1..])),
+            ),
+            None => (field, None),
+        };
+        let key = SortKey::from_name(name.trim())
+            .ok_or_else(|| Error::FormatSort(format!("unknown field '{}'", name)))?;
+
+        let target = match target {
+            Some((operator, value)) => {
+                let value = key.parse_value(value.trim()).ok_or_else(|| {
+                    Error::FormatSort(format!("invalid value '{}' for field '{}'", value, name))
+                })?;
+                Some(match operator {
+                    ":" => Target::Limit(value),
+                    _ => Target::Closest(value),
+                })
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            key,
+            ascending,
+            target,
+        })
+    }
+
+    /// Scores a format for this field, the greater the better.
+    /// Formats within the limit of the field come before the others, and unknown values come last.
+    fn score(&self, format: &Format) -> Option<(bool, OrderedFloat<f64>)> {
+        let value = self.key.value(format)?;
+        let sign = if self.ascending { -1.0 } else { 1.0 };
+
+        Some(match self.target {
+            None => (true, OrderedFloat(sign * value)),
+            Some(Target::Limit(limit)) if sign * value <= sign * limit => {
+                (true, OrderedFloat(sign * value))
+            }
+            // Beyond the limit, the closest values are preferred
+            Some(Target::Limit(_)) => (false, OrderedFloat(-sign * value)),
+            Some(Target::Closest(target)) => (true, OrderedFloat(-(value - target).abs())),
+        })
+    }
+}
+
+/// The preferred value of a field.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Target {
+    /// The values up to this limit are preferred, with `:`.
+    Limit(f64),
+    /// The values closest to this one are preferred, with `~`.
+    Closest(f64),
+}
+
+/// The values that formats can be ranked by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortKey {
+    /// 'quality', the relative quality given by 'yt-dlp'.
+    Quality,
+    /// 'res', the smallest dimension of the video.
+    Resolution,
+    /// 'height'.
+    Height,
+    /// 'width'.
+    Width,
+    /// 'fps'.
+    Fps,
+    /// 'hdr', the dynamic range: HDR > SDR.
+    DynamicRange,
+    /// 'vcodec': AV1 > VP9 > H.265 > H.264 > VP8 > other.
+    VideoCodec,
+    /// 'acodec': FLAC/ALAC > Opus > Vorbis > AAC > MP3 > AC-3/DTS > other.
+    AudioCodec,
+    /// 'ext': MP4/M4A > WebM > other.
+    Extension,
+    /// 'proto', the protocol: HTTPS > HLS > other.
+    Protocol,
+    /// 'size', the exact or approximate file size.
+    Size,
+    /// 'filesize', the exact file size.
+    Filesize,
+    /// 'fs_approx', the approximate file size.
+    FilesizeApprox,
+    /// 'br', the total bitrate, or the sum of the video and audio bitrates.
+    Bitrate,
+    /// 'tbr', the total bitrate.
+    TotalRate,
+    /// 'vbr', the video bitrate.
+    VideoRate,
+    /// 'abr', the audio bitrate.
+    AudioRate,
+    /// 'asr', the audio sample rate.
+    SampleRate,
+    /// 'channels', the number of audio channels.
+    Channels,
+}
+
+impl SortKey {
+    /// Parses a key from its 'yt-dlp' name, e.g. 'res'.
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "quality" => Some(SortKey::Quality),
+            "res" => Some(SortKey::Resolution),
+            "height" => Some(SortKey::Height),
+            "width" => Some(SortKey::Width),
+            "fps" => Some(SortKey::Fps),
+            "hdr" => Some(SortKey::DynamicRange),
+            "vcodec" => Some(SortKey::VideoCodec),
+            "acodec" => Some(SortKey::AudioCodec),
+            "ext" => Some(SortKey::Extension),
+            "proto" => Some(SortKey::Protocol),
+            "size" => Some(SortKey::Size),
+            "filesize" => Some(SortKey::Filesize),
+            "fs_approx" => Some(SortKey::FilesizeApprox),
+            "br" => Some(SortKey::Bitrate),
+            "tbr" => Some(SortKey::TotalRate),
+            "vbr" => Some(SortKey::VideoRate),
+            "abr" => Some(SortKey::AudioRate),
+            "asr" => Some(SortKey::SampleRate),
+            "channels" => Some(SortKey::Channels),
+            _ => None,
+        }
+    }
+
+    /// Parses the preferred value of a field, as a number or as the name of a ranked value.
+    fn parse_value(&self, value: &str) -> Option<f64> {
+        let value = value.to_lowercase();
+        match self {
+            SortKey::DynamicRange => match value.as_str() {
+                "sdr" => Some(1.0),
+                "hdr" | "hdr10" | "hdr10+" | "hdr12" | "hlg" | "dv" => Some(2.0),
+                _ => None,
+            },
+            SortKey::VideoCodec => Some(video_codec_rank(&value)),
+            SortKey::AudioCodec => Some(audio_codec_rank(&value)),
+            SortKey::Extension => Some(match value.as_str() {
+                "mp4" | "m4a" => 2.0,
+                "webm" => 1.0,
+                _ => 0.0,
+            }),
+            SortKey::Protocol => Some(match value.as_str() {
+                "https" | "http" => 2.0,
+                "m3u8" | "m3u8_native" | "hls" => 1.0,
+                _ => 0.0,
+            }),
+            // Resolutions are often written with a trailing 'p', e.g. '1080p'
+            SortKey::Resolution | SortKey::Height => {
+                parse_number(value.strip_suffix('p').unwrap_or(&value))
+            }
+            _ => parse_number(&value),
+        }
+    }
+
+    /// Returns the ranked value of a format, if known.
+    fn value(&self, format: &Format) -> Option<f64> {
+        let resolution = &format.video_resolution;
+        let rates = &format.rates_info;
+
+        match self {
+            SortKey::Quality => format.quality_info.quality.map(|quality| quality.0),
+            SortKey::Resolution => match (resolution.width, resolution.height) {
+                (Some(width), Some(height)) => Some(f64::from(width.min(height))),
+                (width, height) => height.or(width).map(f64::from),
+            },
+            SortKey::Height => resolution.height.map(f64::from),
+            SortKey::Width => resolution.width.map(f64::from),
+            SortKey::Fps => resolution.fps.map(|fps| fps.0),
+            SortKey::DynamicRange => match format.quality_info.dynamic_range {
+                Some(DynamicRange::HDR) => Some(2.0),
+                Some(DynamicRange::SDR) => Some(1.0),
+                _ => None,
+            },
+            SortKey::VideoCodec => format
+                .codec_info
+                .video_codec
+                .as_deref()
+                .map(|codec| video_codec_rank(&codec.to_lowercase())),
+            SortKey::AudioCodec => format
+                .codec_info
+                .audio_codec
+                .as_deref()
+                .map(|codec| audio_codec_rank(&codec.to_lowercase())),
+            SortKey::Extension => match format.download_info.ext {
+                Extension::Mp4 | Extension::M4A => Some(2.0),
+                Extension::Webm => Some(1.0),
+                Extension::Unknown => None,
+                _ => Some(0.0),
+            },
+            SortKey::Protocol => match format.protocol {
+                Protocol::Https => Some(2.0),
+                Protocol::M3U8Native | Protocol::M3U8 | Protocol::HttpDashSegments => Some(1.0),
+                _ => Some(0.0),
+            },
+            SortKey::Size => format
+                .file_info
+                .filesize
+                .or(format.file_info.filesize_approx)
+                .map(|size| size as f64),
+            SortKey::Filesize => format.file_info.filesize.map(|size| size as f64),
+            SortKey::FilesizeApprox => format.file_info.filesize_approx.map(|size| size as f64),
+            SortKey::Bitrate => rates.total_rate.map(|rate| rate.0).or_else(|| {
+                match (rates.video_rate, rates.audio_rate) {
+                    (None, None) => None,
+                    (video, audio) => {
+                        Some(video.map_or(0.0, |rate| rate.0) + audio.map_or(0.0, |rate| rate.0))
+                    }
+                }
+            }),
+            SortKey::TotalRate => rates.total_rate.map(|rate| rate.0),
+            SortKey::VideoRate => rates.video_rate.map(|rate| rate.0),
+            SortKey::AudioRate => rates.audio_rate.map(|rate| rate.0),
+            SortKey::SampleRate => format.codec_info.asr.map(|asr| asr as f64),
+            SortKey::Channels => format
+                .codec_info
+                .audio_channels
+                .map(|channels| channels as f64),
+        }
+    }
+}
+
+/// Ranks a video codec name, e.g. 'avc1.640028', the greater the better.
+fn video_codec_rank(codec: &str) -> f64 {
+    if codec.starts_with("av01") || codec.starts_with("av1") {
+        5.0
+    } else if codec.starts_with("vp09") || codec.starts_with("vp9") {
+        4.0
+    } else if ["hvc1", "hev1", "h265", "hevc"]
+        .iter()
+        .any(|prefix| codec.starts_with(prefix))
+    {
+        3.0
+    } else if ["avc", "h264", "h.264"]
+        .iter()
+        .any(|prefix| codec.starts_with(prefix))
+    {
+        2.0
+    } else if codec.starts_with("vp8") {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// Ranks an audio codec name, e.g. 'mp4a.40.2', the greater the better.
+fn audio_codec_rank(codec: &str) -> f64 {
+    if codec.starts_with("flac") || codec.starts_with("alac") {
+        6.0
+    } else if codec.starts_with("opus") {
+        5.0
+    } else if codec.starts_with("vorbis") {
+        4.0
+    } else if codec.starts_with("mp4a") || codec.starts_with("aac") {
+        3.0
+    } else if codec.starts_with("mp3") {
+        2.0
+    } else if ["ac-3", "ac3", "ec-3", "eac3", "dts"]
+        .iter()
+        .any(|prefix| codec.starts_with(prefix))
+    {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+// Implementation of the Display trait for FormatSort
+impl fmt::Display for FormatSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FormatSort(spec = \"{}\")", self.spec)
+    }
+}
+
+// Implementation of PartialEq for FormatSort
+impl PartialEq for FormatSort {
+    fn eq(&self, other: &Self) -> bool {
+        self.spec == other.spec
+    }
+}
+
+// Implementation of Eq for FormatSort
+impl Eq for FormatSort {}
+
+// Implementation of Hash for FormatSort
+impl Hash for FormatSort {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.spec.hash(state);
+    }
+}
@@ -14,3 +14,14 @@ where
         _ => Ok(string),
     }
 }
+
+/// Deserializes a 'null' value as the default value of the type, e.g. an empty list.
+pub fn json_null_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    let value: Option<T> = Option::deserialize(deserializer)?;
+
+    Ok(value.unwrap_or_default())
+}
@@ -0,0 +1,381 @@
+//! Extraction and transcoding of audio files.
+
+use crate::error::{Error, Result};
+use crate::executor::Executor;
+use crate::metadata::{BaseMetadata, MetadataManager};
+use crate::model::Video;
+use crate::postprocessor::path_arg;
+use crate::{Youtube, utils};
+use std::fmt;
+use std::fmt::Display;
+use std::path::PathBuf;
+
+/// The target integrated loudness of the EBU R128 recommendation, in LUFS.
+pub const EBU_R128_LOUDNESS: f64 = -23.0;
+
+/// The sample rate used after loudness normalization when none is given, in Hz.
+/// The loudness filter upsamples the audio, so the output must be resampled.
+const NORMALIZED_SAMPLE_RATE: u32 = 48000;
+
+/// The audio codecs that files can be converted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AudioCodec {
+    /// MP3, with the LAME encoder.
+    Mp3,
+    /// AAC in an M4A container.
+    M4a,
+    /// Opus in an Ogg container.
+    Opus,
+    /// FLAC, lossless.
+    Flac,
+}
+
+impl AudioCodec {
+    /// Parses a codec from its name or extension, e.g. 'mp3', 'm4a', 'aac', 'opus' or 'flac'.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the codec.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use yt_dlp::postprocessor::audio::AudioCodec;
+    /// assert_eq!(AudioCodec::from_name("aac"), Some(AudioCodec::M4a));
+    /// assert_eq!(AudioCodec::from_name("wav"), None);
+    /// ```
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "mp3" => Some(AudioCodec::Mp3),
+            "m4a" | "aac" => Some(AudioCodec::M4a),
+            "opus" => Some(AudioCodec::Opus),
+            "flac" => Some(AudioCodec::Flac),
+            _ => None,
+        }
+    }
+
+    /// Returns the extension of the files of this codec, without the dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AudioCodec::Mp3 => "mp3",
+            AudioCodec::M4a => "m4a",
+            AudioCodec::Opus => "opus",
+            AudioCodec::Flac => "flac",
+        }
+    }
+
+    /// Returns the name of the FFmpeg encoder of this codec.
+    fn encoder(&self) -> &'static str {
+        match self {
+            AudioCodec::Mp3 => "libmp3lame",
+            AudioCodec::M4a => "aac",
+            AudioCodec::Opus => "libopus",
+            AudioCodec::Flac => "flac",
+        }
+    }
+
+    /// Checks if a codec name reported by 'yt-dlp' is this codec, e.g. 'mp4a.40.2' for AAC.
+    fn matches(&self, codec: &str) -> bool {
+        let codec = codec.to_lowercase();
+        match self {
+            AudioCodec::Mp3 => codec == "mp3",
+            AudioCodec::M4a => codec.starts_with("mp4a") || codec == "aac",
+            AudioCodec::Opus => codec == "opus",
+            AudioCodec::Flac => codec == "flac",
+        }
+    }
+
+    /// Returns the bitrate matching a variable quality, in kbit/s, for the encoders without VBR.
+    fn vbr_bitrate(&self, quality: u8) -> u32 {
+        const AAC: [u32; 10] = [320, 256, 224, 192, 160, 128, 112, 96, 80, 64];
+        const OPUS: [u32; 10] = [256, 192, 160, 128, 112, 96, 80, 64, 48, 32];
+
+        let index = usize::from(quality.min(9));
+        match self {
+            AudioCodec::Opus => OPUS[index],
+            _ => AAC[index],
+        }
+    }
+}
+
+/// The bitrate of a converted audio file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AudioBitrate {
+    /// A constant bitrate, in kbit/s, e.g. 192.
+    Constant(u32),
+    /// A variable quality, from 0 (best) to 9 (worst), as used by LAME.
+    /// Encoders without a VBR mode use a matching average bitrate.
+    Variable(u8),
+}
+
+/// The options of an audio conversion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioConversion {
+    /// The target codec.
+    codec: AudioCodec,
+    /// The target bitrate, the encoder default when `None`.
+    bitrate: Option<AudioBitrate>,
+    /// The target sample rate in Hz, the input sample rate when `None`.
+    sample_rate: Option<u32>,
+    /// The target integrated loudness in LUFS, not normalized when `None`.
+    loudness: Option<f64>,
+    /// The codec of the input, e.g. 'opus' or 'mp4a.40.2', if known.
+    source_codec: Option<String>,
+}
+
+impl AudioConversion {
+    /// Creates a new conversion to the given codec, with the encoder defaults.
+    ///
+    /// # Arguments
+    ///
+    /// * `codec` - The target codec.
+    pub fn new(codec: AudioCodec) -> Self {
+        Self {
+            codec,
+            bitrate: None,
+            sample_rate: None,
+            loudness: None,
+            source_codec: None,
+        }
+    }
+
+    /// Sets the target bitrate. It is ignored for FLAC, which is lossless.
+    ///
+    /// # Arguments
+    ///
+    /// * `bitrate` - The constant bitrate or the variable quality.
+    pub fn with_bitrate(mut self, bitrate: AudioBitrate) -> Self {
+        self.bitrate = Some(bitrate);
+        self
+    }
+
+    /// Sets the target sample rate.
+    ///
+    /// # Arguments
+    ///
+    /// * `sample_rate` - The sample rate in Hz, e.g. 44100 or 48000.
+    pub fn with_sample_rate(mut self, sample_rate: u32) -> Self {
+        self.sample_rate = Some(sample_rate);
+        self
+    }
+
+    /// Normalizes the loudness, with the EBU R128 loudness filter of FFmpeg.
+    ///
+    /// # Arguments
+    ///
+    /// * `loudness` - The target integrated loudness in LUFS, e.g. `EBU_R128_LOUDNESS`.
+    pub fn with_loudness_normalization(mut self, loudness: f64) -> Self {
+        self.loudness = Some(loudness);
+        self
+    }
+
+    /// Sets the codec of the input. When it is already the target codec and nothing
+    /// has to be re-encoded, the audio stream is copied instead of being transcoded.
+    ///
+    /// # Arguments
+    ///
+    /// * `codec` - The codec of the input, e.g. `format.codec_info.audio_codec`.
+    pub fn with_source_codec(mut self, codec: impl AsRef<str>) -> Self {
+        self.source_codec = Some(codec.as_ref().to_string());
+        self
+    }
+
+    /// Returns the target codec.
+    pub fn codec(&self) -> AudioCodec {
+        self.codec
+    }
+
+    /// Checks if the audio stream can be copied without being transcoded.
+    fn can_copy(&self) -> bool {
+        self.bitrate.is_none()
+            && self.sample_rate.is_none()
+            && self.loudness.is_none()
+            && self
+                .source_codec
+                .as_deref()
+                .is_some_and(|codec| self.codec.matches(codec))
+    }
+
+    /// Builds the FFmpeg arguments of the audio stream.
+    fn codec_args(&self) -> Vec<String> {
+        if self.can_copy() {
+            return utils::to_owned(vec!["-c:a", "copy"]);
+        }
+
+        let mut args = utils::to_owned(vec!["-c:a", self.codec.encoder()]);
+        match (self.codec, self.bitrate) {
+            (AudioCodec::Flac, _) | (_, None) => {}
+            (_, Some(AudioBitrate::Constant(kbps))) => {
+                args.push("-b:a".to_string());
+                args.push(format!("{}k", kbps));
+            }
+            (AudioCodec::Mp3, Some(AudioBitrate::Variable(quality))) => {
+                args.push("-q:a".to_string());
+                args.push(quality.min(9).to_string());
+            }
+            (codec, Some(AudioBitrate::Variable(quality))) => {
+                args.push("-b:a".to_string());
+                args.push(format!("{}k", codec.vbr_bitrate(quality)));
+            }
+        }
+
+        if let Some(loudness) = self.loudness {
+            args.push("-af".to_string());
+            args.push(format!("loudnorm=I={}:TP=-1.5:LRA=11", loudness));
+        }
+
+        let sample_rate = match (self.sample_rate, self.loudness) {
+            (Some(sample_rate), _) => Some(sample_rate),
+            (None, Some(_)) => Some(NORMALIZED_SAMPLE_RATE),
+            (None, None) => None,
+        };
+        if let Some(sample_rate) = sample_rate {
+            args.push("-ar".to_string());
+            args.push(sample_rate.to_string());
+        }
+
+        args
+    }
+}
+
+impl Youtube {
+    /// Extracts the audio of a downloaded file, converts it, and tags the result.
+    /// The input can be in any container, whatever its extension, and is removed afterwards.
+    /// The extension of the output is replaced by the one of the target codec, so that
+    /// it always matches the content. MP3 and M4A files are then tagged by `MetadataManager`,
+    /// while Opus and FLAC files are tagged by FFmpeg during the conversion.
+    ///
+    /// # Arguments
+    ///
+    /// * `input_file` - The name of the downloaded file, in the output directory.
+    /// * `output_file` - The name of the converted file, in the output directory.
+    /// * `video` - The video the file was downloaded from, to tag the converted file.
+    /// * `conversion` - The conversion options.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if FFmpeg fails, or if the tags could not be written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use yt_dlp::postprocessor::audio::{AudioBitrate, AudioCodec, AudioConversion, EBU_R128_LOUDNESS};
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url.clone()).await?;
+    /// fetcher.download_audio_stream_from_url(url, "my-audio.webm").await?;
+    ///
+    /// let conversion = AudioConversion::new(AudioCodec::Mp3)
+    ///     .with_bitrate(AudioBitrate::Variable(2))
+    ///     .with_loudness_normalization(EBU_R128_LOUDNESS);
+    /// let audio_path = fetcher.convert_audio("my-audio.webm", "my-audio.mp3", &video, &conversion).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn convert_audio(
+        &self,
+        input_file: impl AsRef<str> + std::fmt::Debug + Display,
+        output_file: impl AsRef<str> + std::fmt::Debug + Display,
+        video: &Video,
+        conversion: &AudioConversion,
+    ) -> Result<PathBuf> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            "Converting audio of {} into {} ({})",
+            input_file,
+            output_file,
+            conversion.codec
+        );
+
+        let input_path = self.output_dir.join(input_file.as_ref());
+        let extension = conversion.codec.extension();
+        let output_path = self
+            .output_dir
+            .join(output_file.as_ref())
+            .with_extension(extension);
+
+        // FFmpeg cannot write over its input
+        let temp_path = output_path.with_extension(format!("converting.{}", extension));
+
+        let mut args = utils::to_owned(vec!["-y", "-i"]);
+        args.push(path_arg(&input_path)?);
+        args.extend(utils::to_owned(vec!["-map", "0:a:0", "-vn", "-sn", "-dn"]));
+        args.extend(conversion.codec_args());
+
+        // Opus and FLAC files are tagged here, as `MetadataManager` needs FFmpeg in the PATH for them
+        args.extend(utils::to_owned(vec!["-map_metadata", "-1"]));
+        if matches!(conversion.codec, AudioCodec::Opus | AudioCodec::Flac) {
+            for (key, value) in MetadataManager::extract_basic_metadata(video) {
+                args.push("-metadata".to_string());
+                args.push(format!("{}={}", key, value));
+            }
+        }
+        args.push(path_arg(&temp_path)?);
+
+        let executor = Executor {
+            executable_path: self.libraries.ffmpeg.clone(),
+            timeout: self.timeout,
+            args,
+            env: Vec::new(),
+        };
+
+        if let Err(e) = executor.execute().await {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            return Err(e);
+        }
+
+        if input_path != output_path {
+            tokio::fs::remove_file(&input_path).await?;
+        }
+        tokio::fs::rename(&temp_path, &output_path).await?;
+
+        if matches!(conversion.codec, AudioCodec::Mp3 | AudioCodec::M4a) {
+            MetadataManager::add_metadata(&output_path, video)
+                .await
+                .map_err(|e| Error::Unknown(format!("Failed to tag converted audio: {}", e)))?;
+        }
+
+        Ok(output_path)
+    }
+}
+
+// Implementation of the Display trait for AudioCodec
+impl fmt::Display for AudioCodec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+// Implementation of the Display trait for AudioBitrate
+impl fmt::Display for AudioBitrate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AudioBitrate::Constant(kbps) => write!(f, "{}k", kbps),
+            AudioBitrate::Variable(quality) => write!(f, "V{}", quality),
+        }
+    }
+}
+
+// Implementation of the Display trait for AudioConversion
+impl fmt::Display for AudioConversion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "AudioConversion(codec={}, bitrate={})",
+            self.codec,
+            self.bitrate
+                .map_or_else(|| "default".to_string(), |bitrate| bitrate.to_string())
+        )
+    }
+}
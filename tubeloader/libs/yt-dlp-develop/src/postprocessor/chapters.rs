@@ -0,0 +1,143 @@
+//! Splitting of downloaded files by chapters.
+
+use crate::error::{Error, Result};
+use crate::executor::Executor;
+use crate::model::Video;
+use crate::postprocessor::path_arg;
+use crate::utils::template::OutputTemplate;
+use crate::{Youtube, utils};
+use std::fmt::Display;
+use std::path::PathBuf;
+
+impl Youtube {
+    /// Splits a downloaded file into one file per chapter of the video.
+    /// The streams are copied without re-encoding, so each cut starts on the keyframe
+    /// preceding the chapter start. The original file is kept.
+    ///
+    /// The files are named from the template, relative to the output directory,
+    /// with the `section_*` fields replaced by the chapter information.
+    /// Chapters with an empty duration are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `video_file` - The name of the downloaded file, in the output directory.
+    /// * `video` - The video the file was downloaded from, with its chapters.
+    /// * `template` - The template of the chapter files, e.g. `DEFAULT_CHAPTER_TEMPLATE`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the video has no chapters,
+    /// if a chapter file would overwrite the downloaded file, or if FFmpeg fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use yt_dlp::utils::template::{OutputTemplate, DEFAULT_CHAPTER_TEMPLATE};
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url.clone()).await?;
+    /// fetcher.download_video_from_url(url, "my-video.mp4").await?;
+    ///
+    /// let template = OutputTemplate::new(DEFAULT_CHAPTER_TEMPLATE)?;
+    /// let chapter_paths = fetcher.split_by_chapters("my-video.mp4", &video, &template).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn split_by_chapters(
+        &self,
+        video_file: impl AsRef<str> + std::fmt::Debug + Display,
+        video: &Video,
+        template: &OutputTemplate,
+    ) -> Result<Vec<PathBuf>> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            "Splitting {} into {} chapters",
+            video_file,
+            video.chapters.len()
+        );
+
+        if video.chapters.is_empty() {
+            return Err(Error::MissingChapters(video.id.clone()));
+        }
+
+        let video_path = self.output_dir.join(video_file.as_ref());
+        let extension = video_path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("mp4")
+            .to_string();
+
+        let mut chapter_paths = Vec::new();
+        for (index, chapter) in video.chapters.iter().enumerate() {
+            let duration = chapter.duration();
+            if duration <= 0.0 {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("Skipping empty chapter: {}", chapter);
+                continue;
+            }
+
+            let chapter_path = self.output_dir.join(template.render_chapter(
+                video,
+                chapter,
+                index + 1,
+                &extension,
+            ));
+            if chapter_path == video_path {
+                return Err(Error::Path(format!(
+                    "Chapter file would overwrite {}",
+                    video_path.display()
+                )));
+            }
+            if let Some(parent) = chapter_path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+
+            let start = format!("{:.3}", chapter.start_time.0.max(0.0));
+            let duration = format!("{:.3}", duration);
+            let title = format!("title={}", chapter.title);
+
+            let mut args = utils::to_owned(vec!["-y", "-ss", &start, "-t", &duration, "-i"]);
+            args.push(path_arg(&video_path)?);
+            args.extend(utils::to_owned(vec![
+                "-map",
+                "0",
+                "-dn",
+                "-ignore_unknown",
+                "-c",
+                "copy",
+                "-map_chapters",
+                "-1",
+                "-metadata",
+                &title,
+            ]));
+            args.push(path_arg(&chapter_path)?);
+
+            let executor = Executor {
+                executable_path: self.libraries.ffmpeg.clone(),
+                timeout: self.timeout,
+                args,
+                env: Vec::new(),
+            };
+
+            if let Err(e) = executor.execute().await {
+                let _ = tokio::fs::remove_file(&chapter_path).await;
+                return Err(e);
+            }
+
+            chapter_paths.push(chapter_path);
+        }
+
+        Ok(chapter_paths)
+    }
+}
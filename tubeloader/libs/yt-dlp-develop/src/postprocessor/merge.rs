@@ -0,0 +1,412 @@
+//! Merging of video and audio streams into a container, with codec compatibility checks.
+//!
+//! Each container only accepts some codecs, e.g. MP4 files with VP9 video or Opus audio
+//! cannot be played by older players. The streams whose codec is accepted by the container
+//! are copied as they are, and only the other ones are transcoded to the default codec of the container.
+
+use crate::model::format::Format;
+use crate::utils;
+use std::fmt;
+use std::path::Path;
+
+/// The containers that video and audio streams can be merged into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MergeContainer {
+    /// MP4, with H.264, H.265 or AV1 video, and AAC, MP3, AC-3 or ALAC audio.
+    #[default]
+    Mp4,
+    /// Matroska, accepting every codec.
+    Mkv,
+    /// WebM, with VP8, VP9 or AV1 video, and Opus or Vorbis audio.
+    Webm,
+    /// QuickTime, with H.264 or H.265 video, and AAC, MP3, AC-3 or ALAC audio.
+    Mov,
+}
+
+impl MergeContainer {
+    /// Parses a container from its name or extension, e.g. 'mp4', 'mkv', 'webm' or 'mov'.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the container.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use yt_dlp::postprocessor::merge::MergeContainer;
+    /// assert_eq!(MergeContainer::from_name("MKV"), Some(MergeContainer::Mkv));
+    /// assert_eq!(MergeContainer::from_name("avi"), None);
+    /// ```
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "mp4" | "m4v" => Some(MergeContainer::Mp4),
+            "mkv" | "matroska" => Some(MergeContainer::Mkv),
+            "webm" => Some(MergeContainer::Webm),
+            "mov" => Some(MergeContainer::Mov),
+            _ => None,
+        }
+    }
+
+    /// Finds the container of a file from its extension, MP4 when the extension is unknown.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the merged file.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        path.as_ref()
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(Self::from_name)
+            .unwrap_or_default()
+    }
+
+    /// Returns the extension of the files of this container, without the dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            MergeContainer::Mp4 => "mp4",
+            MergeContainer::Mkv => "mkv",
+            MergeContainer::Webm => "webm",
+            MergeContainer::Mov => "mov",
+        }
+    }
+
+    /// Checks if the container accepts a video codec reported by 'yt-dlp', e.g. 'vp09.00.40.08'.
+    ///
+    /// # Arguments
+    ///
+    /// * `codec` - The video codec.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use yt_dlp::postprocessor::merge::MergeContainer;
+    /// assert!(MergeContainer::Mp4.supports_video_codec("avc1.640028"));
+    /// assert!(!MergeContainer::Mp4.supports_video_codec("vp9"));
+    /// assert!(MergeContainer::Mkv.supports_video_codec("vp9"));
+    /// ```
+    pub fn supports_video_codec(&self, codec: &str) -> bool {
+        let family = VideoFamily::from_codec(codec);
+        match self {
+            MergeContainer::Mkv => true,
+            MergeContainer::Mp4 => matches!(
+                family,
+                Some(VideoFamily::H264 | VideoFamily::H265 | VideoFamily::Av1)
+            ),
+            MergeContainer::Mov => matches!(family, Some(VideoFamily::H264 | VideoFamily::H265)),
+            MergeContainer::Webm => matches!(
+                family,
+                Some(VideoFamily::Vp8 | VideoFamily::Vp9 | VideoFamily::Av1)
+            ),
+        }
+    }
+
+    /// Checks if the container accepts an audio codec reported by 'yt-dlp', e.g. 'mp4a.40.2'.
+    ///
+    /// # Arguments
+    ///
+    /// * `codec` - The audio codec.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use yt_dlp::postprocessor::merge::MergeContainer;
+    /// assert!(MergeContainer::Mp4.supports_audio_codec("mp4a.40.2"));
+    /// assert!(!MergeContainer::Mp4.supports_audio_codec("opus"));
+    /// assert!(MergeContainer::Webm.supports_audio_codec("opus"));
+    /// ```
+    pub fn supports_audio_codec(&self, codec: &str) -> bool {
+        let family = AudioFamily::from_codec(codec);
+        match self {
+            MergeContainer::Mkv => true,
+            MergeContainer::Mp4 | MergeContainer::Mov => matches!(
+                family,
+                Some(AudioFamily::Aac | AudioFamily::Mp3 | AudioFamily::Ac3 | AudioFamily::Alac)
+            ),
+            MergeContainer::Webm => {
+                matches!(family, Some(AudioFamily::Opus | AudioFamily::Vorbis))
+            }
+        }
+    }
+
+    /// Returns the FFmpeg encoder of an incompatible video stream for this container.
+    fn video_encoder(&self) -> &'static str {
+        match self {
+            MergeContainer::Mp4 | MergeContainer::Mov | MergeContainer::Mkv => "libx264",
+            MergeContainer::Webm => "libvpx-vp9",
+        }
+    }
+
+    /// Returns the FFmpeg options of the video encoder of this container.
+    fn video_encoder_options(&self) -> Vec<String> {
+        match self {
+            // 8-bit 4:2:0, for the players which cannot decode 10-bit H.264
+            MergeContainer::Mp4 | MergeContainer::Mov | MergeContainer::Mkv => {
+                utils::to_owned(vec!["-preset", "veryfast", "-pix_fmt", "yuv420p"])
+            }
+            // Constant quality, as the default bitrate of libvpx is very low
+            MergeContainer::Webm => utils::to_owned(vec!["-crf", "32", "-b:v", "0"]),
+        }
+    }
+
+    /// Returns the FFmpeg encoder of an incompatible audio stream for this container.
+    fn audio_encoder(&self) -> &'static str {
+        match self {
+            MergeContainer::Mp4 | MergeContainer::Mov | MergeContainer::Mkv => "aac",
+            MergeContainer::Webm => "libopus",
+        }
+    }
+}
+
+/// How a stream is written into the merged file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum StreamHandling {
+    /// The stream is copied as it is.
+    Copy,
+    /// The stream is transcoded, as its codec is not accepted by the container.
+    Transcode {
+        /// The codec of the stream, if known.
+        from: Option<String>,
+        /// The FFmpeg encoder used, e.g. 'aac'.
+        encoder: &'static str,
+    },
+}
+
+impl StreamHandling {
+    /// Checks if the stream is copied without being transcoded.
+    pub fn is_copy(&self) -> bool {
+        matches!(self, StreamHandling::Copy)
+    }
+}
+
+/// The way video and audio streams are merged into a container, from their codecs.
+///
+/// Streams accepted by the container are copied, and the others are transcoded.
+/// When a codec is unknown, the video stream is copied and the audio stream is transcoded,
+/// except in MKV files which accept every codec.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MergePlan {
+    /// The container of the merged file.
+    pub container: MergeContainer,
+    /// How the video stream is written.
+    pub video: StreamHandling,
+    /// How each audio stream is written, in the order of the inputs.
+    pub audio: Vec<StreamHandling>,
+}
+
+impl MergePlan {
+    /// Plans the merge of a video stream and audio streams, from their codecs.
+    ///
+    /// # Arguments
+    ///
+    /// * `container` - The container of the merged file.
+    /// * `video_codec` - The codec of the video stream, if known, e.g. 'vp9'.
+    /// * `audio_codecs` - The codecs of the audio streams, if known, e.g. 'opus'.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use yt_dlp::postprocessor::merge::{MergeContainer, MergePlan};
+    /// let plan = MergePlan::new(MergeContainer::Mp4, Some("avc1.640028"), &[Some("opus")]);
+    /// assert!(plan.video.is_copy());
+    /// assert!(!plan.audio[0].is_copy());
+    ///
+    /// let plan = MergePlan::new(MergeContainer::Mkv, Some("vp9"), &[Some("opus")]);
+    /// assert!(plan.is_stream_copy());
+    /// ```
+    pub fn new(
+        container: MergeContainer,
+        video_codec: Option<&str>,
+        audio_codecs: &[Option<&str>],
+    ) -> Self {
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            "Planning merge into {:?} of video {:?} and audio {:?}",
+            container,
+            video_codec,
+            audio_codecs
+        );
+
+        let video = match video_codec {
+            Some(codec) if !container.supports_video_codec(codec) => StreamHandling::Transcode {
+                from: Some(codec.to_string()),
+                encoder: container.video_encoder(),
+            },
+            _ => StreamHandling::Copy,
+        };
+
+        let audio = audio_codecs
+            .iter()
+            .map(|codec| match codec {
+                Some(codec) if container.supports_audio_codec(codec) => StreamHandling::Copy,
+                None if container == MergeContainer::Mkv => StreamHandling::Copy,
+                codec => StreamHandling::Transcode {
+                    from: codec.map(String::from),
+                    encoder: container.audio_encoder(),
+                },
+            })
+            .collect();
+
+        Self {
+            container,
+            video,
+            audio,
+        }
+    }
+
+    /// Plans the merge of the streams of a video format and audio formats.
+    ///
+    /// # Arguments
+    ///
+    /// * `container` - The container of the merged file.
+    /// * `video_format` - The format of the video stream.
+    /// * `audio_formats` - The formats of the audio streams.
+    pub fn for_formats(
+        container: MergeContainer,
+        video_format: &Format,
+        audio_formats: &[&Format],
+    ) -> Self {
+        let audio_codecs: Vec<Option<&str>> = audio_formats
+            .iter()
+            .map(|format| format.codec_info.audio_codec.as_deref())
+            .collect();
+
+        Self::new(
+            container,
+            video_format.codec_info.video_codec.as_deref(),
+            &audio_codecs,
+        )
+    }
+
+    /// Checks if every stream is copied, without any transcoding.
+    pub fn is_stream_copy(&self) -> bool {
+        self.video.is_copy() && self.audio.iter().all(StreamHandling::is_copy)
+    }
+
+    /// Builds the FFmpeg codec arguments of the merged streams.
+    pub(crate) fn codec_args(&self) -> Vec<String> {
+        let mut args = vec!["-c:v".to_string()];
+        match &self.video {
+            StreamHandling::Copy => args.push("copy".to_string()),
+            StreamHandling::Transcode { encoder, .. } => {
+                args.push(encoder.to_string());
+                args.extend(self.container.video_encoder_options());
+            }
+        }
+
+        for (index, handling) in self.audio.iter().enumerate() {
+            args.push(format!("-c:a:{}", index));
+            args.push(match handling {
+                StreamHandling::Copy => "copy".to_string(),
+                StreamHandling::Transcode { encoder, .. } => encoder.to_string(),
+            });
+        }
+
+        args
+    }
+}
+
+// Implementation of the Display trait for MergeContainer
+impl fmt::Display for MergeContainer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+// Implementation of the Display trait for StreamHandling
+impl fmt::Display for StreamHandling {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamHandling::Copy => write!(f, "copy"),
+            StreamHandling::Transcode { from, encoder } => write!(
+                f,
+                "transcode({} -> {})",
+                from.as_deref().unwrap_or("unknown"),
+                encoder
+            ),
+        }
+    }
+}
+
+// Implementation of the Display trait for MergePlan
+impl fmt::Display for MergePlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let audio: Vec<String> = self.audio.iter().map(ToString::to_string).collect();
+        write!(
+            f,
+            "MergePlan(container = {}, video = {}, audio = [{}])",
+            self.container,
+            self.video,
+            audio.join(", ")
+        )
+    }
+}
+
+/// The video codecs that containers are checked against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VideoFamily {
+    H264,
+    H265,
+    Vp8,
+    Vp9,
+    Av1,
+}
+
+impl VideoFamily {
+    /// Finds the family of a video codec reported by 'yt-dlp'.
+    fn from_codec(codec: &str) -> Option<Self> {
+        let codec = codec.to_lowercase();
+        let starts_with = |prefixes: &[&str]| prefixes.iter().any(|p| codec.starts_with(p));
+
+        if starts_with(&["avc1", "avc3", "h264"]) {
+            Some(VideoFamily::H264)
+        } else if starts_with(&["hvc1", "hev1", "h265", "hevc"]) {
+            Some(VideoFamily::H265)
+        } else if starts_with(&["vp8"]) {
+            Some(VideoFamily::Vp8)
+        } else if starts_with(&["vp09", "vp9"]) {
+            Some(VideoFamily::Vp9)
+        } else if starts_with(&["av01", "av1"]) {
+            Some(VideoFamily::Av1)
+        } else {
+            None
+        }
+    }
+}
+
+/// The audio codecs that containers are checked against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AudioFamily {
+    Aac,
+    Mp3,
+    Ac3,
+    Alac,
+    Opus,
+    Vorbis,
+    Flac,
+}
+
+impl AudioFamily {
+    /// Finds the family of an audio codec reported by 'yt-dlp'.
+    fn from_codec(codec: &str) -> Option<Self> {
+        let codec = codec.to_lowercase();
+        let starts_with = |prefixes: &[&str]| prefixes.iter().any(|p| codec.starts_with(p));
+
+        if starts_with(&["mp4a", "aac"]) {
+            Some(AudioFamily::Aac)
+        } else if starts_with(&["mp3"]) {
+            Some(AudioFamily::Mp3)
+        } else if starts_with(&["ac-3", "ac3", "ec-3", "eac3"]) {
+            Some(AudioFamily::Ac3)
+        } else if starts_with(&["alac"]) {
+            Some(AudioFamily::Alac)
+        } else if starts_with(&["opus"]) {
+            Some(AudioFamily::Opus)
+        } else if starts_with(&["vorbis"]) {
+            Some(AudioFamily::Vorbis)
+        } else if starts_with(&["flac"]) {
+            Some(AudioFamily::Flac)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,31 @@
+//! Post-processing of downloaded files.
+//!
+//! This module is subdivided into several modules, each responsible for a specific operation
+//! run with FFmpeg once a video has been downloaded, such as converting its audio,
+//! merging its streams into a container, splitting it by chapters or removing its
+//! SponsorBlock segments.
+
+use crate::error::{Error, Result};
+use std::path::Path;
+use std::time::Duration;
+
+pub mod audio;
+pub mod chapters;
+pub mod merge;
+pub mod sponsorblock;
+
+/// The size copied per second assumed by the timeout of a remux, far below the speed of a stream copy.
+const REMUX_BYTES_PER_SECOND: u64 = 4 * 1024 * 1024;
+
+/// Converts a path to an FFmpeg argument.
+pub(crate) fn path_arg(path: &Path) -> Result<String> {
+    path.to_str()
+        .map(String::from)
+        .ok_or(Error::Path(format!("Invalid path: {}", path.display())))
+}
+
+/// Returns the timeout of a stream copy of a file of the given size, extending the base timeout
+/// by one second per 4 MiB, so that long recordings are not killed while being remuxed.
+pub(crate) fn remux_timeout(base: Duration, size: u64) -> Duration {
+    base.saturating_add(Duration::from_secs(size / REMUX_BYTES_PER_SECOND))
+}
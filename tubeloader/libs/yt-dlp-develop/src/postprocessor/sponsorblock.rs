@@ -0,0 +1,456 @@
+//! Marking or removal of SponsorBlock segments in downloaded files.
+
+use crate::error::Result;
+use crate::executor::Executor;
+use crate::fetcher::sponsorblock::SegmentSource;
+use crate::metadata::MetadataManager;
+use crate::model::Video;
+use crate::model::chapter::Chapter;
+use crate::model::segment::{ActionType, SegmentCategory, SponsorSegment};
+use crate::postprocessor::path_arg;
+use crate::{Youtube, utils};
+use ordered_float::OrderedFloat;
+use std::fmt;
+use std::fmt::Display;
+use std::path::PathBuf;
+
+/// The shortest duration of a chapter or a kept part, in seconds.
+const MIN_DURATION: f64 = 0.001;
+
+/// What to do with the SponsorBlock segments of a downloaded file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SponsorMode {
+    /// Writes the segments as chapters, e.g. '[SponsorBlock]: Sponsor'.
+    Mark,
+    /// Cuts the segments out of the file.
+    Remove,
+}
+
+impl Youtube {
+    /// Fetches the SponsorBlock segments of a downloaded video, and marks them as chapters
+    /// or cuts them out of the file. This is meant to run once the audio and video streams
+    /// have been combined, e.g. after `download_video_from_url`.
+    /// Only the segments to skip are applied, and the file is left untouched when there are none.
+    ///
+    /// # Arguments
+    ///
+    /// * `video_file` - The name of the downloaded file, in the output directory.
+    /// * `video` - The video the file was downloaded from.
+    /// * `source` - The source of the segments, e.g. `SponsorBlockClient::new()`.
+    /// * `categories` - The categories of the segments, all the skippable ones if empty.
+    /// * `mode` - Whether to mark or to remove the segments.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the segments could not be fetched, or if FFmpeg fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use yt_dlp::fetcher::sponsorblock::SponsorBlockClient;
+    /// # use yt_dlp::model::segment::SegmentCategory;
+    /// # use yt_dlp::postprocessor::sponsorblock::SponsorMode;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url.clone()).await?;
+    /// fetcher.download_video_from_url(url, "my-video.mp4").await?;
+    ///
+    /// let categories = [SegmentCategory::Sponsor, SegmentCategory::Intro];
+    /// let segments = fetcher
+    ///     .apply_sponsor_segments("my-video.mp4", &video, &SponsorBlockClient::new(), &categories, SponsorMode::Remove)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn apply_sponsor_segments(
+        &self,
+        video_file: impl AsRef<str> + std::fmt::Debug + Display,
+        video: &Video,
+        source: &impl SegmentSource,
+        categories: &[SegmentCategory],
+        mode: SponsorMode,
+    ) -> Result<Vec<SponsorSegment>> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            "Applying SponsorBlock segments to {} ({})",
+            video_file,
+            mode
+        );
+
+        let segments: Vec<SponsorSegment> = source
+            .fetch_segments(&video.id, categories)
+            .await?
+            .into_iter()
+            .filter(|segment| segment.action_type == ActionType::Skip)
+            .filter(|segment| segment.end_time() - segment.start_time() > MIN_DURATION)
+            .collect();
+
+        if segments.is_empty() {
+            return Ok(segments);
+        }
+
+        match mode {
+            SponsorMode::Mark => self.mark_segments(video_file, video, &segments).await?,
+            SponsorMode::Remove => self.remove_segments(video_file, video, &segments).await?,
+        };
+
+        Ok(segments)
+    }
+
+    /// Writes the segments as chapters of a downloaded file, merged with the chapters of the video.
+    ///
+    /// # Arguments
+    ///
+    /// * `video_file` - The name of the downloaded file, in the output directory.
+    /// * `video` - The video the file was downloaded from.
+    /// * `segments` - The segments to mark.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the chapters could not be written.
+    pub async fn mark_segments(
+        &self,
+        video_file: impl AsRef<str> + std::fmt::Debug + Display,
+        video: &Video,
+        segments: &[SponsorSegment],
+    ) -> Result<PathBuf> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Marking {} segments in {}", segments.len(), video_file);
+
+        let video_path = self.output_dir.join(video_file.as_ref());
+        let chapters = sponsor_chapters(video, segments, SponsorMode::Mark);
+        MetadataManager::add_chapters(&video_path, &chapters).await?;
+
+        Ok(video_path)
+    }
+
+    /// Cuts the segments out of a downloaded file, with the FFmpeg concat demuxer.
+    /// The streams are copied without re-encoding, so each kept part starts on the keyframe
+    /// preceding its start. The chapters of the video are shifted accordingly.
+    ///
+    /// # Arguments
+    ///
+    /// * `video_file` - The name of the downloaded file, in the output directory.
+    /// * `video` - The video the file was downloaded from.
+    /// * `segments` - The segments to remove.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if FFmpeg fails, or if the chapters could not be written.
+    pub async fn remove_segments(
+        &self,
+        video_file: impl AsRef<str> + std::fmt::Debug + Display,
+        video: &Video,
+        segments: &[SponsorSegment],
+    ) -> Result<PathBuf> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Removing {} segments from {}", segments.len(), video_file);
+
+        let video_path = self.output_dir.join(video_file.as_ref());
+        let kept = kept_ranges(&merge_ranges(segments), video_duration(video, segments));
+        if kept.is_empty() {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("The segments cover the whole video, nothing is removed");
+            return Ok(video_path);
+        }
+
+        let file_name = video_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+            .replace('\'', "'\\''");
+        let mut list = String::from("ffconcat version 1.0\n");
+        for (start, end) in &kept {
+            list.push_str(&format!("file '{}'\ninpoint {:.3}\n", file_name, start));
+            if let Some(end) = end {
+                list.push_str(&format!("outpoint {:.3}\n", end));
+            }
+        }
+
+        // The paths of the list are relative to the list itself
+        let list_path = video_path.with_extension("ffconcat");
+        let container = video_path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("mp4")
+            .to_string();
+        let temp_path = video_path.with_extension(format!("sponsorblock.{}", container));
+        tokio::fs::write(&list_path, list).await?;
+
+        let mut args = utils::to_owned(vec!["-y", "-f", "concat", "-safe", "0", "-i"]);
+        args.push(path_arg(&list_path)?);
+        args.push("-i".to_string());
+        args.push(path_arg(&video_path)?);
+        args.extend(utils::to_owned(vec![
+            "-map",
+            "0",
+            "-dn",
+            "-ignore_unknown",
+            "-map_metadata",
+            "1",
+            "-map_chapters",
+            "-1",
+            "-c",
+            "copy",
+        ]));
+        args.push(path_arg(&temp_path)?);
+
+        let executor = Executor {
+            executable_path: self.libraries.ffmpeg.clone(),
+            timeout: self.timeout,
+            args,
+            env: Vec::new(),
+        };
+
+        let result = executor.execute().await;
+        let _ = tokio::fs::remove_file(&list_path).await;
+        if let Err(e) = result {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            return Err(e);
+        }
+
+        tokio::fs::rename(&temp_path, &video_path).await?;
+
+        let chapters = sponsor_chapters(video, segments, SponsorMode::Remove);
+        MetadataManager::add_chapters(&video_path, &chapters).await?;
+
+        Ok(video_path)
+    }
+}
+
+/// Returns the chapters of a downloaded file once the segments have been applied:
+/// the chapters of the video merged with the segments when marking them,
+/// or the chapters of the video shifted over the removed parts when removing them.
+///
+/// # Arguments
+///
+/// * `video` - The video the file was downloaded from, with its chapters.
+/// * `segments` - The applied segments.
+/// * `mode` - Whether the segments were marked or removed.
+pub fn sponsor_chapters(
+    video: &Video,
+    segments: &[SponsorSegment],
+    mode: SponsorMode,
+) -> Vec<Chapter> {
+    match mode {
+        SponsorMode::Mark => {
+            let duration = video_duration(video, segments)
+                .or_else(|| {
+                    segments
+                        .iter()
+                        .map(SponsorSegment::end_time)
+                        .reduce(f64::max)
+                })
+                .unwrap_or_default();
+            segments_to_chapters(&video.chapters, segments, duration)
+        }
+        SponsorMode::Remove => cut_chapters(&video.chapters, segments),
+    }
+}
+
+/// Merges segments into chapters, named after their category.
+/// The chapters keep their title outside of the segments, and the parts of the video
+/// that are neither in a chapter nor in a segment get an empty title.
+///
+/// # Arguments
+///
+/// * `chapters` - The chapters of the video.
+/// * `segments` - The segments to mark.
+/// * `duration` - The duration of the video, in seconds.
+///
+/// # Examples
+///
+/// ```rust
+/// # use yt_dlp::model::chapter::Chapter;
+/// # use yt_dlp::model::segment::{SegmentCategory, SponsorSegment};
+/// # use yt_dlp::postprocessor::sponsorblock::segments_to_chapters;
+/// let chapters = [Chapter::new(0.0, 60.0, "Intro"), Chapter::new(60.0, 120.0, "Talk")];
+/// let segments = [SponsorSegment::new(SegmentCategory::Sponsor, 50.0, 70.0)];
+///
+/// let titles: Vec<String> = segments_to_chapters(&chapters, &segments, 120.0)
+///     .into_iter()
+///     .map(|chapter| chapter.title)
+///     .collect();
+/// assert_eq!(titles, ["Intro", "[SponsorBlock]: Sponsor", "Talk"]);
+/// ```
+pub fn segments_to_chapters(
+    chapters: &[Chapter],
+    segments: &[SponsorSegment],
+    duration: f64,
+) -> Vec<Chapter> {
+    let mut bounds = vec![0.0, duration];
+    bounds.extend(
+        chapters
+            .iter()
+            .flat_map(|chapter| [chapter.start_time.0, chapter.end_time.0]),
+    );
+    bounds.extend(
+        segments
+            .iter()
+            .flat_map(|segment| [segment.start_time(), segment.end_time()]),
+    );
+    bounds.retain(|bound| bound.is_finite() && (0.0..=duration).contains(bound));
+    bounds.sort_by(f64::total_cmp);
+
+    let mut merged: Vec<Chapter> = Vec::new();
+    for window in bounds.windows(2) {
+        let (start, end) = (window[0], window[1]);
+        if end - start < MIN_DURATION {
+            continue;
+        }
+
+        let middle = (start + end) / 2.0;
+        let covers = |start: f64, end: f64| start <= middle && middle < end;
+
+        // The shortest segment is the most specific when segments overlap
+        let title = segments
+            .iter()
+            .filter(|segment| covers(segment.start_time(), segment.end_time()))
+            .min_by(|a, b| {
+                (a.end_time() - a.start_time()).total_cmp(&(b.end_time() - b.start_time()))
+            })
+            .map(|segment| format!("[SponsorBlock]: {}", segment.category.title()))
+            .or_else(|| {
+                chapters
+                    .iter()
+                    .find(|chapter| covers(chapter.start_time.0, chapter.end_time.0))
+                    .map(|chapter| chapter.title.clone())
+            })
+            .unwrap_or_default();
+
+        match merged.last_mut() {
+            Some(last) if last.title == title => last.end_time = OrderedFloat(end),
+            _ => merged.push(Chapter::new(start, end, title)),
+        }
+    }
+
+    merged
+}
+
+/// Shifts chapters over the removed segments. Chapters inside a removed segment are dropped.
+///
+/// # Arguments
+///
+/// * `chapters` - The chapters of the video.
+/// * `segments` - The removed segments.
+///
+/// # Examples
+///
+/// ```rust
+/// # use yt_dlp::model::chapter::Chapter;
+/// # use yt_dlp::model::segment::{SegmentCategory, SponsorSegment};
+/// # use yt_dlp::postprocessor::sponsorblock::cut_chapters;
+/// let chapters = [Chapter::new(0.0, 60.0, "Intro"), Chapter::new(60.0, 120.0, "Talk")];
+/// let segments = [SponsorSegment::new(SegmentCategory::Sponsor, 50.0, 70.0)];
+///
+/// let chapters = cut_chapters(&chapters, &segments);
+/// assert_eq!(chapters[1], Chapter::new(50.0, 100.0, "Talk"));
+/// ```
+pub fn cut_chapters(chapters: &[Chapter], segments: &[SponsorSegment]) -> Vec<Chapter> {
+    let removed = merge_ranges(segments);
+    let shift = |time: f64| {
+        time - removed
+            .iter()
+            .map(|(start, end)| (time.min(*end) - start).max(0.0))
+            .sum::<f64>()
+    };
+
+    chapters
+        .iter()
+        .map(|chapter| {
+            Chapter::new(
+                shift(chapter.start_time.0),
+                shift(chapter.end_time.0),
+                &chapter.title,
+            )
+        })
+        .filter(|chapter| chapter.duration() >= MIN_DURATION)
+        .collect()
+}
+
+/// Returns the time ranges of the segments, sorted and without overlaps.
+fn merge_ranges(segments: &[SponsorSegment]) -> Vec<(f64, f64)> {
+    let mut ranges: Vec<(f64, f64)> = segments
+        .iter()
+        .map(|segment| (segment.start_time().max(0.0), segment.end_time()))
+        .filter(|(start, end)| end - start >= MIN_DURATION)
+        .collect();
+    ranges.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut merged: Vec<(f64, f64)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    merged
+}
+
+/// Returns the time ranges to keep between the removed ranges.
+/// The last range is open when the duration of the video is unknown.
+fn kept_ranges(removed: &[(f64, f64)], duration: Option<f64>) -> Vec<(f64, Option<f64>)> {
+    let mut kept = Vec::new();
+    let mut start = 0.0;
+
+    for (removed_start, removed_end) in removed {
+        if removed_start - start >= MIN_DURATION {
+            kept.push((start, Some(*removed_start)));
+        }
+        start = *removed_end;
+    }
+
+    match duration {
+        Some(duration) if duration - start >= MIN_DURATION => kept.push((start, None)),
+        Some(_) => {}
+        None => kept.push((start, None)),
+    }
+
+    kept
+}
+
+/// Returns the duration of the video, from its information or from the segments.
+fn video_duration(video: &Video, segments: &[SponsorSegment]) -> Option<f64> {
+    let known = |duration: &f64| *duration > 0.0;
+
+    video
+        .duration
+        .map(|duration| duration.0)
+        .filter(known)
+        .or_else(|| {
+            segments
+                .iter()
+                .map(|segment| segment.video_duration.0)
+                .reduce(f64::max)
+                .filter(known)
+        })
+        .or_else(|| {
+            video
+                .chapters
+                .last()
+                .map(|chapter| chapter.end_time.0)
+                .filter(known)
+        })
+}
+
+// Implementation of the Display trait for SponsorMode
+impl fmt::Display for SponsorMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SponsorMode::Mark => write!(f, "mark"),
+            SponsorMode::Remove => write!(f, "remove"),
+        }
+    }
+}
@@ -0,0 +1,254 @@
+//! The bandwidth limits of the downloads.
+//!
+//! A `RateLimiter` is a token bucket shared by all the streams it throttles: the segments of a
+//! `Fetcher`, the fragments of a `ManifestFetcher`, and every download of a `DownloadManager`.
+//! A global limiter caps the bandwidth of all the downloads, and each download of the manager
+//! can be capped by its own limiter too. The limits can be changed while downloading.
+
+use crate::error::{Error, Result};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The longest wait before checking the bucket again, so that a new rate applies quickly.
+const MAX_WAIT: Duration = Duration::from_millis(250);
+
+/// A token bucket limiting the bandwidth, in bytes per second.
+/// Up to one second of bandwidth can be used in a burst, and a read larger than the
+/// available tokens is allowed, the next reads waiting until the debt is paid back.
+///
+/// # Examples
+///
+/// ```rust
+/// # use yt_dlp::rate_limit::RateLimiter;
+/// let limiter = RateLimiter::new(Some(5 * 1024 * 1024));
+/// assert_eq!(limiter.rate(), Some(5 * 1024 * 1024));
+/// assert_eq!(limiter.yt_dlp_args(), ["--limit-rate", "5242880"]);
+///
+/// limiter.set_rate(None);
+/// assert!(limiter.yt_dlp_args().is_empty());
+/// ```
+pub struct RateLimiter {
+    /// The state of the bucket, shared by the throttled streams.
+    bucket: Mutex<Bucket>,
+}
+
+/// The state of a token bucket.
+struct Bucket {
+    /// The rate, in bytes per second, or None if unlimited.
+    rate: Option<u64>,
+    /// The available bytes, negative when a read was larger than the available bytes.
+    tokens: f64,
+    /// The last time the bucket was refilled.
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    /// Creates a new limiter.
+    ///
+    /// # Arguments
+    ///
+    /// * `rate` - The rate, in bytes per second, or None (or zero) to not limit the bandwidth.
+    pub fn new(rate: Option<u64>) -> Self {
+        Self {
+            bucket: Mutex::new(Bucket {
+                rate: rate.filter(|&rate| rate > 0),
+                tokens: 0.0,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Creates a new limiter, without limiting the bandwidth.
+    pub fn unlimited() -> Self {
+        Self::new(None)
+    }
+
+    /// Returns the rate, in bytes per second, or None if unlimited.
+    pub fn rate(&self) -> Option<u64> {
+        self.lock().rate
+    }
+
+    /// Changes the rate, applied to the reads in progress too.
+    ///
+    /// # Arguments
+    ///
+    /// * `rate` - The rate, in bytes per second, or None (or zero) to not limit the bandwidth.
+    pub fn set_rate(&self, rate: Option<u64>) {
+        let mut bucket = self.lock();
+
+        bucket.refill();
+        bucket.rate = rate.filter(|&rate| rate > 0);
+        // The debt is kept, to be paid back at the new rate, but not the unused bandwidth
+        bucket.tokens = bucket.tokens.min(0.0);
+    }
+
+    /// Waits until the given number of bytes can be read.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The number of bytes read.
+    pub async fn acquire(&self, bytes: u64) {
+        loop {
+            let wait = {
+                let mut bucket = self.lock();
+                bucket.refill();
+
+                let Some(rate) = bucket.rate else {
+                    return;
+                };
+                if bucket.tokens >= 0.0 {
+                    bucket.tokens -= bytes as f64;
+                    return;
+                }
+
+                // The debt of the previous reads is paid back first
+                Duration::from_secs_f64(-bucket.tokens / rate as f64)
+            };
+
+            tokio::time::sleep(wait.min(MAX_WAIT)).await;
+        }
+    }
+
+    /// Returns the arguments giving the rate to 'yt-dlp', when it downloads a format itself.
+    pub fn yt_dlp_args(&self) -> Vec<String> {
+        match self.rate() {
+            Some(rate) => vec!["--limit-rate".to_string(), rate.to_string()],
+            None => Vec::new(),
+        }
+    }
+
+    /// Locks the bucket, even if a thread panicked while holding it.
+    fn lock(&self) -> std::sync::MutexGuard<'_, Bucket> {
+        self.bucket
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
+impl Bucket {
+    /// Adds the bytes allowed since the last refill, up to one second of bandwidth.
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.last_refill = now;
+
+        if let Some(rate) = self.rate {
+            let rate = rate as f64;
+            self.tokens = (self.tokens + elapsed * rate).min(rate);
+        }
+    }
+}
+
+// Implementation of the Debug trait for RateLimiter
+impl fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("rate", &self.rate())
+            .finish()
+    }
+}
+
+// Implementation of the Display trait for RateLimiter
+impl fmt::Display for RateLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.rate() {
+            Some(rate) => write!(f, "RateLimiter(rate = {} B/s)", rate),
+            None => write!(f, "RateLimiter(unlimited)"),
+        }
+    }
+}
+
+/// Waits until all the given limiters allow the given number of bytes to be read.
+///
+/// # Arguments
+///
+/// * `limiters` - The limiters of the stream, e.g. the global and the per-download ones.
+/// * `bytes` - The number of bytes read.
+pub(crate) async fn throttle(limiters: &[Arc<RateLimiter>], bytes: usize) {
+    for limiter in limiters {
+        limiter.acquire(bytes as u64).await;
+    }
+}
+
+/// Reads the body of a response, throttled by the given limiters.
+///
+/// # Arguments
+///
+/// * `response` - The response to read.
+/// * `limiters` - The limiters of the stream.
+///
+/// # Errors
+///
+/// This function will return an error if the body could not be read.
+pub(crate) async fn read_body(
+    mut response: reqwest::Response,
+    limiters: &[Arc<RateLimiter>],
+) -> Result<Vec<u8>> {
+    if limiters.is_empty() {
+        return Ok(response.bytes().await?.to_vec());
+    }
+
+    let mut data = Vec::with_capacity(response.content_length().unwrap_or(0) as usize);
+
+    while let Some(chunk) = response.chunk().await? {
+        throttle(limiters, chunk.len()).await;
+        data.extend_from_slice(&chunk);
+    }
+
+    Ok(data)
+}
+
+/// Parses a rate like the '--limit-rate' option of 'yt-dlp', e.g. '50K' or '4.2M'.
+/// The suffixes 'K', 'M', 'G' and 'T' are powers of 1024, and can be followed by 'B' or 'iB'.
+///
+/// # Arguments
+///
+/// * `rate` - The rate, in bytes per second.
+///
+/// # Errors
+///
+/// This function will return an error if the rate is not a positive number of bytes.
+///
+/// # Examples
+///
+/// ```rust
+/// # use yt_dlp::rate_limit::parse_rate;
+/// assert_eq!(parse_rate("5M").unwrap(), 5 * 1024 * 1024);
+/// assert_eq!(parse_rate("1.5k").unwrap(), 1536);
+/// assert_eq!(parse_rate("800").unwrap(), 800);
+/// assert!(parse_rate("fast").is_err());
+/// ```
+pub fn parse_rate(rate: impl AsRef<str>) -> Result<u64> {
+    let rate = rate.as_ref().trim();
+    let invalid = || Error::RateLimit(format!("'{}' is not a number of bytes", rate));
+
+    let value = rate
+        .strip_suffix("iB")
+        .or_else(|| rate.strip_suffix(['B', 'b']))
+        .unwrap_or(rate);
+
+    let (number, multiplier) = match value.chars().last().map(|unit| unit.to_ascii_uppercase()) {
+        Some('K') => (&value[..value.len() - 1], 1u64 << 10),
+        Some('M') => (&value[..value.len() - 1], 1u64 << 20),
+        Some('G') => (&value[..value.len() - 1], 1u64 << 30),
+        Some('T') => (&value[..value.len() - 1], 1u64 << 40),
+        _ => (value, 1),
+    };
+
+    let number: f64 = number.trim().parse().map_err(|_| invalid())?;
+    if !number.is_finite() || number <= 0.0 {
+        return Err(Error::RateLimit(format!(
+            "'{}' is not a positive rate",
+            rate
+        )));
+    }
+
+    Ok(((number * multiplier as f64) as u64).max(1))
+}
@@ -202,8 +202,30 @@ pub fn random_filename(length: usize) -> String {
 }
 
 /// Extracts a potential video ID from a filename.
+///
+/// # Examples
+///
+/// ```rust
+/// # use yt_dlp::utils::file_system::extract_video_id;
+/// // The ID between brackets of the default output template, "{title} [{id}].{ext}"
+/// assert_eq!(
+///     extract_video_id("Documentary [dQw4w9WgXcQ].mp4").as_deref(),
+///     Some("dQw4w9WgXcQ")
+/// );
+/// assert_eq!(extract_video_id("video-dQw4w9WgXcQ.mp4").as_deref(), Some("dQw4w9WgXcQ"));
+/// ```
 pub fn extract_video_id(filename: &str) -> Option<String> {
-    // Pattern 1: filename contains "video-[ID]" or "audio-[ID]"
+    // Pattern 1: filename contains "[ID]", as written by the default output template
+    if let Some(captures) = regex::Regex::new(r"\[([a-zA-Z0-9_-]{11})\]")
+        .ok()?
+        .captures(filename)
+    {
+        if let Some(id) = captures.get(1) {
+            return Some(id.as_str().to_string());
+        }
+    }
+
+    // Pattern 2: filename contains "video-[ID]" or "audio-[ID]"
     if let Some(captures) = regex::Regex::new(r"(?:video|audio)-([a-zA-Z0-9_-]{11})")
         .ok()?
         .captures(filename)
@@ -213,7 +235,7 @@ pub fn extract_video_id(filename: &str) -> Option<String> {
         }
     }
 
-    // Pattern 2: filename contains "[ID].mp4" or "[ID].mp3", etc.
+    // Pattern 3: filename ends with "ID.mp4" or "ID.mp3", etc.
     if let Some(captures) = regex::Regex::new(r"([a-zA-Z0-9_-]{11})\.[a-zA-Z0-9]+$")
         .ok()?
         .captures(filename)
@@ -223,7 +245,7 @@ pub fn extract_video_id(filename: &str) -> Option<String> {
         }
     }
 
-    // Pattern 3: if the name directly contains a YouTube ID (11 characters)
+    // Pattern 4: if the name directly contains a YouTube ID (11 characters)
     if let Some(captures) = regex::Regex::new(r"[a-zA-Z0-9_-]{11}")
         .ok()?
         .captures(filename)
@@ -1,6 +1,6 @@
 //! Utility functions and types used throughout the application.
 //!
-//! This module contains various utility to interact with the shell, and the file system.
+//! This module contains various utility to interact with the shell, the file system, and output templates.
 
 use crate::error::Result;
 use platform::Platform;
@@ -8,6 +8,8 @@ use tokio::task::JoinHandle;
 
 pub mod file_system;
 pub mod platform;
+pub mod template;
+pub(crate) mod xml;
 
 /// Converts a vector of string slices to a vector of owned strings.
 pub fn to_owned(vec: Vec<impl AsRef<str>>) -> Vec<String> {
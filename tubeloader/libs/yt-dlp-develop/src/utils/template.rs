@@ -0,0 +1,546 @@
+//! Output filename templates, to build download paths from the video information.
+//!
+//! A template is a path where fields between braces are replaced by the video information,
+//! e.g. `{channel}/{upload_date:%Y-%m}/{title} [{id}].{ext}`.
+//! Slashes in the template create subdirectories, while slashes in the fields are replaced.
+//! Literal braces are written `{{` and `}}`.
+//!
+//! The `section_*` fields describe a chapter, and are only available when rendering
+//! the files of a video split by chapters, e.g. `{title}/{section_number:02} {section_title}.{ext}`.
+
+use crate::error::{Error, Result};
+use crate::model::Video;
+use crate::model::chapter::Chapter;
+use chrono::DateTime;
+use chrono::format::{Item, StrftimeItems};
+use std::fmt;
+use std::path::PathBuf;
+
+/// The default maximum length of each path component, in bytes.
+/// Most file systems limit names to 255 bytes, some room is kept for temporary suffixes.
+pub const DEFAULT_MAX_LENGTH: usize = 200;
+
+/// The default template of the files, as used by 'yt-dlp'.
+/// The ID keeps the names of videos with the same title apart.
+pub const DEFAULT_TEMPLATE: &str = "{title} [{id}].{ext}";
+
+/// The default template of the files of a video split by chapters, as used by 'yt-dlp'.
+pub const DEFAULT_CHAPTER_TEMPLATE: &str =
+    "{title} - {section_number:03} {section_title} [{id}].{ext}";
+
+/// The default format of the `upload_date` field, as used by 'yt-dlp'.
+const DEFAULT_DATE_FORMAT: &str = "%Y%m%d";
+
+/// The fields that can be used in a template.
+pub const FIELDS: &[&str] = &[
+    "id",
+    "title",
+    "channel",
+    "uploader",
+    "channel_id",
+    "upload_date",
+    "timestamp",
+    "ext",
+    "extractor",
+    "availability",
+    "live_status",
+    "view_count",
+    "like_count",
+    "comment_count",
+    "age_limit",
+    "section_title",
+    "section_number",
+    "section_start",
+    "section_end",
+];
+
+/// The device names reserved by Windows, with or without an extension.
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// A part of a parsed template.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Segment {
+    /// Literal text, which may contain path separators.
+    Literal(String),
+    /// A field, with an optional format specification.
+    Field {
+        /// The name of the field.
+        name: String,
+        /// The format specification, after the colon.
+        spec: Option<String>,
+    },
+    /// A path separator.
+    Separator,
+}
+
+/// An output filename template.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OutputTemplate {
+    /// The template, as given by the user.
+    template: String,
+    /// The parsed template.
+    segments: Vec<Segment>,
+    /// The maximum length of each path component, in bytes.
+    max_length: usize,
+}
+
+impl OutputTemplate {
+    /// Parses a new output template.
+    ///
+    /// # Arguments
+    ///
+    /// * `template` - The template, e.g. `{channel}/{title} [{id}].{ext}`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a brace is not closed, if a field is unknown,
+    /// or if a format specification is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use yt_dlp::utils::template::OutputTemplate;
+    /// let template = OutputTemplate::new("{channel}/{upload_date:%Y-%m}/{title} [{id}].{ext}");
+    /// assert!(template.is_ok());
+    ///
+    /// let template = OutputTemplate::new("{unknown}.{ext}");
+    /// assert!(template.is_err());
+    /// ```
+    pub fn new(template: impl Into<String>) -> Result<Self> {
+        let template = template.into();
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!("Parsing output template: {}", template);
+
+        let segments = Self::parse(&template)?;
+
+        Ok(Self {
+            template,
+            segments,
+            max_length: DEFAULT_MAX_LENGTH,
+        })
+    }
+
+    /// Sets the maximum length of each path component, in bytes.
+    /// Names are truncated on character boundaries, so multi-byte text such as Hangul is never split.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_length` - The maximum length in bytes, at least 16.
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length.max(16);
+        self
+    }
+
+    /// Returns the template, as given by the user.
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// Renders the template for the given video, as a path relative to the output directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `video` - The video to take the fields from.
+    /// * `ext` - The extension of the output file, without the dot.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use yt_dlp::utils::template::OutputTemplate;
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url).await?;
+    ///
+    /// let template = OutputTemplate::new("{channel}/{title} [{id}].{ext}")?;
+    /// let path = template.render(&video, "mp4");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn render(&self, video: &Video, ext: &str) -> PathBuf {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            "Rendering output template {} for video {}",
+            self.template,
+            video.id
+        );
+
+        self.render_section(video, ext, None)
+    }
+
+    /// Renders the template for a chapter of the given video, as a path relative to the output directory.
+    /// The `section_*` fields are replaced by the chapter information.
+    ///
+    /// # Arguments
+    ///
+    /// * `video` - The video to take the fields from.
+    /// * `chapter` - The chapter to take the `section_*` fields from.
+    /// * `number` - The number of the chapter, starting at 1.
+    /// * `ext` - The extension of the output file, without the dot.
+    ///
+    /// # Examples
+    ///
+    /// ```rust, no_run
+    /// # use yt_dlp::Youtube;
+    /// # use yt_dlp::utils::template::{OutputTemplate, DEFAULT_CHAPTER_TEMPLATE};
+    /// # use std::path::PathBuf;
+    /// # use yt_dlp::fetcher::deps::Libraries;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let libraries_dir = PathBuf::from("libs");
+    /// # let output_dir = PathBuf::from("output");
+    /// # let youtube = libraries_dir.join("yt-dlp");
+    /// # let ffmpeg = libraries_dir.join("ffmpeg");
+    /// # let libraries = Libraries::new(youtube, ffmpeg);
+    /// let fetcher = Youtube::new(libraries, output_dir)?;
+    ///
+    /// let url = String::from("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+    /// let video = fetcher.fetch_video_infos(url).await?;
+    ///
+    /// let template = OutputTemplate::new(DEFAULT_CHAPTER_TEMPLATE)?;
+    /// for (index, chapter) in video.chapters.iter().enumerate() {
+    ///     let path = template.render_chapter(&video, chapter, index + 1, "mp4");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn render_chapter(
+        &self,
+        video: &Video,
+        chapter: &Chapter,
+        number: usize,
+        ext: &str,
+    ) -> PathBuf {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            "Rendering output template {} for chapter {} of video {}",
+            self.template,
+            number,
+            video.id
+        );
+
+        self.render_section(video, ext, Some((chapter, number)))
+    }
+
+    /// Renders the template, with an optional chapter and its number.
+    fn render_section(
+        &self,
+        video: &Video,
+        ext: &str,
+        section: Option<(&Chapter, usize)>,
+    ) -> PathBuf {
+        let mut components = vec![String::new()];
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => components.last_mut().unwrap().push_str(text),
+                Segment::Field { name, spec } => {
+                    let value = Self::field(video, ext, name, spec.as_deref(), section);
+                    components
+                        .last_mut()
+                        .unwrap()
+                        .push_str(&value.replace(['/', '\\'], "_"));
+                }
+                Segment::Separator => components.push(String::new()),
+            }
+        }
+
+        let last = components.len() - 1;
+        components
+            .iter()
+            .enumerate()
+            .filter(|(index, component)| *index == last || !component.trim().is_empty())
+            .map(|(index, component)| sanitize_component(component, self.max_length, index == last))
+            .collect()
+    }
+
+    /// Parses a template into segments.
+    fn parse(template: &str) -> Result<Vec<Segment>> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut field = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => field.push(c),
+                            None => {
+                                return Err(Error::Template(format!(
+                                    "unclosed brace in '{}'",
+                                    template
+                                )));
+                            }
+                        }
+                    }
+
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Self::parse_field(&field)?);
+                }
+                '}' => {
+                    return Err(Error::Template(format!(
+                        "unmatched closing brace in '{}'",
+                        template
+                    )));
+                }
+                '/' | '\\' => {
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Separator);
+                }
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        if matches!(segments.last(), None | Some(Segment::Separator)) {
+            return Err(Error::Template(format!(
+                "'{}' does not end with a file name",
+                template
+            )));
+        }
+
+        Ok(segments)
+    }
+
+    /// Parses a field, with its optional format specification.
+    fn parse_field(field: &str) -> Result<Segment> {
+        let (name, spec) = match field.split_once(':') {
+            Some((name, spec)) => (name.trim(), Some(spec.to_string())),
+            None => (field.trim(), None),
+        };
+
+        if !FIELDS.contains(&name) {
+            return Err(Error::Template(format!("unknown field '{}'", name)));
+        }
+
+        match (name, &spec) {
+            (_, None) => {}
+            ("upload_date", Some(spec)) => {
+                if StrftimeItems::new(spec).any(|item| matches!(item, Item::Error)) {
+                    return Err(Error::Template(format!("invalid date format '{}'", spec)));
+                }
+            }
+            // The number of digits, e.g. '03' for '001'
+            ("section_number", Some(spec)) => {
+                if spec.is_empty() || spec.len() > 2 || !spec.chars().all(|c| c.is_ascii_digit()) {
+                    return Err(Error::Template(format!("invalid number format '{}'", spec)));
+                }
+            }
+            _ => {
+                return Err(Error::Template(format!(
+                    "field '{}' does not accept a format",
+                    name
+                )));
+            }
+        }
+
+        Ok(Segment::Field {
+            name: name.to_string(),
+            spec,
+        })
+    }
+
+    /// Returns the value of a field for the given video, and the given chapter if any.
+    fn field(
+        video: &Video,
+        ext: &str,
+        name: &str,
+        spec: Option<&str>,
+        section: Option<(&Chapter, usize)>,
+    ) -> String {
+        let optional =
+            |value: Option<i64>| value.map_or_else(|| "NA".to_string(), |v| v.to_string());
+
+        match name {
+            "id" => video.id.clone(),
+            "title" => video.title.clone(),
+            "channel" | "uploader" => video.channel.clone(),
+            "channel_id" => video.channel_id.clone(),
+            "upload_date" => DateTime::from_timestamp(video.upload_date, 0)
+                .map(|date| date.format(spec.unwrap_or(DEFAULT_DATE_FORMAT)).to_string())
+                .unwrap_or_else(|| "NA".to_string()),
+            "timestamp" => video.upload_date.to_string(),
+            "ext" => ext.to_string(),
+            "extractor" => video.extractor_info.extractor_key.clone(),
+            "availability" => video.availability.clone(),
+            "live_status" => video.live_status.clone(),
+            "view_count" => video.view_count.to_string(),
+            "like_count" => optional(video.like_count),
+            "comment_count" => optional(video.comment_count),
+            "age_limit" => video.age_limit.to_string(),
+            "section_title" => section.map_or_else(
+                || "NA".to_string(),
+                |(chapter, number)| {
+                    if chapter.title.trim().is_empty() {
+                        format!("Chapter {}", number)
+                    } else {
+                        chapter.title.clone()
+                    }
+                },
+            ),
+            "section_number" => section.map_or_else(
+                || "NA".to_string(),
+                |(_, number)| {
+                    let width = spec.and_then(|spec| spec.parse().ok()).unwrap_or(0);
+                    format!("{:0width$}", number, width = width)
+                },
+            ),
+            "section_start" => optional(section.map(|(chapter, _)| chapter.start_time.0 as i64)),
+            "section_end" => optional(section.map(|(chapter, _)| chapter.end_time.0 as i64)),
+            _ => "NA".to_string(),
+        }
+    }
+}
+
+/// Makes a single path component safe on every platform.
+/// Forbidden characters are replaced, trailing dots and spaces are removed,
+/// the name is truncated to `max_length` bytes, and reserved names are prefixed.
+///
+/// # Arguments
+///
+/// * `component` - The path component to sanitize.
+/// * `max_length` - The maximum length of the component, in bytes.
+/// * `keep_extension` - Whether the extension must be kept when truncating.
+///
+/// # Examples
+///
+/// ```rust
+/// # use yt_dlp::utils::template::sanitize_component;
+/// assert_eq!(sanitize_component("What? Why: Now", 200, true), "What_ Why_ Now");
+/// assert_eq!(sanitize_component("con.mp4", 200, true), "_con.mp4");
+/// assert_eq!(sanitize_component("가나다라마바사.mp4", 13, true), "가나다.mp4");
+/// ```
+pub fn sanitize_component(component: &str, max_length: usize, keep_extension: bool) -> String {
+    let cleaned: String = component
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            '\n' | '\r' | '\t' => ' ',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let cleaned = cleaned.trim();
+
+    // Keep a short extension intact, and truncate the stem only
+    let (stem, extension) = match cleaned.rfind('.') {
+        Some(dot) if keep_extension && dot > 0 && cleaned.len() - dot <= 10 => {
+            cleaned.split_at(dot)
+        }
+        _ => (cleaned, ""),
+    };
+
+    let stem = truncate_utf8(stem, max_length.saturating_sub(extension.len()));
+    let stem = stem.trim_end_matches(['.', ' ']);
+
+    let mut name = format!("{}{}", stem, extension);
+    if name.is_empty() || name == "." || name == ".." {
+        name = format!("_{}", name);
+    }
+
+    let base = name.split('.').next().unwrap_or_default().to_uppercase();
+    if RESERVED_NAMES.contains(&base.as_str()) {
+        name.insert(0, '_');
+    }
+
+    name
+}
+
+/// Truncates a string to at most `max_length` bytes, on a character boundary.
+/// Combining characters, such as the medial and final jamo of decomposed Hangul,
+/// are never separated from the character they belong to.
+///
+/// # Arguments
+///
+/// * `text` - The text to truncate.
+/// * `max_length` - The maximum length, in bytes.
+///
+/// # Examples
+///
+/// ```rust
+/// # use yt_dlp::utils::template::truncate_utf8;
+/// assert_eq!(truncate_utf8("안녕하세요", 7), "안녕");
+/// // Decomposed "한" is three jamo, which are dropped together
+/// assert_eq!(truncate_utf8("가\u{1112}\u{1161}\u{11AB}", 7), "가");
+/// ```
+pub fn truncate_utf8(text: &str, max_length: usize) -> &str {
+    if text.len() <= max_length {
+        return text;
+    }
+
+    let mut end = max_length;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    // Move back while the first dropped character combines with the previous one
+    while end > 0 {
+        match text[end..].chars().next() {
+            Some(c) if is_combining(c) => {
+                end -= text[..end].chars().next_back().map_or(0, char::len_utf8);
+            }
+            _ => break,
+        }
+    }
+
+    // A trailing zero width joiner would be meaningless
+    text[..end].trim_end_matches('\u{200D}')
+}
+
+/// Checks if a character combines with the previous one.
+fn is_combining(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'
+        | '\u{1160}'..='\u{11FF}'
+        | '\u{1AB0}'..='\u{1AFF}'
+        | '\u{200D}'
+        | '\u{20D0}'..='\u{20FF}'
+        | '\u{302E}'..='\u{302F}'
+        | '\u{3099}'..='\u{309A}'
+        | '\u{D7B0}'..='\u{D7FF}'
+        | '\u{FE00}'..='\u{FE0F}'
+        | '\u{FE20}'..='\u{FE2F}'
+    )
+}
+
+// Implementation of the Display trait for OutputTemplate
+impl fmt::Display for OutputTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "OutputTemplate(template = \"{}\", max_length = {})",
+            self.template, self.max_length
+        )
+    }
+}
@@ -0,0 +1,334 @@
+//! A minimal XML reader, for the srv and TTML caption formats and DASH manifests.
+//!
+//! Only elements, attributes and text are read: comments, processing instructions and
+//! declarations are skipped. Namespace prefixes are removed from names.
+
+use crate::error::{Error, Result};
+
+/// An XML event.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum XmlEvent<'a> {
+    /// An opening or self-closing tag.
+    Start {
+        /// The local name of the element, e.g. 'p'.
+        name: &'a str,
+        /// The attributes, by local name, with decoded values.
+        attributes: Vec<(&'a str, String)>,
+        /// If the tag is self-closing, e.g. '<br/>'.
+        empty: bool,
+    },
+    /// A closing tag.
+    End {
+        /// The local name of the element.
+        name: &'a str,
+    },
+    /// Decoded text between tags.
+    Text(String),
+}
+
+impl XmlEvent<'_> {
+    /// Returns the value of an attribute of a start tag.
+    pub(crate) fn attribute(&self, key: &str) -> Option<&str> {
+        match self {
+            XmlEvent::Start { attributes, .. } => attributes
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| value.as_str()),
+            _ => None,
+        }
+    }
+}
+
+/// Reads an XML document into a list of events.
+pub(crate) fn parse(content: &str) -> Result<Vec<XmlEvent<'_>>> {
+    let mut events = Vec::new();
+    let mut rest = content;
+
+    while !rest.is_empty() {
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            let end = comment.find("-->").ok_or(unclosed("comment"))?;
+            rest = &comment[end + 3..];
+            continue;
+        }
+
+        if let Some(data) = rest.strip_prefix("<![CDATA[") {
+            let end = data.find("]]>").ok_or(unclosed("CDATA section"))?;
+            events.push(XmlEvent::Text(data[..end].to_string()));
+            rest = &data[end + 3..];
+            continue;
+        }
+
+        if rest.starts_with("<?") || rest.starts_with("<!") {
+            let end = rest.find('>').ok_or(unclosed("declaration"))?;
+            rest = &rest[end + 1..];
+            continue;
+        }
+
+        if rest.starts_with('<') {
+            let end = find_tag_end(rest).ok_or(unclosed("tag"))?;
+            let tag = &rest[1..end];
+            rest = &rest[end + 1..];
+
+            if let Some(name) = tag.strip_prefix('/') {
+                events.push(XmlEvent::End {
+                    name: local_name(name.trim()),
+                });
+                continue;
+            }
+
+            let empty = tag.ends_with('/');
+            let tag = tag.trim_end_matches('/').trim();
+            let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+
+            events.push(XmlEvent::Start {
+                name: local_name(&tag[..name_end]),
+                attributes: parse_attributes(&tag[name_end..])?,
+                empty,
+            });
+            continue;
+        }
+
+        let end = rest.find('<').unwrap_or(rest.len());
+        events.push(XmlEvent::Text(decode_entities(&rest[..end])));
+        rest = &rest[end..];
+    }
+
+    Ok(events)
+}
+
+/// Collects the elements with the given name, with their text content.
+/// The text of nested elements is included, and line breaks ('<br/>') become '\n'.
+pub(crate) fn elements<'a>(
+    events: &'a [XmlEvent<'a>],
+    element: &str,
+) -> Vec<(&'a XmlEvent<'a>, String)> {
+    let mut elements = Vec::new();
+    let mut current: Option<(&XmlEvent, String, usize)> = None;
+
+    for event in events {
+        match (event, &mut current) {
+            (XmlEvent::Start { name, empty, .. }, None) if *name == element => {
+                if *empty {
+                    elements.push((event, String::new()));
+                } else {
+                    current = Some((event, String::new(), 0));
+                }
+            }
+            (XmlEvent::Start { name, empty, .. }, Some((_, text, depth))) => {
+                if *name == "br" {
+                    text.push('\n');
+                } else if !*empty {
+                    *depth += 1;
+                }
+            }
+            (XmlEvent::Text(content), Some((_, text, _))) => text.push_str(content),
+            (XmlEvent::End { .. }, Some((_, _, depth))) if *depth > 0 => *depth -= 1,
+            (XmlEvent::End { .. }, Some(_)) => {
+                if let Some((start, text, _)) = current.take() {
+                    elements.push((start, text));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    elements
+}
+
+/// An element of an XML document, with its children.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct XmlNode<'a> {
+    /// The local name of the element.
+    pub(crate) name: &'a str,
+    /// The attributes, by local name, with decoded values.
+    pub(crate) attributes: Vec<(&'a str, String)>,
+    /// The child elements, in document order.
+    pub(crate) children: Vec<XmlNode<'a>>,
+    /// The text directly inside the element, trimmed.
+    pub(crate) text: String,
+}
+
+impl<'a> XmlNode<'a> {
+    /// Returns the value of an attribute.
+    pub(crate) fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(name, _)| *name == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the first child element with the given name.
+    pub(crate) fn child(&self, name: &str) -> Option<&XmlNode<'a>> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    /// Returns the child elements with the given name.
+    pub(crate) fn children_named<'s>(
+        &'s self,
+        name: &'s str,
+    ) -> impl Iterator<Item = &'s XmlNode<'a>> + 's {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+}
+
+/// Builds the tree of the root element of a list of events.
+pub(crate) fn tree<'a>(events: &[XmlEvent<'a>]) -> Result<XmlNode<'a>> {
+    let mut stack: Vec<XmlNode<'a>> = Vec::new();
+
+    for event in events {
+        match event {
+            XmlEvent::Start {
+                name,
+                attributes,
+                empty,
+            } => {
+                let node = XmlNode {
+                    name,
+                    attributes: attributes.clone(),
+                    children: Vec::new(),
+                    text: String::new(),
+                };
+
+                if !*empty {
+                    stack.push(node);
+                } else if let Some(parent) = stack.last_mut() {
+                    parent.children.push(node);
+                } else {
+                    return Ok(node);
+                }
+            }
+            XmlEvent::End { .. } => {
+                let mut node = stack
+                    .pop()
+                    .ok_or(Error::Xml("unexpected closing tag".to_string()))?;
+                node.text = node.text.trim().to_string();
+
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(node),
+                    None => return Ok(node),
+                }
+            }
+            XmlEvent::Text(text) => {
+                if let Some(node) = stack.last_mut() {
+                    node.text.push_str(text);
+                }
+            }
+        }
+    }
+
+    Err(unclosed("root element"))
+}
+
+/// Decodes the XML entities of a text, e.g. '&amp;' or '&#39;'. Unknown entities are kept.
+pub(crate) fn decode_entities(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest
+            .find(';')
+            .filter(|&end| end <= 10)
+            .map(|end| (&rest[1..end], end));
+
+        match entity.and_then(|(entity, end)| decode_entity(entity).map(|c| (c, end))) {
+            Some((character, end)) => {
+                output.extend(character);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                output.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+
+    output
+}
+
+/// Decodes a single entity, without its '&' and ';'.
+/// Returns None for unknown entities, and no character for direction marks.
+fn decode_entity(entity: &str) -> Option<Option<char>> {
+    let character = match entity {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "lrm" | "rlm" => return Some(None),
+        _ => {
+            let code = match entity.strip_prefix("#x").or(entity.strip_prefix("#X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => entity.strip_prefix('#')?.parse().ok()?,
+            };
+            char::from_u32(code)?
+        }
+    };
+
+    Some(Some(character))
+}
+
+/// Parses the attributes of a tag, e.g. ' t="100" d="200"'.
+fn parse_attributes(mut rest: &str) -> Result<Vec<(&str, String)>> {
+    let mut attributes = Vec::new();
+
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            return Ok(attributes);
+        }
+
+        let name_end = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let name = &rest[..name_end];
+        rest = rest[name_end..].trim_start();
+
+        // Attributes without value are tolerated
+        let Some(value) = rest.strip_prefix('=') else {
+            attributes.push((local_name(name), String::new()));
+            continue;
+        };
+
+        let value = value.trim_start();
+        let quote = value
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')
+            .ok_or(Error::Xml(format!("unquoted value for attribute {}", name)))?;
+        let end = value[1..].find(quote).ok_or(unclosed("attribute value"))?;
+
+        attributes.push((local_name(name), decode_entities(&value[1..end + 1])));
+        rest = &value[end + 2..];
+    }
+}
+
+/// Finds the closing '>' of a tag, ignoring the ones in attribute values.
+fn find_tag_end(tag: &str) -> Option<usize> {
+    let mut quote = None;
+
+    for (index, character) in tag.char_indices() {
+        match (quote, character) {
+            (None, '"' | '\'') => quote = Some(character),
+            (Some(open), _) if open == character => quote = None,
+            (None, '>') => return Some(index),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Removes the namespace prefix of a name, e.g. 'tts:color' becomes 'color'.
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+/// Builds the error of an unclosed construct.
+fn unclosed(construct: &str) -> Error {
+    Error::Xml(format!("unclosed {}", construct))
+}
@@ -0,0 +1,508 @@
+//! Downloads of HLS playlists and DASH manifests, served with their fragments by a local HTTP server.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use yt_dlp::fetcher::manifest::{ManifestFetcher, ManifestKind};
+
+/// A file served by the fixture server.
+#[derive(Clone, Default)]
+struct Route {
+    /// The successive contents of the file, the last one being served again, e.g. for live playlists.
+    bodies: Vec<Vec<u8>>,
+    /// The time before answering each request.
+    delay: Duration,
+    /// The number of requests answered with '503 Service Unavailable' before the file is served.
+    failures: usize,
+}
+
+impl Route {
+    /// Creates a route serving the given content.
+    fn new(body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            bodies: vec![body.into()],
+            ..Self::default()
+        }
+    }
+
+    /// Serves the given content from the next request on.
+    fn then(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.bodies.push(body.into());
+        self
+    }
+
+    /// Waits before answering each request.
+    fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Fails the first requests.
+    fn with_failures(mut self, failures: usize) -> Self {
+        self.failures = failures;
+        self
+    }
+}
+
+/// The state of the fixture server, shared by its connections.
+#[derive(Default)]
+struct State {
+    /// The served files, by path.
+    routes: HashMap<String, Route>,
+    /// The number of requests of each path.
+    requests: Mutex<HashMap<String, usize>>,
+    /// The number of requests being answered.
+    in_flight: AtomicUsize,
+    /// The highest number of requests answered at the same time.
+    max_in_flight: AtomicUsize,
+}
+
+/// A local HTTP/1.1 server, serving fixture manifests and fragments with keep-alive and range requests.
+struct FixtureServer {
+    /// The URL of the server, without trailing slash.
+    base_url: String,
+    /// The state of the server.
+    state: Arc<State>,
+}
+
+impl FixtureServer {
+    /// Starts a server serving the given files.
+    async fn start(routes: impl IntoIterator<Item = (&'static str, Route)>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(State {
+            routes: routes
+                .into_iter()
+                .map(|(path, route)| (path.to_string(), route))
+                .collect(),
+            ..State::default()
+        });
+
+        let server_state = state.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handle(stream, server_state.clone()));
+            }
+        });
+
+        Self { base_url, state }
+    }
+
+    /// Returns the URL of a served file.
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Returns the number of requests of a path.
+    fn requests(&self, path: &str) -> usize {
+        let requests = self.state.requests.lock().unwrap();
+        requests.get(path).copied().unwrap_or(0)
+    }
+
+    /// Returns the highest number of requests answered at the same time.
+    fn max_in_flight(&self) -> usize {
+        self.state.max_in_flight.load(Ordering::SeqCst)
+    }
+}
+
+/// Answers the requests of a connection until it is closed.
+async fn handle(stream: TcpStream, state: Arc<State>) -> std::io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+
+    loop {
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line).await? == 0 {
+            return Ok(());
+        }
+
+        let mut range = None;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).await? == 0 {
+                return Ok(());
+            }
+
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':')
+                && name.eq_ignore_ascii_case("range")
+            {
+                range = parse_range(value.trim());
+            }
+        }
+
+        let path = request_line
+            .split_whitespace()
+            .nth(1)
+            .unwrap_or("/")
+            .to_string();
+        let response = respond(&state, &path, range).await;
+        writer.write_all(&response).await?;
+    }
+}
+
+/// Builds the response to a request of a path, counting it.
+async fn respond(state: &State, path: &str, range: Option<(usize, usize)>) -> Vec<u8> {
+    let Some(route) = state.routes.get(path) else {
+        return b"HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\n\r\n".to_vec();
+    };
+
+    let request = {
+        let mut requests = state.requests.lock().unwrap();
+        let count = requests.entry(path.to_string()).or_insert(0);
+        *count += 1;
+        *count - 1
+    };
+
+    let in_flight = state.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+    state.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+    tokio::time::sleep(route.delay).await;
+    state.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+    if request < route.failures {
+        return b"HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\n\r\n".to_vec();
+    }
+
+    let body = &route.bodies[(request - route.failures).min(route.bodies.len() - 1)];
+    let (head, content) = match range {
+        Some((start, end)) => {
+            let end = end.min(body.len() - 1);
+            let head = format!(
+                "HTTP/1.1 206 Partial Content\r\ncontent-range: bytes {}-{}/{}\r\ncontent-length: {}\r\n\r\n",
+                start,
+                end,
+                body.len(),
+                end - start + 1
+            );
+            (head, &body[start..=end])
+        }
+        None => (
+            format!("HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n", body.len()),
+            &body[..],
+        ),
+    };
+
+    let mut response = head.into_bytes();
+    response.extend_from_slice(content);
+    response
+}
+
+/// Parses a 'bytes=start-end' range.
+fn parse_range(value: &str) -> Option<(usize, usize)> {
+    let (start, end) = value.strip_prefix("bytes=")?.split_once('-')?;
+    Some((start.parse().ok()?, end.parse().ok()?))
+}
+
+/// Returns the content of a fragment, distinct from the others.
+fn fragment(name: &str) -> Vec<u8> {
+    format!("<{}>", name).repeat(64).into_bytes()
+}
+
+/// Returns a destination in the temporary directory, removed if it exists.
+fn destination(name: &str) -> PathBuf {
+    let path = std::env::temp_dir()
+        .join("yt-dlp-tests-manifest")
+        .join(name);
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[tokio::test]
+async fn hls_master_playlist_uses_the_variant_with_the_highest_bandwidth() {
+    let master = "#EXTM3U
+#EXT-X-STREAM-INF:BANDWIDTH=500000,RESOLUTION=640x360
+low/index.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=2000000,RESOLUTION=1280x720
+high/index.m3u8";
+    let media = "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXT-X-MAP:URI=\"init.mp4\"
+#EXTINF:4.0,
+1.m4s
+#EXTINF:4.0,
+2.m4s
+#EXT-X-ENDLIST";
+
+    let server = FixtureServer::start([
+        ("/hls/master.m3u8", Route::new(master)),
+        ("/hls/low/index.m3u8", Route::new(media)),
+        ("/hls/high/index.m3u8", Route::new(media)),
+        ("/hls/high/init.mp4", Route::new(fragment("init"))),
+        ("/hls/high/1.m4s", Route::new(fragment("1"))),
+        ("/hls/high/2.m4s", Route::new(fragment("2"))),
+    ])
+    .await;
+
+    let path = destination("master.mp4");
+    let download = ManifestFetcher::new(server.url("/hls/master.m3u8"))
+        .fetch_manifest(&path)
+        .await
+        .unwrap();
+
+    assert_eq!(download.kind, ManifestKind::Hls);
+    assert_eq!(download.fragments, 3);
+    assert!(!download.is_transport_stream);
+    assert_eq!(
+        tokio::fs::read(&path).await.unwrap(),
+        [fragment("init"), fragment("1"), fragment("2")].concat()
+    );
+    assert_eq!(server.requests("/hls/low/index.m3u8"), 0);
+}
+
+#[tokio::test]
+async fn hls_byte_ranges_are_downloaded_from_a_single_file() {
+    let mut stream: Vec<u8> = (0..300).map(|i| (i % 251) as u8).collect();
+    stream[0] = 0x47;
+    let playlist = "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXTINF:4.0,
+#EXT-X-BYTERANGE:100@0
+stream.ts
+#EXTINF:4.0,
+#EXT-X-BYTERANGE:50
+stream.ts
+#EXTINF:4.0,
+#EXT-X-BYTERANGE:30@200
+stream.ts
+#EXT-X-ENDLIST";
+
+    let server = FixtureServer::start([
+        ("/hls/index.m3u8", Route::new(playlist)),
+        ("/hls/stream.ts", Route::new(stream.clone())),
+    ])
+    .await;
+
+    let path = destination("ranges.ts");
+    let download = ManifestFetcher::new(server.url("/hls/index.m3u8"))
+        .fetch_manifest(&path)
+        .await
+        .unwrap();
+
+    assert_eq!(download.fragments, 3);
+    assert_eq!(download.size, 180);
+    assert!(download.is_transport_stream);
+    assert_eq!(
+        tokio::fs::read(&path).await.unwrap(),
+        [&stream[0..100], &stream[100..150], &stream[200..230]].concat()
+    );
+    assert_eq!(server.requests("/hls/stream.ts"), 3);
+}
+
+#[tokio::test]
+async fn fragments_are_downloaded_in_parallel_and_written_in_order() {
+    const NAMES: [&str; 8] = ["1", "2", "3", "4", "5", "6", "7", "8"];
+    const PATHS: [&str; 8] = [
+        "/hls/1.ts",
+        "/hls/2.ts",
+        "/hls/3.ts",
+        "/hls/4.ts",
+        "/hls/5.ts",
+        "/hls/6.ts",
+        "/hls/7.ts",
+        "/hls/8.ts",
+    ];
+    let mut playlist = "#EXTM3U\n#EXT-X-TARGETDURATION:2\n".to_string();
+    for name in NAMES {
+        playlist.push_str(&format!("#EXTINF:2.0,\n{}.ts\n", name));
+    }
+    playlist.push_str("#EXT-X-ENDLIST\n");
+
+    // The first fragments are the slowest, so that they complete after the next ones
+    let mut routes = vec![("/hls/index.m3u8", Route::new(playlist))];
+    for (index, (name, path)) in NAMES.iter().zip(PATHS).enumerate() {
+        let delay = Duration::from_millis(40 * (NAMES.len() - index) as u64);
+        routes.push((path, Route::new(fragment(name)).with_delay(delay)));
+    }
+    let server = FixtureServer::start(routes).await;
+
+    let path = destination("parallel.ts");
+    let download = ManifestFetcher::new(server.url("/hls/index.m3u8"))
+        .with_parallel_fragments(4)
+        .fetch_manifest(&path)
+        .await
+        .unwrap();
+
+    assert_eq!(download.fragments, NAMES.len());
+    assert_eq!(
+        tokio::fs::read(&path).await.unwrap(),
+        NAMES.map(fragment).concat()
+    );
+    assert!(server.max_in_flight() > 1);
+    assert!(server.max_in_flight() <= 4);
+}
+
+#[tokio::test]
+async fn failed_fragments_are_retried() {
+    let playlist = "#EXTM3U
+#EXT-X-TARGETDURATION:2
+#EXTINF:2.0,
+1.ts
+#EXTINF:2.0,
+2.ts
+#EXT-X-ENDLIST";
+
+    let server = FixtureServer::start([
+        ("/hls/index.m3u8", Route::new(playlist)),
+        ("/hls/1.ts", Route::new(fragment("1"))),
+        ("/hls/2.ts", Route::new(fragment("2")).with_failures(2)),
+    ])
+    .await;
+
+    let path = destination("retried.ts");
+    ManifestFetcher::new(server.url("/hls/index.m3u8"))
+        .with_retry_attempts(3)
+        .fetch_manifest(&path)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        tokio::fs::read(&path).await.unwrap(),
+        [fragment("1"), fragment("2")].concat()
+    );
+    assert_eq!(server.requests("/hls/2.ts"), 3);
+}
+
+#[tokio::test]
+async fn fragments_failing_every_attempt_fail_the_download() {
+    let playlist = "#EXTM3U
+#EXT-X-TARGETDURATION:2
+#EXTINF:2.0,
+1.ts
+#EXT-X-ENDLIST";
+
+    let server = FixtureServer::start([
+        ("/hls/index.m3u8", Route::new(playlist)),
+        ("/hls/1.ts", Route::new(fragment("1")).with_failures(5)),
+    ])
+    .await;
+
+    let result = ManifestFetcher::new(server.url("/hls/index.m3u8"))
+        .with_retry_attempts(2)
+        .fetch_manifest(destination("failed.ts"))
+        .await;
+
+    assert!(result.is_err());
+    assert_eq!(server.requests("/hls/1.ts"), 2);
+}
+
+#[tokio::test]
+async fn dash_manifest_downloads_the_wanted_representation() {
+    let manifest = r#"<?xml version="1.0"?>
+<MPD xmlns="urn:mpeg:dash:schema:mpd:2011" type="static" mediaPresentationDuration="PT10S">
+  <Period>
+    <AdaptationSet mimeType="video/mp4">
+      <SegmentTemplate initialization="$RepresentationID$/init.mp4" media="$RepresentationID$/$Number%03d$.m4s" duration="4"/>
+      <Representation id="720p" bandwidth="2000000"/>
+      <Representation id="360p" bandwidth="500000"/>
+    </AdaptationSet>
+  </Period>
+</MPD>"#;
+
+    let server = FixtureServer::start([
+        ("/dash/manifest.mpd", Route::new(manifest)),
+        ("/dash/360p/init.mp4", Route::new(fragment("init"))),
+        ("/dash/360p/001.m4s", Route::new(fragment("1"))),
+        ("/dash/360p/002.m4s", Route::new(fragment("2"))),
+        ("/dash/360p/003.m4s", Route::new(fragment("3"))),
+    ])
+    .await;
+
+    let path = destination("dash.mp4");
+    let download = ManifestFetcher::new(server.url("/dash/manifest.mpd"))
+        .with_representation("360p")
+        .fetch_manifest(&path)
+        .await
+        .unwrap();
+
+    assert_eq!(download.kind, ManifestKind::Dash);
+    assert_eq!(download.fragments, 4);
+    assert_eq!(
+        tokio::fs::read(&path).await.unwrap(),
+        [
+            fragment("init"),
+            fragment("1"),
+            fragment("2"),
+            fragment("3")
+        ]
+        .concat()
+    );
+}
+
+/// The live playlist, before and after a new fragment is added and the stream ends.
+fn live_playlist() -> Route {
+    Route::new(
+        "#EXTM3U
+#EXT-X-TARGETDURATION:1
+#EXT-X-MEDIA-SEQUENCE:0
+#EXTINF:1.0,
+0.ts
+#EXTINF:1.0,
+1.ts",
+    )
+    .then(
+        "#EXTM3U
+#EXT-X-TARGETDURATION:1
+#EXT-X-MEDIA-SEQUENCE:1
+#EXTINF:1.0,
+1.ts
+#EXTINF:1.0,
+2.ts
+#EXT-X-ENDLIST",
+    )
+}
+
+#[tokio::test]
+async fn live_recordings_reload_the_playlist_until_it_ends() {
+    let server = FixtureServer::start([
+        ("/live/index.m3u8", live_playlist()),
+        ("/live/0.ts", Route::new(fragment("0"))),
+        ("/live/1.ts", Route::new(fragment("1"))),
+        ("/live/2.ts", Route::new(fragment("2"))),
+    ])
+    .await;
+
+    let path = destination("live-from-start.ts");
+    let download = ManifestFetcher::new(server.url("/live/index.m3u8"))
+        .record_live(&path, true, None)
+        .await
+        .unwrap();
+
+    assert_eq!(download.fragments, 3);
+    assert_eq!(
+        tokio::fs::read(&path).await.unwrap(),
+        [fragment("0"), fragment("1"), fragment("2")].concat()
+    );
+    assert_eq!(server.requests("/live/index.m3u8"), 2);
+    assert_eq!(server.requests("/live/1.ts"), 1);
+}
+
+#[tokio::test]
+async fn live_recordings_start_from_the_latest_fragment() {
+    let server = FixtureServer::start([
+        ("/live/index.m3u8", live_playlist()),
+        ("/live/0.ts", Route::new(fragment("0"))),
+        ("/live/1.ts", Route::new(fragment("1"))),
+        ("/live/2.ts", Route::new(fragment("2"))),
+    ])
+    .await;
+
+    let path = destination("live-latest.ts");
+    let download = ManifestFetcher::new(server.url("/live/index.m3u8"))
+        .record_live(&path, false, None)
+        .await
+        .unwrap();
+
+    assert_eq!(download.fragments, 2);
+    assert_eq!(
+        tokio::fs::read(&path).await.unwrap(),
+        [fragment("1"), fragment("2")].concat()
+    );
+    assert_eq!(server.requests("/live/0.ts"), 0);
+}
@@ -0,0 +1,79 @@
+//! Classification of the errors reported by 'yt-dlp', against a corpus of captured stderr samples.
+
+use yt_dlp::error::Error;
+
+/// The captured stderr samples, each following a '### <variant> <retryable|permanent>' header.
+const CORPUS: &str = include_str!("fixtures/yt_dlp_stderr.txt");
+
+/// A captured stderr sample, with its expected classification.
+struct Sample {
+    /// The expected variant of the error, or 'None' if the stderr reports no error.
+    variant: &'static str,
+    /// If the error is expected to be retryable, or None if the stderr reports no error.
+    retryable: Option<bool>,
+    /// The stderr of the process.
+    stderr: &'static str,
+}
+
+/// Parses the samples of the corpus, skipping its description.
+fn samples() -> Vec<Sample> {
+    CORPUS
+        .split("\n### ")
+        .skip(1)
+        .map(|block| {
+            let (header, stderr) = block.split_once('\n').unwrap_or((block, ""));
+            let mut words = header.split_whitespace();
+            let variant = words.next().expect("sample without variant");
+            let retryable = words.next().map(|word| match word {
+                "retryable" => true,
+                "permanent" => false,
+                _ => panic!("invalid retryability: {}", word),
+            });
+
+            Sample {
+                variant,
+                retryable,
+                stderr: stderr.trim_end(),
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn stderr_samples_are_classified() {
+    let samples = samples();
+    assert!(samples.len() >= 20);
+
+    for sample in samples {
+        match Error::from_yt_dlp_stderr(sample.stderr) {
+            Some(error) => {
+                assert!(
+                    format!("{:?}", error).starts_with(sample.variant),
+                    "{} => {:?}",
+                    sample.stderr,
+                    error
+                );
+                assert_eq!(
+                    Some(error.is_retryable()),
+                    sample.retryable,
+                    "{} => {:?}",
+                    sample.stderr,
+                    error
+                );
+            }
+            None => assert_eq!(sample.variant, "None", "{}", sample.stderr),
+        }
+    }
+}
+
+#[test]
+fn the_last_error_line_is_classified() {
+    let stderr = "ERROR: [youtube] dQw4w9WgXcQ: Unable to download API page: HTTP Error 503: Service Unavailable\n\
+        WARNING: [youtube] dQw4w9WgXcQ: Retrying with another client\n\
+        ERROR: [youtube] dQw4w9WgXcQ: Private video. Sign in if you've been granted access to this video";
+
+    assert!(matches!(
+        Error::from_yt_dlp_stderr(stderr),
+        Some(Error::Private(_))
+    ));
+}
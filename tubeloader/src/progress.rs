@@ -0,0 +1,126 @@
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// 다운로드 스트림 종류
+#[derive(Debug, Clone, Copy)]
+pub enum StreamKind {
+    Video,
+    Audio,
+}
+
+/// 전체 다운로드 진행률 (영상별 진행률 바 + 전체 합계 바)
+pub struct BatchProgress {
+    multi: MultiProgress,
+    total: ProgressBar,
+    video_count: usize,
+    finished: AtomicUsize,
+}
+
+impl BatchProgress {
+    pub fn new(video_count: usize) -> Self {
+        let multi = MultiProgress::new();
+        let total = multi.add(ProgressBar::new(0));
+        total.set_style(
+            ProgressStyle::default_bar()
+                .template("{prefix:.bold} [{bar:30.green/white}] {bytes}/{total_bytes} {binary_bytes_per_sec} ETA {eta} {msg}")
+                .unwrap()
+                .progress_chars("#>-"),
+        );
+        total.set_prefix("전체");
+        total.set_message(format!("완료 0/{}", video_count));
+
+        Self {
+            multi,
+            total,
+            video_count,
+            finished: AtomicUsize::new(0),
+        }
+    }
+
+    /// 진행률 바를 깨뜨리지 않고 한 줄 출력
+    pub fn println(&self, message: impl AsRef<str>) {
+        self.multi.suspend(|| println!("{}", message.as_ref()));
+    }
+
+    /// 영상 하나의 진행률 바 추가
+    pub fn add_video(&self, index: usize, title: &str) -> Arc<VideoProgress> {
+        let bar = self.multi.insert_before(&self.total, ProgressBar::new(0));
+        bar.set_style(
+            ProgressStyle::default_bar()
+                .template("{msg:30!} [{bar:30.cyan/blue}] {bytes}/{total_bytes} {binary_bytes_per_sec} ETA {eta}")
+                .unwrap()
+                .progress_chars("#>-"),
+        );
+        bar.set_message(format!("[{}] {}", index, title));
+
+        Arc::new(VideoProgress {
+            bar,
+            total: self.total.clone(),
+            downloaded: [AtomicU64::new(0), AtomicU64::new(0)],
+            sizes: [AtomicU64::new(0), AtomicU64::new(0)],
+        })
+    }
+
+    /// 영상 하나의 처리가 끝났음을 기록 (성공, 실패, 건너뜀 모두)
+    pub fn finish_one(&self) {
+        let finished = self.finished.fetch_add(1, Ordering::SeqCst) + 1;
+        self.total.set_message(format!("완료 {}/{}", finished, self.video_count));
+    }
+
+    /// 전체 진행률 바 종료
+    pub fn finish(&self) {
+        self.total.finish();
+    }
+}
+
+/// 영상 하나의 진행률 (비디오와 오디오 스트림 합산)
+pub struct VideoProgress {
+    bar: ProgressBar,
+    total: ProgressBar,
+    downloaded: [AtomicU64; 2],
+    sizes: [AtomicU64; 2],
+}
+
+impl VideoProgress {
+    /// 스트림의 다운로드 바이트 수 갱신
+    pub fn update(&self, stream: StreamKind, downloaded: u64, size: u64) {
+        let slot = stream as usize;
+
+        // 병렬 세그먼트의 콜백은 순서가 뒤바뀔 수 있으므로 증가분만 반영
+        let previous_size = self.sizes[slot].fetch_max(size, Ordering::SeqCst);
+        if size > previous_size {
+            self.bar.inc_length(size - previous_size);
+            self.total.inc_length(size - previous_size);
+        }
+
+        let previous = self.downloaded[slot].fetch_max(downloaded, Ordering::SeqCst);
+        if downloaded > previous {
+            self.bar.inc(downloaded - previous);
+            self.total.inc(downloaded - previous);
+        }
+    }
+
+    /// 라이브러리에 넘길 진행률 콜백 생성
+    pub fn callback(self: &Arc<Self>, stream: StreamKind) -> impl Fn(u64, u64) + Send + Sync + 'static {
+        let progress = Arc::clone(self);
+        move |downloaded, size| progress.update(stream, downloaded, size)
+    }
+
+    /// 다운로드 완료 후 바 제거
+    pub fn finish(&self) {
+        self.bar.finish_and_clear();
+    }
+
+    /// 실패한 시도의 바이트 수를 전체 합계에서 빼고 바 제거 (재시도 시 중복 집계 방지)
+    pub fn discard(&self) {
+        let downloaded: u64 = self.downloaded.iter().map(|d| d.load(Ordering::SeqCst)).sum();
+        let size: u64 = self.sizes.iter().map(|s| s.load(Ordering::SeqCst)).sum();
+
+        self.total.set_position(self.total.position().saturating_sub(downloaded));
+        if let Some(length) = self.total.length() {
+            self.total.set_length(length.saturating_sub(size));
+        }
+        self.bar.finish_and_clear();
+    }
+}